@@ -43,6 +43,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string())?;
     let mut dialogue_box = two_d::DialogueBox::new();
+    // Frame with fixed corners, only its edges and center grow with the text box
+    let mut frame = two_d::NineSlice::load(&texture_creator, std::path::Path::new("test_assets/dialogue_box-removebg.png"), 32, 32, 32, 32)?;
+    frame.set_region(sdl2::rect::Rect::new(112, 36, 584, 206));
+    dialogue_box.set_background(frame);
     // Load a font:
     let font_path = std::path::Path::new("test_assets/ARIALUNI.TTF");
     let font_size = 24;
@@ -41,51 +41,23 @@ fn enemy_collision_check(enemies: &[Enemy], current_index: usize, new_position:
 
 fn generate_level(width: usize, height: usize, player_pos: (usize, usize), door_pos: (usize, usize)) -> (Vec<Vec<u32>>, Vec<(usize, usize)>, (usize, usize)) {
     let mut rng = rand::thread_rng();
-    let mut grid = vec![vec![0; width]; height];  // Initialize grid with empty spaces
-    let mut spawn_points = Vec::new();
-    
-    // Set the perimeter walls
-    for x in 0..width {
-        grid[0][x] = 2;
-        grid[height-1][x] = 2;
-    }
-    for y in 0..height {
-        grid[y][0] = 2;
-        grid[y][width-1] = 2;
-    }
 
-    // Set the door position
-    grid[door_pos.0][door_pos.1] = 0;
-    
-    // Fill the interior with random walls and obstacles, avoiding the player and door positions
-    for y in 1..(height - 1) {
-        for x in 1..(width - 1) {
-            if (x, y) != player_pos && (x, y) != door_pos {
-                grid[y][x] = if rng.gen_bool(0.2) { 2 } else { 0 };  // 20% chance of a wall, adjust as needed
-            }
-        }
-    }
-
-    // Collect valid spawn points
-    for y in 1..(height - 1) {
-        for x in 1..(width - 1) {
-            if (x, y) != player_pos && (x, y) != door_pos && grid[y][x] == 0 {
-                spawn_points.push((x, y));
-            }
-        }
-    }
+    // Carve a connected cave starting where the player stands
+    let mut walk = two_d::procgen::DrunkardWalk::new(width, height, rng.gen());
+    walk.set_fill_ratio(0.6);
+    walk.set_start(player_pos.0, player_pos.1);
+    let level = walk.generate();
+    let mut grid = level.grid.clone();
 
-    // Determine a random position for the ladder
-    let ladder_pos = {
-        let mut pos;
-        loop {
-            pos = (rng.gen_range(1..width - 1), rng.gen_range(1..height - 1));
-            if pos != player_pos && pos != door_pos && grid[pos.1][pos.0] == 0 {
-                break;
-            }
-        }
-        pos
-    };
+    // Set the door position
+    grid[door_pos.1][door_pos.0] = 0;
+
+    // Ladder as far from the player as possible, enemies a few steps away from them
+    let ladder_pos = level.place_exit().unwrap_or(player_pos);
+    let spawn_points: Vec<(usize, usize)> = level.place_spawns(width * height, 3, rng.gen())
+        .into_iter()
+        .filter(|&pos| pos != ladder_pos && pos != door_pos)
+        .collect();
     grid[ladder_pos.1][ladder_pos.0] = 3;
 
     // grid
@@ -255,6 +227,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     player.load_texture("walk_right", std::path::Path::new("test_assets/character_walk_anim.png"), 16, 17, 150, 2)?;
     let mut player_health = 100;
     let mut last_player_attacked_time = std::time::Instant::now();
+    let mut player_was_hit = false;
 
     let mut floor = two_d::TextureManager::new(&texture_creator);
     floor.load_texture(&std::path::Path::new("test_assets/ground.png"))?;
@@ -293,20 +266,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         vec![&floor, &wall, &obstacle, &ladder],
         None,
     )?;
+    // Walls, obstacles and the ladder are described per tile id instead of hardcoded
+    let tileset = two_d::TilesetDefinition::load(std::path::Path::new("test_assets/roguelike_tiles.json"))?;
+    tile_map.set_tileset(&tileset);
+
+    let mut tile_renderer = two_d::TileChunkRenderer::new(&texture_creator, 8);
 
     let mut camera = two_d::Camera::new(nalgebra::Vector2::new(0, 0), nalgebra::Vector2::new(800, 600));
 
     let mut flip_horizontal = false;
 
-    let mut light_spot_texture = texture_creator.load_texture("test_assets/point_light.png")?;
-    let light = two_d::PointLight::new(
+    // Torch carried by the player, the walls of the dungeon cast shadows
+    let mut light = two_d::PointLight::new(
         nalgebra::Vector2::new(400.0, 300.0),
-        100.0,
-        0.6,  // Intensity: 0.0 (off) to 1.0 (full intensity)
-        two_d::Color::new(255, 255, 255)  // White color for pure light. You can change this!
+        350.0,
+        1.0,  // Intensity: 0.0 (off) to 1.0 (full intensity)
+        two_d::Color::new(255, 225, 180)  // Warm torch light
     );
-    let mut darkness_texture = texture_creator.create_texture_target(None, 800, 600)?;
-    darkness_texture.set_blend_mode(sdl2::render::BlendMode::Mod);
+    let mut occluders = two_d::Occluders::from_tile(&tile_map);
+    let mut light_map = two_d::LightMap::new(&texture_creator, 800, 600)?;
+    light_map.set_ambient(sdl2::pixels::Color::RGB(35, 35, 55)); // What stays visible in the dark
 
     // Key press state tracking
     let mut left_key_pressed = false;
@@ -477,7 +456,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 // Update camera position to follow player
                 camera.update(player.get_position());
 
-                if player_grid_position == ladder_position {
+                let on_ladder = tile_map.typed_property_at(player_grid_position.0, player_grid_position.1, "ladder")
+                    .and_then(|ladder| ladder.as_bool())
+                    .unwrap_or(false);
+                if on_ladder {
                     let (new_map, new_spawn_points, new_ladder_position) = generate_level(10, 10, player_grid_position, (1, 8));
                     ladder_position = new_ladder_position;
 
@@ -487,6 +469,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         vec![&floor, &wall, &obstacle, &ladder],
                         None,
                     )?;
+                    tile_map.set_tileset(&tileset);
+                    occluders = two_d::Occluders::from_tile(&tile_map);
+                    tile_renderer.invalidate_all();
 
                     // Clear and repopulate enemies for the new level
                     enemies.clear();
@@ -511,16 +496,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                 window.canvas.clear();
 
-                // Render tile map
-                for y in 0..tile_map.tile_map.len() {
-                    for x in 0..tile_map.tile_map[0].len() {
-                        let tile_index = tile_map.tile_map[y][x] as usize;
-                        let texture_manager = &tile_map.textures[tile_index];
-                        let rect = &two_d::Rect::new((x as i32 * TILE_SIZE as i32) as i32, (y as i32 * TILE_SIZE as i32) as i32, TILE_SIZE as u32, TILE_SIZE as u32);
-                        let transformed_rect = camera.transform_rect(rect);
-                        texture_manager.render_texture(&mut window.canvas, transformed_rect.unwrap())?;
-                    }
-                }
+                // Render tile map, only the chunks the camera can see
+                tile_renderer.render(&mut window.canvas, &tile_map, Some(&camera))?;
 
                 // Render player
                 if let Some(current_animation_tag) = &player.texture_manager_anim.current_animation {
@@ -532,7 +509,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             animated_texture.sprite_sheet.frame_height * 2
                         );
                         let transformed_player_rect = camera.transform_rect(player_rect);
-                        player.texture_manager_anim.render_texture(&mut window.canvas, transformed_player_rect.unwrap(), flip_horizontal as u32)?;
+                        // Flash white for a moment after being hit
+                        let mut draw_params = two_d::SpriteDrawParams::new();
+                        draw_params.set_flip(flip_horizontal, false);
+                        if player_was_hit {
+                            draw_params.set_flash(1.0 - last_player_attacked_time.elapsed().as_secs_f32() / 0.25);
+                        }
+                        player.texture_manager_anim.render_texture_ex(&mut window.canvas, transformed_player_rect.unwrap(), &draw_params)?;
                     }
                 }
 
@@ -624,6 +607,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         println!("{}", player_health);
                         attacked_by_enemy = true; // Mark that player was attacked
                         last_player_attacked_time = std::time::Instant::now(); // Update last attacked time
+                        player_was_hit = true;
                     }
                 }
 
@@ -638,21 +622,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     break; // Or transition to a different game state
                 }
 
-                // Render each light onto the light texture
-                window.canvas.with_texture_canvas(&mut darkness_texture, |canvas| {
-                    // Clear the texture with a semi-transparent black for darkness
-                    canvas.set_draw_color(sdl2::pixels::Color::RGBA(0, 0, 0, 150));
-                    canvas.clear();
-
-                    // Render each light onto this dark texture
-                    light.render(canvas, &mut light_spot_texture);
-                })?;
-
-                // Set blend mode to Mod for blending the light texture onto the main scene
-                // Now, set the blend mode and render the darkness_texture over the main canvas to achieve the lighting effect
-                window.canvas.set_blend_mode(sdl2::render::BlendMode::Mod);
-                window.canvas.copy(&darkness_texture, None, None)?;
-                window.canvas.set_blend_mode(sdl2::render::BlendMode::None);
+                // Move the torch with the player and light the scene, walls block the light
+                light.position = nalgebra::Vector2::new(
+                    (player.position.x + TILE_SIZE / 2) as f32,
+                    (player.position.y + TILE_SIZE / 2) as f32
+                );
+                light_map.clear();
+                light_map.add_point_light(&light, &occluders, Some(&camera));
+                light_map.render(&mut window.canvas)?;
 
                 // Inside the game loop, under GameState::Playing
                 render_minimap(&mut window.canvas, &tile_map, player_grid_position, &enemies, minimap_rect)?;
@@ -47,10 +47,10 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut event_pump = sdl_context.event_pump().unwrap();
     let mut i = 0;
 
-    let camera = two_d::Camera { 
-        position: nalgebra::Vector2::new(0, 0), 
-        size: nalgebra::Vector2::new(800, 600)
-    };
+    let camera = two_d::Camera::new(
+        nalgebra::Vector2::new(0, 0),
+        nalgebra::Vector2::new(800, 600)
+    );
 
     'running: loop {
         for event in event_pump.poll_iter() {
@@ -93,6 +93,7 @@ impl Platform {
 fn main() {
     let mut window = two_d::Window::new("Rust SDL2 Demo: Platformer", SCREEN_WIDTH, SCREEN_HEIGHT, false).unwrap();
     let mut input_handler = two_d::InputHandler::new(&window.sdl_context).unwrap();
+    let texture_creator = window.canvas.texture_creator();
 
     let mut player = Player::new(SCREEN_WIDTH as i32 / 2, SCREEN_HEIGHT as i32 - 100);
 
@@ -100,6 +101,9 @@ fn main() {
         nalgebra::Vector2::new(player.rect.x(), player.rect.y()), 
         nalgebra::Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT)
     );
+    // Smooth follow that lets the player move a little before the view catches up
+    camera.set_damping(6.0);
+    camera.set_dead_zone(Some(two_d::Rect::new(-60, -40, 120, 80)));
     
     let mut platforms = vec![
         Platform::new(0, SCREEN_HEIGHT as i32 - 50, SCREEN_WIDTH, 50), // Initial ground platform
@@ -107,6 +111,16 @@ fn main() {
 
     let mut last_platform_x = 0; // Track the last platform's X position for generation
 
+    // A lantern carried by the player, the platforms cast shadows
+    let mut lantern = two_d::PointLight::new(
+        nalgebra::Vector2::new(0.0, 0.0),
+        320.0,
+        1.0,
+        two_d::Color::new(255, 220, 170)
+    );
+    let mut light_map = two_d::LightMap::new(&texture_creator, SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+    light_map.set_ambient(sdl2::pixels::Color::RGB(40, 40, 60)); // What stays visible in the dark
+
     'running: loop {
         input_handler.poll_events(); // Poll events using InputHandler
 
@@ -144,7 +158,7 @@ fn main() {
         platforms.retain(|p| p.rect.x() + p.rect.width() as i32 > screen_left_edge);
 
         // Update the camera to follow the player
-        camera.update(nalgebra::Vector2::new(player.rect.x(), player.rect.y()));
+        camera.follow(nalgebra::Vector2::new(player.rect.x() as f32, player.rect.y() as f32), 1.0 / 60.0);
 
         window.canvas.set_draw_color(two_d::Color::new(0, 0, 0).sdl_color());
         window.canvas.clear();
@@ -159,6 +173,17 @@ fn main() {
             window.canvas.fill_rect(camera.transform_rect(&platform.rect)).unwrap();
         }
 
+        // Platforms come and go, so the occluders are rebuilt every frame
+        let platform_rects: Vec<sdl2::rect::Rect> = platforms.iter().map(|platform| *platform.rect.sdl_rect()).collect();
+        let occluders = two_d::Occluders::from_rects(&platform_rects);
+        lantern.position = nalgebra::Vector2::new(
+            (player.rect.x() + player.rect.width() as i32 / 2) as f32,
+            (player.rect.y() + player.rect.height() as i32 / 2) as f32
+        );
+        light_map.clear();
+        light_map.add_point_light(&lantern, &occluders, Some(&camera));
+        light_map.render(&mut window.canvas).unwrap();
+
         window.canvas.present();
         ::std::thread::sleep(Duration::from_millis(1000 / 60));
     }
@@ -5,6 +5,7 @@ use std::time::Instant;
 
 const WIDTH: u32 = 800;
 const HEIGHT: u32 = 600;
+const DOOR: u32 = 3;
 const MAP_WIDTH: usize = 24;
 const MAP_HEIGHT: usize = 24;
 
@@ -20,7 +21,7 @@ static WORLD_MAP: [[i32; MAP_WIDTH]; MAP_HEIGHT] = [
     [1,0,0,0,0,0,0,2,0,0,0,0,2,0,0,0,0,0,0,0,0,0,0,1],
     [1,0,0,0,0,0,0,2,0,0,0,0,2,0,0,0,0,0,0,0,0,0,0,1],
     [1,0,0,0,0,0,0,2,0,0,0,0,2,0,0,0,0,0,0,0,0,0,0,1],
-    [1,0,0,0,0,0,0,2,2,2,2,2,2,0,0,0,0,0,0,0,0,0,0,1],
+    [1,0,0,0,0,0,0,2,2,3,2,2,2,0,0,0,0,0,0,0,0,0,0,1],
     [1,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
     [1,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
     [1,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
@@ -35,101 +36,46 @@ static WORLD_MAP: [[i32; MAP_WIDTH]; MAP_HEIGHT] = [
     [1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1],
 ];
 
-fn cast_ray(player_pos: (f64, f64), player_dir: (f64, f64), camera: &two_d::Camera3D, x: u32, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) {
-    let camera_x = 2.0 * x as f64 / WIDTH as f64 - 1.0; // X-coordinate in camera space
-    let ray_dir_x = player_dir.0 + camera.plane.x * camera_x;
-    let ray_dir_y = player_dir.1 + camera.plane.y * camera_x;
-
-    // Map position of the current square
-    let mut map_x = player_pos.0 as i32;
-    let mut map_y = player_pos.1 as i32;
-
-    // Length of ray from one side to next in map
-    let delta_dist_x = (1.0 / ray_dir_x).abs();
-    let delta_dist_y = (1.0 / ray_dir_y).abs();
-
-    let mut side_dist_x;
-    let mut side_dist_y;
-
-    // Calculate step and initial sideDist
-    let step_x;
-    let step_y;
-    let perp_wall_dist;
-
-    if ray_dir_x < 0.0 {
-        step_x = -1;
-        side_dist_x = (player_pos.0 - map_x as f64) * delta_dist_x;
-    } else {
-        step_x = 1;
-        side_dist_x = (map_x as f64 + 1.0 - player_pos.0) * delta_dist_x;
-    }
-    if ray_dir_y < 0.0 {
-        step_y = -1;
-        side_dist_y = (player_pos.1 - map_y as f64) * delta_dist_y;
-    } else {
-        step_y = 1;
-        side_dist_y = (map_y as f64 + 1.0 - player_pos.1) * delta_dist_y;
-    }
-
-    // Perform DDA
-    let mut hit = 0; // Hit flag
-    let mut side = 0; // NS or EW wall
-    while hit == 0 {
-        // Jump to next map square
-        if side_dist_x < side_dist_y {
-            side_dist_x += delta_dist_x;
-            map_x += step_x;
-            side = 0;
-        } else {
-            side_dist_y += delta_dist_y;
-            map_y += step_y;
-            side = 1;
-        }
-        // Check if ray has hit a wall
-        if WORLD_MAP[map_x as usize][map_y as usize] > 0 {
-            hit = 1;
-        }
-    }
-
-    // Calculate distance to the point of impact
-    if side == 0 {
-        perp_wall_dist = (map_x as f64 - player_pos.0 + (1 - step_x) as f64 / 2.0) / ray_dir_x;
-    } else {
-        perp_wall_dist = (map_y as f64 - player_pos.1 + (1 - step_y) as f64 / 2.0) / ray_dir_y;
-    }
-
-    // Calculate height of line to draw on screen
-    let line_height = ((HEIGHT as f64 / perp_wall_dist) as i32).max(1);
-
-    // Calculate lowest and highest pixel to fill in current stripe
-    let draw_start = (-line_height / 2 + HEIGHT as i32 / 2).max(0);
-    let draw_end = (line_height / 2 + HEIGHT as i32 / 2).min(HEIGHT as i32 - 1);
-
-    // Choose wall color
-    let wall_color = match WORLD_MAP[map_x as usize][map_y as usize] {
-        1 => two_d::Color::new(255, 0, 0),
-        2 => two_d::Color::new(0, 255, 0),
-        3 => two_d::Color::new(0, 0, 255),
-        4 => two_d::Color::new(255, 255, 0),
-        _ => two_d::Color::new(255, 255, 255),
-    };
-
-    // Draw the pixels of the stripe as a vertical line
-    canvas.set_draw_color(wall_color.sdl_color());
-    canvas.draw_line((x as i32, draw_start), (x as i32, draw_end)).unwrap();
-}
-
 fn main() -> Result<(), String> {
-    let mut window = two_d::Window::new("My Game", 800, 600, false)?;
+    let mut window = two_d::Window::new("My Game", WIDTH, HEIGHT, false)?;
     let mut event_pump = window.sdl_context.event_pump()?;
-
-    let mut player_pos:(f64, f64) = (22.0, 12.0); // Player start position
-    let mut player_dir:(f64, f64) = (-1.0, 0.0); // Initial direction vector
-    let camera = two_d::Camera3D::new(
+    let texture_creator = window.canvas.texture_creator();
+
+    // Rows of the map are y, columns are x
+    let grid: Vec<Vec<u32>> = WORLD_MAP.iter().map(|row| row.iter().map(|&cell| cell as u32).collect()).collect();
+    let mut tile_map = two_d::Tile::from_generated_map(grid, Vec::new(), None).map_err(|e| e.to_string())?;
+    let mut config = two_d::TileConfig::default();
+    for wall in [1, 2, DOOR] {
+        config.set_solid(wall, true);
+    }
+    tile_map.set_config(config);
+
+    // Render at half resolution and stretch it to the window
+    let mut raycaster = two_d::Raycaster::new(&texture_creator, WIDTH / 2, HEIGHT / 2)?;
+    let wall = raycaster.load_texture(std::path::Path::new("test_assets/wall.png"))?;
+    let stone = raycaster.load_texture(std::path::Path::new("test_assets/stone.png"))?;
+    let door = raycaster.load_texture(std::path::Path::new("test_assets/door.png"))?;
+    let ground = raycaster.load_texture(std::path::Path::new("test_assets/ground.png"))?;
+    let dirt = raycaster.load_texture(std::path::Path::new("test_assets/dirt.png"))?;
+    let ladder = raycaster.load_texture(std::path::Path::new("test_assets/ladder.png"))?;
+    raycaster.set_wall_texture(1, wall);
+    raycaster.set_wall_texture(2, stone);
+    raycaster.set_wall_texture(DOOR, door);
+    raycaster.set_floor_texture(0, ground);
+    raycaster.set_ceiling_texture(Some(dirt));
+    raycaster.set_fog(sdl2::pixels::Color::RGB(0, 0, 0), 3.0, 14.0);
+    raycaster.add_door(9, 11);
+
+    let mut ladder_sprite = two_d::RaycastSprite::new(nalgebra::Vector2::new(9.5, 9.5), ladder);
+    ladder_sprite.scale = 0.8;
+    ladder_sprite.vertical_offset = 0.1;
+    let sprites = vec![ladder_sprite, two_d::RaycastSprite::new(nalgebra::Vector2::new(18.5, 4.5), ladder)];
+
+    let mut camera = two_d::Camera3D::new(
         nalgebra::Vector2::new(22.0, 12.0), // Initial position
         nalgebra::Vector2::new(-1.0, 0.0),  // Initial direction
         nalgebra::Vector2::new(0.0, 0.66),  // Initial plane
-        nalgebra::Vector2::new(800, 600)    // Viewport size
+        nalgebra::Vector2::new(WIDTH, HEIGHT) // Viewport size
     );
 
     let mut last_time = Instant::now();
@@ -143,55 +89,35 @@ fn main() -> Result<(), String> {
             match event {
                 Event::Quit { .. }
                 | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => break 'running,
+                // Open or close the door in front of the player
+                Event::KeyDown { keycode: Some(Keycode::Space), repeat: false, .. } => {
+                    raycaster.interact(&camera, 1.5);
+                }
                 _ => {}
             }
         }
 
         let keys: Vec<Keycode> = event_pump.keyboard_state().pressed_scancodes().filter_map(Keycode::from_scancode).collect();
 
-        let move_speed:f64 = delta_time * 5.0; // the constant value is in squares/second
-        let rot_speed:f64 = delta_time * 3.0; // the constant value is in radians/second
+        let move_speed: f64 = delta_time * 5.0; // the constant value is in squares/second
+        let rot_speed: f64 = delta_time * 3.0; // the constant value is in radians/second
 
-        // Move forward if no wall in front
-        if keys.contains(&Keycode::W) {
-            if WORLD_MAP[(player_pos.0 + player_dir.0 * move_speed) as usize][player_pos.1 as usize] == 0 {
-                player_pos.0 += player_dir.0 * move_speed;
-            }
-            if WORLD_MAP[player_pos.0 as usize][(player_pos.1 + player_dir.1 * move_speed) as usize] == 0 {
-                player_pos.1 += player_dir.1 * move_speed;
-            }
-        }
-        // Move backwards if no wall behind
-        if keys.contains(&Keycode::S) {
-            if WORLD_MAP[(player_pos.0 - player_dir.0 * move_speed) as usize][player_pos.1 as usize] == 0 {
-                player_pos.0 -= player_dir.0 * move_speed;
-            }
-            if WORLD_MAP[player_pos.0 as usize][(player_pos.1 - player_dir.1 * move_speed) as usize] == 0 {
-                player_pos.1 -= player_dir.1 * move_speed;
-            }
-        }
-        // Rotate to the right
+        // Walls and closed doors stop the player, moving into them at an angle slides along
+        let forward = if keys.contains(&Keycode::W) { move_speed } else if keys.contains(&Keycode::S) { -move_speed } else { 0.0 };
+        let strafe = if keys.contains(&Keycode::E) { move_speed } else if keys.contains(&Keycode::Q) { -move_speed } else { 0.0 };
+        raycaster.move_camera(&mut camera, &tile_map, forward, strafe);
         if keys.contains(&Keycode::D) {
-            let old_dir_x = player_dir.0;
-            player_dir.0 = player_dir.0 * rot_speed.cos() - player_dir.1 * rot_speed.sin();
-            player_dir.1 = old_dir_x * rot_speed.sin() + player_dir.1 * rot_speed.cos();
+            camera.rotate(rot_speed);
         }
-        // Rotate to the left
         if keys.contains(&Keycode::A) {
-            let old_dir_x = player_dir.0;
-            player_dir.0 = player_dir.0 * (-rot_speed).cos() - player_dir.1 * (-rot_speed).sin();
-            player_dir.1 = old_dir_x * (-rot_speed).sin() + player_dir.1 * (-rot_speed).cos();
+            camera.rotate(-rot_speed);
         }
 
-        // Clear the screen to black (or your floor/ceiling colors)
+        raycaster.update(delta_time);
+
         window.canvas.set_draw_color(two_d::Color::new(0, 0, 0).sdl_color());
         window.canvas.clear();
-
-        // Cast rays and draw scene
-        for x in 0..WIDTH {
-            cast_ray(player_pos, player_dir, &camera, x, &mut window.canvas);
-        }
-
+        raycaster.render(&mut window.canvas, &camera, &tile_map, &sprites)?;
         window.canvas.present();
     }
 
@@ -0,0 +1,84 @@
+use goku::*;
+use sdl2::keyboard::Keycode;
+
+const PLAYER_SPEED: i32 = 4;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Set current directory to the root of the project
+    std::env::set_current_dir(std::path::Path::new(env!("CARGO_MANIFEST_DIR")))
+        .expect("Failed to set project root as current directory");
+
+    let mut window = two_d::Window::new("Split Screen", 800, 600, false)?;
+    let texture_creator = window.canvas.texture_creator();
+    let mut input_handler = two_d::InputHandler::new(&window.sdl_context)?;
+
+    let mut t1 = two_d::TextureManager::new(&texture_creator);
+    t1.load_texture(std::path::Path::new("test_assets/TilesetField_1.png"))?;
+    let mut t2 = two_d::TextureManager::new(&texture_creator);
+    t2.load_texture(std::path::Path::new("test_assets/TilesetField_2.png"))?;
+    let mut t3 = two_d::TextureManager::new(&texture_creator);
+    t3.load_texture(std::path::Path::new("test_assets/TilesetHouse_1.png"))?;
+
+    let tile_map = two_d::Tile::new(std::path::Path::new("test_assets/map.txt"), vec![&t1, &t2, &t3], None)?;
+
+    // Two players side by side, each with its own camera
+    let mut players = [two_d::Rect::new(100, 100, 40, 40), two_d::Rect::new(300, 200, 40, 40)];
+    let mut cameras = two_d::CameraStack::split_screen(800, 600, 2);
+
+    // A zoomed out camera rendering into a texture, shown as a minimap
+    let mut minimap_camera = two_d::Camera::new(nalgebra::Vector2::new(0, 0), nalgebra::Vector2::new(200, 150));
+    minimap_camera.set_zoom(0.25);
+    minimap_camera.set_render_order(-1);
+    let minimap = cameras.add_texture_camera(minimap_camera, &texture_creator, sdl2::pixels::Color::RGB(0, 0, 0))?;
+
+    'mainloop: loop {
+        input_handler.poll_events();
+        if input_handler.is_key_pressed(Keycode::Escape) {
+            break 'mainloop;
+        }
+
+        let controls = [
+            (Keycode::A, Keycode::D, Keycode::W, Keycode::S),
+            (Keycode::Left, Keycode::Right, Keycode::Up, Keycode::Down),
+        ];
+        for (player, (left, right, up, down)) in players.iter_mut().zip(controls.iter()) {
+            if input_handler.is_key_pressed(*left) { player.set_x(player.x() - PLAYER_SPEED); }
+            if input_handler.is_key_pressed(*right) { player.set_x(player.x() + PLAYER_SPEED); }
+            if input_handler.is_key_pressed(*up) { player.set_y(player.y() - PLAYER_SPEED); }
+            if input_handler.is_key_pressed(*down) { player.set_y(player.y() + PLAYER_SPEED); }
+        }
+
+        for (index, player) in players.iter().enumerate() {
+            if let Some(camera) = cameras.camera_mut(index) {
+                camera.update(nalgebra::Vector2::new(player.x(), player.y()));
+            }
+        }
+        let middle = nalgebra::Vector2::new((players[0].x() + players[1].x()) / 2, (players[0].y() + players[1].y()) / 2);
+        if let Some(camera) = cameras.camera_mut(minimap) {
+            camera.update(middle);
+        }
+
+        window.canvas.set_draw_color(sdl2::pixels::Color::RGB(0, 0, 0));
+        window.canvas.clear();
+
+        // The whole scene is drawn once per camera
+        cameras.render(&mut window.canvas, |canvas, camera| {
+            tile_map.render_with_camera_configured(canvas, camera)?;
+            for (index, player) in players.iter().enumerate() {
+                let color = if index == 0 { sdl2::pixels::Color::RGB(255, 80, 80) } else { sdl2::pixels::Color::RGB(80, 80, 255) };
+                canvas.set_draw_color(color);
+                canvas.fill_rect(camera.transform_rect(player))?;
+            }
+            Ok(())
+        })?;
+
+        // Divider between the two halves and the minimap in the top right corner
+        window.canvas.set_draw_color(sdl2::pixels::Color::RGB(255, 255, 255));
+        window.canvas.fill_rect(sdl2::rect::Rect::new(399, 0, 2, 600))?;
+        cameras.copy_target(&mut window.canvas, minimap, sdl2::rect::Rect::new(590, 10, 200, 150))?;
+
+        window.canvas.present();
+        ::std::thread::sleep(std::time::Duration::from_millis(16)); // ~60 FPS
+    }
+    Ok(())
+}
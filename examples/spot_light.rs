@@ -1,5 +1,3 @@
-use sdl2::image::LoadTexture;
-
 use goku::*;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -43,17 +41,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut flip_horizontal = false; // Create a boolean flag to keep track of the flip state
 
-    let mut spotlight_texture = texture_creator.load_texture("test_assets/spot_light.png")?;
-    let spotlight = two_d::SpotLight::new(
+    // The cone and its falloff are generated, no light texture is needed
+    let mut lighting = two_d::LightingSystem::new(&texture_creator, 800, 600)?;
+    lighting.set_ambient(two_d::Color::new(90, 90, 110).sdl_color());
+    lighting.add_spot_light(two_d::SpotLight::new(
         nalgebra::Vector2::new(400.0, 300.0),
         nalgebra::Vector2::new(0.0, -1.0),   // Pointing upwards
         45.0,                                // 45-degree cone
         200.0,
         0.6,
         two_d::Color::new(255, 255, 255)
-    );
-    let mut darkness_texture = texture_creator.create_texture_target(None, 800, 600)?;
-    darkness_texture.set_blend_mode(sdl2::render::BlendMode::Mod);
+    ));
 
     'mainloop: loop {
         current_frame_time = unsafe { sdl2::sys::SDL_GetTicks() };
@@ -127,21 +125,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        // Render each light onto the light texture
-        window.canvas.with_texture_canvas(&mut darkness_texture, |canvas| {
-            // Clear the texture with a semi-transparent black for darkness
-            canvas.set_draw_color(two_d::Color::new_rgba(0, 0, 0, 150).sdl_color());
-            canvas.clear();
-            
-            // Render each light onto this dark texture
-            spotlight.render(canvas, &mut spotlight_texture);
-        })?;
-
-        // Set blend mode to Mod for blending the light texture onto the main scene
-        // Now, set the blend mode and render the darkness_texture over the main canvas to achieve the lighting effect
-        window.canvas.set_blend_mode(sdl2::render::BlendMode::Mod);
-        window.canvas.copy(&darkness_texture, None, None)?;
-        window.canvas.set_blend_mode(sdl2::render::BlendMode::None);
+        // Darken the scene outside the light cone in a single pass
+        lighting.render(&mut window.canvas, None)?;
 
         window.canvas.present();
     }
@@ -0,0 +1,66 @@
+use goku::*;
+use sdl2::keyboard::Keycode;
+
+const VIRTUAL_WIDTH: u32 = 320;
+const VIRTUAL_HEIGHT: u32 = 180;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Set current directory to the root of the project
+    std::env::set_current_dir(std::path::Path::new(env!("CARGO_MANIFEST_DIR")))
+        .expect("Failed to set project root as current directory");
+
+    let mut window = two_d::Window::new("Virtual Resolution (1: stretch, 2: letterbox, 3: integer scale)", 960, 600, false)?;
+    window.set_resizable(true);
+    let texture_creator = window.canvas.texture_creator();
+    let mut input_handler = two_d::InputHandler::new(&window.sdl_context)?;
+
+    // The game is drawn at 320x180 and scaled to the window
+    let mut screen = two_d::VirtualResolution::new(&texture_creator, VIRTUAL_WIDTH, VIRTUAL_HEIGHT, two_d::ScalingMode::IntegerScale)?;
+    input_handler.follow_virtual_resolution(&screen);
+
+    let mut player_texture = two_d::TextureManager::new(&texture_creator);
+    player_texture.load_texture(std::path::Path::new("test_assets/player.png"))?;
+
+    let mut marker = (VIRTUAL_WIDTH as i32 / 2, VIRTUAL_HEIGHT as i32 / 2);
+
+    'mainloop: loop {
+        input_handler.poll_events();
+        if input_handler.is_key_pressed(Keycode::Escape) {
+            break 'mainloop;
+        }
+
+        let mode = if input_handler.is_key_pressed(Keycode::Num1) {
+            Some(two_d::ScalingMode::Stretch)
+        } else if input_handler.is_key_pressed(Keycode::Num2) {
+            Some(two_d::ScalingMode::Letterbox)
+        } else if input_handler.is_key_pressed(Keycode::Num3) {
+            Some(two_d::ScalingMode::IntegerScale)
+        } else {
+            None
+        };
+        if let Some(mode) = mode {
+            screen.set_mode(mode);
+            input_handler.follow_virtual_resolution(&screen);
+        }
+
+        // Clicks are already in virtual coordinates, clicks on the black bars are ignored
+        if input_handler.is_mouse_button_pressed() {
+            marker = input_handler.get_mouse_position();
+        }
+
+        screen.render(&mut window.canvas, |canvas| {
+            canvas.set_draw_color(sdl2::pixels::Color::RGB(40, 60, 90));
+            canvas.clear();
+            canvas.set_draw_color(sdl2::pixels::Color::RGB(90, 150, 70));
+            canvas.fill_rect(sdl2::rect::Rect::new(0, 140, VIRTUAL_WIDTH, 40))?;
+            player_texture.render_texture(canvas, sdl2::rect::Rect::new(40, 108, 32, 32))?;
+            canvas.set_draw_color(sdl2::pixels::Color::RGB(255, 220, 80));
+            canvas.fill_rect(sdl2::rect::Rect::new(marker.0 - 2, marker.1 - 2, 5, 5))?;
+            Ok(())
+        })?;
+
+        window.canvas.present();
+        ::std::thread::sleep(std::time::Duration::from_millis(16)); // ~60 FPS
+    }
+    Ok(())
+}
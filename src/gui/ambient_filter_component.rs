@@ -4,4 +4,12 @@ use serde::{Deserialize, Serialize};
 pub struct AmbientFilterComponent {
     pub intensity: f32,
     pub color: [f32; 4], // RGBA color
+    #[serde(default)]
+    pub day_night_cycle: bool, // Drive intensity and color from a DayNightCycle in the generated game
+    #[serde(default = "default_day_length")]
+    pub day_length: f32, // Seconds for a full day
+}
+
+fn default_day_length() -> f32 {
+    120.0
 }
\ No newline at end of file
@@ -7,11 +7,19 @@ pub fn generate_template(state: &mut State) {
     let window_height = state.window_height;
     // Assume enable_fullscreen is a boolean reflecting the fullscreen state
     let enable_fullscreen = state.general_settings.enable_fullscreen;
+    // One render layer per layer used by the editor images
+    let mut layer_ids: Vec<i32> = state.textures.iter().map(|texture| texture.layer).collect();
+    layer_ids.sort();
+    layer_ids.dedup();
+    let mut render_layers = String::from(if layer_ids.is_empty() { "let render_layers = RenderLayers::new();" } else { "let mut render_layers = RenderLayers::new();" });
+    for id in &layer_ids {
+        render_layers.push_str(&format!("\n    render_layers.add_layer(RenderLayer::new({}, \"Layer {}\"));", id, id));
+    }
     let mut content = format!(r#"
 mod two_d;
 use nalgebra::Vector2;
 use std::path::Path;
-use crate::two_d::{{Window, TextureManagerAnim, GameObject, Camera, InputHandler, AmbientFilter, PointLight, SpotLight}};
+use crate::two_d::{{Window, TextureManagerAnim, GameObject, Camera, InputHandler, AmbientFilter, PointLight, SpotLight, Rect, RenderQueue, RenderLayers, RenderLayer, DayNightCycle}};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {{
     let mut window = Window::new("{}", {}, {}, {})?;
@@ -23,8 +31,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
     // Create a camera object
     let mut camera = Camera::new(Vector2::new(0, 0), Vector2::new({}, {}));
 
+    // Render layers set in the editor, drawn from the lowest to the highest
+    {}
+
     let texture_creator = window.canvas.texture_creator();
-    "#, window_title, window_width, window_height, enable_fullscreen, window_width, window_height);
+    "#, window_title, window_width, window_height, enable_fullscreen, window_width, window_height, render_layers);
 
     // Create a vector to store game object variable names
     let mut game_objects = Vec::new();
@@ -46,6 +57,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
         }
     }
 
+    if !game_objects.is_empty() {
+        let texture_layers: Vec<String> = state.textures.iter()
+            .map(|texture| format!("(\"{}\", {})", texture.tag_name, texture.layer))
+            .collect();
+        content.push_str(&format!(r#"
+    // Render layer of each image, game objects are drawn on the layer of their current animation
+    let texture_layers: &[(&str, i32)] = &[{}];
+    "#, texture_layers.join(", ")));
+    }
+
     // Additional code for AmbientFilter if it exists in state
     if state.components.iter().any(|c| c.name == "Ambient Filter") {
         for (index, filter) in state.ambient_filters.iter().enumerate() {
@@ -53,8 +74,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
     // Initialize AmbientFilter {}
     let mut light_texture{} = texture_creator.create_texture_streaming(None, 800, 600)?;
     light_texture{}.set_blend_mode(sdl2::render::BlendMode::Add);
-    let light{} = AmbientFilter::new({});  // Intensity from the state
-    "#, index, index, index, index, filter.intensity));
+    let {}light{} = AmbientFilter::new({});  // Intensity from the state
+    "#, index, index, index, if filter.day_night_cycle { "mut " } else { "" }, index, filter.intensity));
+            if filter.day_night_cycle {
+                content.push_str(&format!(r#"
+    // Day/night cycle driving AmbientFilter {}
+    let mut day_night{} = DayNightCycle::new({:.1}); // Seconds for a full day
+    "#, index, index, filter.day_length));
+            }
         }
     }
 
@@ -80,17 +107,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
             LightType::Point => {
                 add_light_setup = true;
                 content.push_str(&format!(r#"
-    // Initialize Point Light
-    let light_texture = texture_creator.load_texture("{}")?;
-    let light = two_d::PointLight::new(
+    // Initialize lighting, all lights are drawn in one pass per frame
+    let mut lighting = two_d::LightingSystem::new(&texture_creator, {}, {})?;
+    lighting.set_ambient(sdl2::pixels::Color::RGB(60, 60, 80)); // Color of unlit areas
+    lighting.add_point_light(PointLight::new(
         nalgebra::Vector2::new(400.0, 300.0), // You may want to replace these with dynamic values
         100.0,  // Light radius
         0.6,    // Light intensity
-        sdl2::pixels::Color::RGB({}, {}, {}) // Light color
-    );
-    let mut darkness_texture = texture_creator.create_texture_target(None, 800, 600)?;
-    darkness_texture.set_blend_mode(sdl2::render::BlendMode::Mod);
-    "#, state.light_png_path, 
+        two_d::Color::new({}, {}, {}) // Light color
+    ));
+    "#, window_width, window_height,
        (state.light_color[0] * 255.0) as u8,
        (state.light_color[1] * 255.0) as u8,
        (state.light_color[2] * 255.0) as u8));
@@ -99,19 +125,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
                 // Add code generation for Spotlight if applicable
                 add_light_setup = true;
                 content.push_str(&format!(r#"
-    // Initialize Spot Light
-    let mut spotlight_texture = texture_creator.load_texture("{}")?;
-    let spotlight = two_d::SpotLight::new(
+    // Initialize lighting, all lights are drawn in one pass per frame
+    let mut lighting = two_d::LightingSystem::new(&texture_creator, {}, {})?;
+    lighting.set_ambient(sdl2::pixels::Color::RGB(60, 60, 80)); // Color of unlit areas
+    lighting.add_spot_light(SpotLight::new(
         nalgebra::Vector2::new(400.0, 300.0),
         nalgebra::Vector2::new(0.0, -1.0),   // Pointing upwards
         45.0,                                // 45-degree cone
         200.0,
         0.6,
-        sdl2::pixels::Color::RGB({}, {}, {})
-    );
-    let mut darkness_texture = texture_creator.create_texture_target(None, 800, 600)?;
-    darkness_texture.set_blend_mode(sdl2::render::BlendMode::Mod);
-    "#, state.light_png_path, 
+        two_d::Color::new({}, {}, {})
+    ));
+    "#, window_width, window_height,
         (state.light_color[0] * 255.0) as u8,
         (state.light_color[1] * 255.0) as u8,
         (state.light_color[2] * 255.0) as u8));
@@ -125,6 +150,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
     'mainloop: loop {
         current_frame_time = unsafe { sdl2::sys::SDL_GetTicks() };
         delta_time = (current_frame_time - last_frame_time) as f32 / 1000.0;
+        last_frame_time = current_frame_time;
     "#);
 
     if state.general_settings.enable_input_handler {
@@ -136,29 +162,38 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
         "#);
     }
 
+    content.push_str(r#"
+        window.canvas.clear();
+        // Draw commands are sorted by layer before rendering
+        let mut render_queue = RenderQueue::new();
+        "#);
+
     for game_object_var_name in &game_objects {
         content.push_str(&format!(r#"
-        // Update and render each game object: {}
+        // Update and queue each game object: {}
         // Update camera position to follow this game object (if needed)
         camera.update({}.get_position());
+        {}.texture_manager_anim.update();
 
-        window.canvas.clear();
-        // Render the game object
-        if let Some(current_animation_tag) = &{}.texture_manager_anim.current_animation {{
-            if let Some(animated_texture) = {}.texture_manager_anim.animations.get(current_animation_tag) {{
-                let rect = sdl2::rect::Rect::new(
-                    {}.position.x, 
-                    {}.position.y, 
-                    animated_texture.sprite_sheet.frame_width * 2, 
-                    animated_texture.sprite_sheet.frame_height * 2
-                );
-                let transformed_rect = camera.transform_rect(rect);
-                {}.texture_manager_anim.render_texture(&mut window.canvas, transformed_rect, 0)?;
-            }}
+        if let Some(animated_texture) = {}.texture_manager_anim.current() {{
+            let rect = Rect::new(
+                {}.position.x,
+                {}.position.y,
+                animated_texture.sprite_sheet.frame_width * 2,
+                animated_texture.sprite_sheet.frame_height * 2
+            );
+            let layer = {}.texture_manager_anim.current_animation.as_deref()
+                .and_then(|tag| texture_layers.iter().find(|(name, _)| *name == tag))
+                .map_or(0, |(_, layer)| *layer);
+            render_queue.draw_animation(layer, 0, &{}.texture_manager_anim, rect, false)?;
         }}
-        "#, game_object_var_name, game_object_var_name, game_object_var_name, game_object_var_name, game_object_var_name, game_object_var_name, game_object_var_name));
+        "#, game_object_var_name, game_object_var_name, game_object_var_name, game_object_var_name, game_object_var_name, game_object_var_name, game_object_var_name, game_object_var_name));
     }
 
+    content.push_str(r#"
+        render_queue.flush(&mut window.canvas, &render_layers, Some(&camera))?;
+        "#);
+
     // Additional rendering code for AmbientFilter
     if state.components.iter().any(|c| c.name == "Ambient Filter") {
         // Loop through each AmbientFilter component for rendering
@@ -168,6 +203,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
             let green = (filter.color[1] * 255.0) as u8;
             let blue = (filter.color[2] * 255.0) as u8;
             let alpha = (filter.color[3] * 255.0) as u8;
+            // The cycle tints a white texture through the filter color instead
+            let (red, green, blue) = if filter.day_night_cycle { (255, 255, 255) } else { (red, green, blue) };
+            if filter.day_night_cycle {
+                content.push_str(&format!(r#"
+        // Advance the day/night cycle, the returned DayNightEvent::Dawn/Dusk can trigger game events
+        let _day_night_events{} = day_night{}.update(delta_time);
+        day_night{}.apply_to_ambient_filter(&mut light{});
+        "#, index, index, index, index));
+            }
             content.push_str(&format!(r#"
         // Clear the light texture with an ambient color (e.g., dark blue)
         light_texture{}.with_lock(None, |buffer: &mut [u8], pitch: usize| {{
@@ -193,16 +237,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
 
     // Insert the light rendering code inside the main loop
     if add_light_setup {
+        // The first day/night cycle also sets the ambient light and switches night lights
+        let has_ambient_filter = state.components.iter().any(|c| c.name == "Ambient Filter");
+        if let Some(index) = state.ambient_filters.iter().position(|filter| filter.day_night_cycle).filter(|_| has_ambient_filter) {
+            content.push_str(&format!(r#"
+        day_night{}.apply_to_lighting(&mut lighting);
+        "#, index));
+        }
         content.push_str(r#"
-        // Render the light
-        window.canvas.with_texture_canvas(&mut darkness_texture, |canvas| {
-            canvas.set_draw_color(sdl2::pixels::Color::RGBA(0, 0, 0, 150)); // Semi-transparent black
-            canvas.clear();
-            light.render(canvas, &light_texture);
-        }).unwrap();
-        window.canvas.set_blend_mode(sdl2::render::BlendMode::Mod);
-        window.canvas.copy(&darkness_texture, None, None).unwrap();
-        window.canvas.set_blend_mode(sdl2::render::BlendMode::None);
+        // Render the lights
+        lighting.render(&mut window.canvas, Some(&camera))?;
         "#);
     }
 
@@ -282,6 +282,7 @@ pub fn launcher() -> Result<(), String> {
                             height, // Image height
                             frames: 4, // Default single frame
                             rows: 3, // Default single row
+                            layer: 0,
                         });
                     }
                 },
@@ -584,6 +585,7 @@ pub fn launcher() -> Result<(), String> {
                                 height: 0,                // default value, e.g., 0
                                 frames: 1, // Initialize with 1, will be updated later in UI
                                 rows: 3,
+                                layer: 0,
                             });
                         } else {
                             println!("No file chosen");
@@ -639,6 +641,8 @@ pub fn launcher() -> Result<(), String> {
                         state.ambient_filters.push(AmbientFilterComponent { 
                             intensity: 0.5, 
                             color: [1.0, 1.0, 1.0, 1.0], // White color 
+                            day_night_cycle: false,
+                            day_length: 120.0,
                         });
                     } else {
                         // If an AmbientFilterComponent already exists, update its intensity
@@ -648,6 +652,13 @@ pub fn launcher() -> Result<(), String> {
                                 .step_fast(0.1)
                                 .build();
                             ui.color_edit4("Color", &mut filter.color);
+                            ui.checkbox("Day/Night Cycle", &mut filter.day_night_cycle);
+                            if filter.day_night_cycle {
+                                ui.input_float("Day Length (s)", &mut filter.day_length)
+                                    .step(1.0)
+                                    .step_fast(10.0)
+                                    .build();
+                            }
                         }
                     }
                 },
@@ -692,22 +703,8 @@ pub fn launcher() -> Result<(), String> {
                         };
                     }
     
+                    // Falloff is generated by the LightingSystem, lights need no texture
                     ui.color_edit3("Color", &mut state.light_color);
-    
-                    if ui.button("Select PNG...") {
-                        let file = FileDialog::new()
-                            .add_filter("PNG Image", &["png"])
-                            .pick_file();
-    
-                        if let Some(file_path) = file {
-                            state.light_png_path = file_path.to_str().unwrap_or_default().to_string();
-                        } else {
-                            println!("No file chosen");
-                            state.terminal.log_error("No file chosen");
-                        }
-                    }
-    
-                    ui.text(format!("PNG Path: {}", state.light_png_path));
                 },                         
                 Some(component) => ui.text(component),
                 None => ui.text("No component selected"),
@@ -814,6 +811,10 @@ pub fn launcher() -> Result<(), String> {
                             ui.checkbox("Enable Animation", &mut textures[selected_index].animation); // Toggle animation for the selected image
                             if ui.input_int("Layer", &mut layer).build() {
                                 textures[selected_index].layer = layer;
+                                // Keep the exported texture on the same render layer
+                                if let Some(texture) = state.textures.get_mut(selected_index) {
+                                    texture.layer = layer;
+                                }
                             }
                         }
 
@@ -926,7 +927,7 @@ pub fn launcher() -> Result<(), String> {
                             // Append the required dependencies to Cargo.toml
                             let cargo_toml_path = state.project_dir.join("Cargo.toml");
                             let mut cargo_toml = std::fs::read_to_string(&cargo_toml_path).expect("Failed to read Cargo.toml");
-                            cargo_toml.push_str("\nnalgebra = \"0.32.2\"\nsdl2-sys = \"0.35.2\"\nserde = { version = \"1.0\", features = [\"derive\"] }\nserde_json = \"1.0\"\nserde_derive = \"1.0.163\"\nrand = \"0.8.5\"\n");
+                            cargo_toml.push_str("\nnalgebra = \"0.32.2\"\nsdl2-sys = \"0.35.2\"\nserde = { version = \"1.0\", features = [\"derive\"] }\nserde_json = \"1.0\"\nserde_derive = \"1.0.163\"\nrand = \"0.8.5\"\nchrono = \"0.4.31\"\nimage = \"0.23.14\"\nroxmltree = \"0.19\"\nbase64 = \"0.21\"\nflate2 = \"1.0\"\n");
                             cargo_toml.push_str("sdl2 = { version = \"0.35\", default-features = false, features = [\"image\", \"ttf\", \"mixer\"] }\n");
                             std::fs::write(&cargo_toml_path, cargo_toml).expect("Failed to write to Cargo.toml");
         
@@ -988,12 +989,10 @@ pub fn launcher() -> Result<(), String> {
                 let mut height = tilemap.height as i32;
     
                 if ui.input_int("Width", &mut width).build() {
-                    tilemap.width = width as usize;
-                    tilemap.tiles.resize(tilemap.width * tilemap.height, 0);
+                    tilemap.resize(width as usize, tilemap.height);
                 }
                 if ui.input_int("Height", &mut height).build() {
-                    tilemap.height = height as usize;
-                    tilemap.tiles.resize(tilemap.width * tilemap.height, 0);
+                    tilemap.resize(tilemap.width, height as usize);
                 }
     
                 if ui.button("Save Tilemap") {
@@ -1011,6 +1010,15 @@ pub fn launcher() -> Result<(), String> {
                         }
                     }
                 }
+                // Paint terrain ids, then turn them into edge tiles with a JSON ruleset
+                if ui.button("Autotile") {
+                    if let Some(path) = FileDialog::new().add_filter("JSON files", &["json"]).pick_file() {
+                        match goku::two_d::Autotiler::load(&path) {
+                            Ok(autotiler) => tilemap.autotile(&autotiler),
+                            Err(e) => eprintln!("Error loading autotile rules: {}", e),
+                        }
+                    }
+                }
     
                 // Display the tilemap
                 for y in 0..tilemap.height {
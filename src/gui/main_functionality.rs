@@ -182,7 +182,7 @@ pub fn build_code(state: &mut State) -> Result<(), Box<dyn std::error::Error>> {
 #[allow(dead_code)]
 pub fn append_dependencies_to_cargo_toml(cargo_toml_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     let mut cargo_toml = std::fs::read_to_string(cargo_toml_path)?;
-    cargo_toml.push_str("\nnalgebra = \"0.32.2\"\nsdl2-sys = \"0.35.2\"\nserde = { version = \"1.0\", features = [\"derive\"] }\nserde_json = \"1.0\"\nserde_derive = \"1.0.163\"\nrand = \"0.8.5\"\n[dependencies.sdl2]\nversion = \"0.35\"\ndefault-features = false\nfeatures = [\"image\", \"ttf\", \"mixer\"]\n");
+    cargo_toml.push_str("\nnalgebra = \"0.32.2\"\nsdl2-sys = \"0.35.2\"\nserde = { version = \"1.0\", features = [\"derive\"] }\nserde_json = \"1.0\"\nserde_derive = \"1.0.163\"\nrand = \"0.8.5\"\nchrono = \"0.4.31\"\nimage = \"0.23.14\"\nroxmltree = \"0.19\"\nbase64 = \"0.21\"\nflate2 = \"1.0\"\n[dependencies.sdl2]\nversion = \"0.35\"\ndefault-features = false\nfeatures = [\"image\", \"ttf\", \"mixer\"]\n");
     std::fs::write(cargo_toml_path, cargo_toml)?;
     Ok(())
 }
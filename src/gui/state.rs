@@ -62,8 +62,6 @@ pub struct State {
     #[serde(skip)]
     pub light_color: [f32; 3],
     #[serde(skip)]
-    pub light_png_path: String,
-    #[serde(skip)]
     pub open_image_view: bool,
     #[serde(skip)]
     pub dynamic_texture_id: Option<u32>,
@@ -116,7 +114,6 @@ impl State {
             window_height: 0,
             light_type: LightType::None,
             light_color: [0.0, 0.0, 0.0],
-            light_png_path: "".to_string(),
             open_image_view: false,
             dynamic_texture_id: None,
             surf_texture_id: 0,
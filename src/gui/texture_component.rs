@@ -8,6 +8,8 @@ pub struct TextureComponent {
     pub height: u32,
     pub frames: u32,
     pub rows: usize,
+    #[serde(default)]
+    pub layer: i32, // Render layer in the generated game, lower layers are drawn first
 }
 
 impl Default for TextureComponent {
@@ -19,6 +21,7 @@ impl Default for TextureComponent {
             height: 0,
             frames: 0,
             rows: 0,
+            layer: 0,
         }
     }
 }
\ No newline at end of file
@@ -10,6 +10,7 @@ pub struct Tilemap {
     pub width: usize,
     pub height: usize,
     pub tiles: Vec<u32>, // Stores texture IDs or tile indices
+    pub terrain: Vec<u32>, // Painted terrain ids, autotile reads these and writes tiles
 }
 
 impl Tilemap {
@@ -18,12 +19,22 @@ impl Tilemap {
             width,
             height,
             tiles: vec![0; width * height], // Initialize with zeros
+            terrain: vec![0; width * height],
         }
     }
 
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.tiles.resize(width * height, 0);
+        self.terrain.resize(width * height, 0);
+    }
+
+    // Paints a cell, it shows the painted id until the next autotile
     pub fn set_tile(&mut self, x: usize, y: usize, tile: u32) {
         if x < self.width && y < self.height {
             self.tiles[y * self.width + x] = tile;
+            self.terrain[y * self.width + x] = tile;
         }
     }
 
@@ -35,6 +46,12 @@ impl Tilemap {
         }
     }
 
+    // Shows the tiles the autotile rules pick for the painted terrain, which stays as it is
+    pub fn autotile(&mut self, autotiler: &goku::two_d::Autotiler) {
+        let rows: Vec<Vec<u32>> = self.terrain.chunks(self.width.max(1)).map(|row| row.to_vec()).collect();
+        self.tiles = autotiler.apply(&rows).concat();
+    }
+
     pub fn save_to_file(&self, path: &str) -> std::io::Result<()> {
         let mut file = File::create(path)?;
         writeln!(file, "{} {}", self.width, self.height)?;
@@ -58,7 +75,9 @@ impl Tilemap {
             tiles.push(line?.trim().parse().unwrap());
         }
 
-        Ok(Tilemap { width, height, tiles })
+        // The saved tiles become the terrain to paint and autotile from
+        let terrain = tiles.clone();
+        Ok(Tilemap { width, height, tiles, terrain })
     }
 }
 pub fn load_texture_from_path(path: &str) -> Result<(u32, u32, u32), String> {
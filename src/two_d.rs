@@ -2,6 +2,16 @@ extern crate sdl2;
 
 pub mod sprite_sheet;
 pub use sprite_sheet::SpriteSheet;
+pub use sprite_sheet::SharedTexture;
+
+pub mod sprite_atlas;
+pub use sprite_atlas::SpriteAtlas;
+pub use sprite_atlas::SpriteFrame;
+pub use sprite_atlas::FrameTag;
+pub use sprite_atlas::TagDirection;
+
+pub mod sprite_effects;
+pub use sprite_effects::SpriteDrawParams;
 
 pub mod animated_texture;
 pub use animated_texture::AnimatedTexture;
@@ -17,18 +27,81 @@ pub use game_object::GameObject;
 
 pub mod tile;
 pub use tile::Tile;
+pub use tile::TileConfig;
+pub mod collider_merge;
+pub use collider_merge::EdgeChain;
+pub mod tile_chunks;
+pub use tile_chunks::TileChunkRenderer;
+pub mod tileset;
+pub use tileset::TilesetDefinition;
+pub use tileset::TileProperty;
+pub mod grid_layout;
+pub use grid_layout::{GridLayout, GridOrientation, StaggerIndex, Hex};
+pub mod procgen;
+pub use procgen::{BspGenerator, DrunkardWalk, CaveGenerator, NoiseField, NoiseKind};
+pub mod wfc;
+pub use wfc::{WaveFunctionCollapse, WfcModel, WfcDirection};
+pub mod autotile;
+pub use autotile::Autotiler;
+pub use autotile::AutotileRule;
+pub mod tiled;
+pub use tiled::TiledMap;
+pub use tiled::TiledMapRenderer;
+pub mod ldtk;
+pub use ldtk::LdtkProject;
+pub use ldtk::LdtkRenderer;
 
 pub mod camera;
 pub use camera::Camera;
 
+pub mod camera_stack;
+pub use camera_stack::CameraStack;
+pub use camera_stack::CameraView;
+pub use camera_stack::split_screen_viewports;
+
+pub mod render_queue;
+pub use render_queue::RenderQueue;
+pub use render_queue::RenderLayers;
+pub use render_queue::RenderLayer;
+pub use render_queue::DrawCommand;
+pub use render_queue::DrawKind;
+pub use render_queue::TextureDrawOptions;
+
 pub mod camera3d;
 pub use camera3d::Camera3D;
+pub mod raycaster;
+pub use raycaster::{Raycaster, RaycastSprite, RaycastTexture};
 
 pub mod light;
 pub use light::PointLight;
 pub use light::SpotLight;
 pub use light::AmbientFilter;
 
+pub mod shadow;
+pub use shadow::Occluders;
+pub use shadow::Segment;
+pub use shadow::visibility_polygon;
+
+pub mod light_map;
+pub use light_map::LightMap;
+
+pub mod lighting;
+pub use lighting::LightingSystem;
+
+pub mod day_night;
+pub use day_night::DayNightCycle;
+pub use day_night::DayNightEvent;
+pub use day_night::TimeKeyframe;
+pub use day_night::NightLight;
+
+pub mod capture;
+pub use capture::GifRecorder;
+
+pub mod post_process;
+pub use post_process::PostProcess;
+pub use post_process::PostFilter;
+pub use post_process::Lut3D;
+
 pub mod physics;
 pub use physics::RigidBody;
 
@@ -39,9 +112,25 @@ pub use particle_system::spawn_particles_fires;
 pub use particle_system::spawn_particles_rain;
 pub use particle_system::spawn_particles_sparks;
 
+pub mod draw;
+
 pub mod shapes;
 pub use shapes::Shape2D;
 
+pub mod text;
+pub use text::GlyphSource;
+pub use text::GlyphAtlas;
+pub use text::FontCache;
+pub use text::TextAlign;
+pub use text::TextOptions;
+
+pub mod bitmap_font;
+pub use bitmap_font::BitmapFont;
+
+pub mod nine_slice;
+pub use nine_slice::NineSlice;
+pub use nine_slice::SliceMode;
+
 pub mod ui;
 pub use ui::Layer;
 pub use ui::Button;
@@ -60,6 +149,10 @@ pub use ai_system::BehaviourTreeResult;
 pub mod window_system;
 pub use window_system::Window;
 
+pub mod virtual_resolution;
+pub use virtual_resolution::VirtualResolution;
+pub use virtual_resolution::ScalingMode;
+
 pub mod input_handler;
 pub use input_handler::InputHandler;
 
@@ -1,6 +1,7 @@
 extern crate sdl2;
 // extern  crate gl;
 use crate::two_d::sprite_sheet::SpriteSheet;
+use crate::two_d::sprite_effects::{self, SpriteDrawParams, SpriteEffectCache};
 use sdl2::rect::Rect;
 use sdl2::render::Canvas;
 
@@ -10,6 +11,7 @@ pub struct AnimatedTexture<'a> {
     pub current_frame: u32,
     pub last_frame_time: u32,
     pub flip: sdl2_sys::SDL_RendererFlip,
+    pub effects: Option<SpriteEffectCache<'a>>, // Masks and outlines for SpriteDrawParams effects
 }
 
 impl<'a> AnimatedTexture<'a> {
@@ -20,23 +22,67 @@ impl<'a> AnimatedTexture<'a> {
             current_frame: 0,
             last_frame_time: 0,
             flip: sdl2_sys::SDL_RendererFlip::SDL_FLIP_NONE, // Default to no flip
+            effects: None,
         }
     }
 
-    pub fn render(&mut self, canvas: &mut Canvas<sdl2::video::Window>, dest: Rect, flip: u32) -> Result<(), String> {
+    // Advances to the next frame once the frame delay has passed
+    pub fn update(&mut self) {
         let now = unsafe {
             sdl2_sys::SDL_GetTicks()
         };
         let delta_time = now - self.last_frame_time;
-        if delta_time >= self.frame_delay {
-            self.current_frame = (self.current_frame + 1) % (self.sprite_sheet.texture.query().width / self.sprite_sheet.frame_width) as u32;
+        // Frames imported from atlas metadata may carry their own duration
+        let frame_delay = self.sprite_sheet.frame_duration(self.current_frame).unwrap_or(self.frame_delay);
+        if delta_time >= frame_delay {
+            self.current_frame = (self.current_frame + 1) % self.sprite_sheet.frame_count().max(1);
             self.last_frame_time = now;
         }
+    }
+
+    pub fn render(&mut self, canvas: &mut Canvas<sdl2::video::Window>, dest: Rect, flip: u32) -> Result<(), String> {
+        self.update();
+        self.draw(canvas, dest, flip)
+    }
 
+    // Draws the current frame without advancing the animation
+    pub fn draw(&self, canvas: &mut Canvas<sdl2::video::Window>, dest: Rect, flip: u32) -> Result<(), String> {
         let src = self.sprite_sheet.get_frame(self.current_frame);
-        canvas.copy_ex(&self.sprite_sheet.texture, Some(src), Some(dest), 0.0, None, flip == sdl2_sys::SDL_RendererFlip::SDL_FLIP_HORIZONTAL as u32, false)?;
+        let dest = self.sprite_sheet.frame_dest(self.current_frame, dest);
+        let flip_horizontal = flip & sdl2_sys::SDL_RendererFlip::SDL_FLIP_HORIZONTAL as u32 != 0;
+        let flip_vertical = flip & sdl2_sys::SDL_RendererFlip::SDL_FLIP_VERTICAL as u32 != 0;
+        if self.sprite_sheet.is_rotated(self.current_frame) {
+            // Packed sideways: draw with swapped sides around the same center and turn it back
+            let center = dest.center();
+            let rotated_dest = Rect::from_center(center, dest.height(), dest.width());
+            canvas.copy_ex(&self.sprite_sheet.texture, Some(src), Some(rotated_dest), -90.0, None, flip_vertical, flip_horizontal)?;
+        } else {
+            canvas.copy_ex(&self.sprite_sheet.texture, Some(src), Some(dest), 0.0, None, flip_horizontal, flip_vertical)?;
+        }
         // canvas.copy(&self.sprite_sheet.texture, src, dest)?;
 
         Ok(())
     }
+
+    pub fn render_ex(&mut self, canvas: &mut Canvas<sdl2::video::Window>, dest: Rect, params: &SpriteDrawParams) -> Result<(), String> {
+        self.update();
+        self.draw_ex(canvas, dest, params)
+    }
+
+    // Draws the current frame with a transform, tint and effects. dest is the unscaled sprite,
+    // params.scale grows it around the pivot.
+    pub fn draw_ex(&mut self, canvas: &mut Canvas<sdl2::video::Window>, dest: Rect, params: &SpriteDrawParams) -> Result<(), String> {
+        let src = self.sprite_sheet.get_frame(self.current_frame);
+        let sprite_dest = params.transform_dest(dest);
+        let frame_dest = self.sprite_sheet.frame_dest(self.current_frame, sprite_dest);
+        // Trimmed frames still turn around the pivot of the whole sprite
+        let mut params = *params;
+        if frame_dest != sprite_dest {
+            let (pivot_x, pivot_y) = params.pivot.unwrap_or((0.5, 0.5));
+            let pivot = (sprite_dest.x() as f32 + sprite_dest.width() as f32 * pivot_x, sprite_dest.y() as f32 + sprite_dest.height() as f32 * pivot_y);
+            params.pivot = Some(((pivot.0 - frame_dest.x() as f32) / frame_dest.width() as f32, (pivot.1 - frame_dest.y() as f32) / frame_dest.height() as f32));
+        }
+        let rotated = self.sprite_sheet.is_rotated(self.current_frame);
+        sprite_effects::draw_sprite(canvas, &self.sprite_sheet.texture, src, frame_dest, &params, rotated, self.effects.as_mut())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,232 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use serde::Deserialize;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+// Neighbour bits of the 4-bit mask
+pub const NORTH: u8 = 1;
+pub const EAST: u8 = 2;
+pub const SOUTH: u8 = 4;
+pub const WEST: u8 = 8;
+
+// Neighbour bits of the 8-bit blob mask, clockwise from north
+pub const BLOB_NORTH: u8 = 1;
+pub const BLOB_NORTH_EAST: u8 = 2;
+pub const BLOB_EAST: u8 = 4;
+pub const BLOB_SOUTH_EAST: u8 = 8;
+pub const BLOB_SOUTH: u8 = 16;
+pub const BLOB_SOUTH_WEST: u8 = 32;
+pub const BLOB_WEST: u8 = 64;
+pub const BLOB_NORTH_WEST: u8 = 128;
+
+// Corner bits of a Wang corner tile, in Tiled's order
+pub const CORNER_NORTH_EAST: u8 = 1;
+pub const CORNER_SOUTH_EAST: u8 = 2;
+pub const CORNER_SOUTH_WEST: u8 = 4;
+pub const CORNER_NORTH_WEST: u8 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutotileMode {
+    FourBit,    // 16 tiles, edges only
+    Blob,       // 47 tiles, corners count when both neighbouring edges connect
+    WangCorner, // 16 tiles, a corner is filled when all four cells around it are terrain
+}
+
+// One tile or several variants picked by position, so large areas do not look repetitive
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum AutotileChoice {
+    Single(u32),
+    Variants(Vec<u32>),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AutotileRule {
+    pub terrain: u32, // Terrain value in the input grid painted by this rule
+    pub mode: AutotileMode,
+    #[serde(default)]
+    pub connects_to: Vec<u32>, // Other terrains that count as connected, e.g. doors in walls
+    #[serde(default = "default_true")]
+    pub edges_connect: bool, // Whether cells outside the map count as the same terrain
+    #[serde(default)]
+    pub first_tile: u32, // Tiles laid out in mask order: 0..15, or the 47 blob masks ascending
+    #[serde(default)]
+    pub tiles: HashMap<u8, AutotileChoice>, // Mask to tile, overrides first_tile
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct RawRuleset {
+    rules: Vec<AutotileRule>,
+}
+
+#[allow(dead_code)]
+impl AutotileRule {
+    pub fn new(terrain: u32, mode: AutotileMode, first_tile: u32) -> Self {
+        Self { terrain, mode, connects_to: Vec::new(), edges_connect: true, first_tile, tiles: HashMap::new() }
+    }
+
+    pub fn set_tile(&mut self, mask: u8, tile: u32) {
+        self.tiles.insert(mask, AutotileChoice::Single(tile));
+    }
+
+    pub fn set_variants(&mut self, mask: u8, tiles: Vec<u32>) {
+        self.tiles.insert(mask, AutotileChoice::Variants(tiles));
+    }
+
+    fn connects(&self, terrain: Option<u32>) -> bool {
+        match terrain {
+            Some(terrain) => terrain == self.terrain || self.connects_to.contains(&terrain),
+            None => self.edges_connect,
+        }
+    }
+
+    // Mask of the cell at x, y; the cell itself is expected to be this rule's terrain
+    pub fn mask(&self, terrain: &[Vec<u32>], x: usize, y: usize) -> u8 {
+        let at = |dx: i32, dy: i32| {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if nx < 0 || ny < 0 {
+                return self.connects(None);
+            }
+            self.connects(terrain.get(ny as usize).and_then(|row| row.get(nx as usize)).copied())
+        };
+        let bit = |set: bool, value: u8| if set { value } else { 0 };
+        match self.mode {
+            AutotileMode::FourBit => bit(at(0, -1), NORTH) | bit(at(1, 0), EAST) | bit(at(0, 1), SOUTH) | bit(at(-1, 0), WEST),
+            AutotileMode::Blob => {
+                let mask = bit(at(0, -1), BLOB_NORTH)
+                    | bit(at(1, -1), BLOB_NORTH_EAST)
+                    | bit(at(1, 0), BLOB_EAST)
+                    | bit(at(1, 1), BLOB_SOUTH_EAST)
+                    | bit(at(0, 1), BLOB_SOUTH)
+                    | bit(at(-1, 1), BLOB_SOUTH_WEST)
+                    | bit(at(-1, 0), BLOB_WEST)
+                    | bit(at(-1, -1), BLOB_NORTH_WEST);
+                reduce_blob_mask(mask)
+            }
+            AutotileMode::WangCorner => {
+                let (n, e, s, w) = (at(0, -1), at(1, 0), at(0, 1), at(-1, 0));
+                bit(n && e && at(1, -1), CORNER_NORTH_EAST)
+                    | bit(s && e && at(1, 1), CORNER_SOUTH_EAST)
+                    | bit(s && w && at(-1, 1), CORNER_SOUTH_WEST)
+                    | bit(n && w && at(-1, -1), CORNER_NORTH_WEST)
+            }
+        }
+    }
+
+    // Tile for a mask, the position picks between variants the same way every time
+    pub fn tile_for(&self, mask: u8, x: usize, y: usize) -> u32 {
+        match self.tiles.get(&mask) {
+            Some(AutotileChoice::Single(tile)) => *tile,
+            Some(AutotileChoice::Variants(tiles)) if !tiles.is_empty() => {
+                let hash = (x as u32).wrapping_mul(73_856_093) ^ (y as u32).wrapping_mul(19_349_663);
+                tiles[(hash % tiles.len() as u32) as usize]
+            }
+            _ => match self.mode {
+                AutotileMode::Blob => self.first_tile + blob_index(mask).unwrap_or(0) as u32,
+                _ => self.first_tile + mask as u32,
+            },
+        }
+    }
+}
+
+// Drops corner bits whose two neighbouring edges are not both set, leaving one of the 47 blob masks
+pub fn reduce_blob_mask(mask: u8) -> u8 {
+    let mut reduced = mask & (BLOB_NORTH | BLOB_EAST | BLOB_SOUTH | BLOB_WEST);
+    let corners = [
+        (BLOB_NORTH_EAST, BLOB_NORTH, BLOB_EAST),
+        (BLOB_SOUTH_EAST, BLOB_SOUTH, BLOB_EAST),
+        (BLOB_SOUTH_WEST, BLOB_SOUTH, BLOB_WEST),
+        (BLOB_NORTH_WEST, BLOB_NORTH, BLOB_WEST),
+    ];
+    for (corner, first, second) in corners {
+        if mask & corner != 0 && mask & first != 0 && mask & second != 0 {
+            reduced |= corner;
+        }
+    }
+    reduced
+}
+
+// Position of a reduced mask among the 47 blob masks in ascending order (0 is the single cell, 46 is 255)
+pub fn blob_index(mask: u8) -> Option<usize> {
+    if reduce_blob_mask(mask) != mask {
+        return None;
+    }
+    Some((0..mask).filter(|&m| reduce_blob_mask(m) == m).count())
+}
+
+// Turns a terrain grid into tile indices with a set of rules, one per terrain
+pub struct Autotiler {
+    pub rules: Vec<AutotileRule>,
+}
+
+impl Default for Autotiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl Autotiler {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    // { "rules": [ { "terrain": 2, "mode": "blob", "first_tile": 16, "tiles": { "255": [62, 63] } } ] }
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let raw: RawRuleset = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        Ok(Self { rules: raw.rules })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_json(&json).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn add_rule(&mut self, rule: AutotileRule) {
+        self.rules.push(rule);
+    }
+
+    pub fn rule_for(&self, terrain: u32) -> Option<&AutotileRule> {
+        self.rules.iter().find(|rule| rule.terrain == terrain)
+    }
+
+    // Tile of one cell, cells without a rule keep their terrain value
+    pub fn tile_at(&self, terrain: &[Vec<u32>], x: usize, y: usize) -> Option<u32> {
+        let value = *terrain.get(y)?.get(x)?;
+        Some(match self.rule_for(value) {
+            Some(rule) => rule.tile_for(rule.mask(terrain, x, y), x, y),
+            None => value,
+        })
+    }
+
+    pub fn apply(&self, terrain: &[Vec<u32>]) -> Vec<Vec<u32>> {
+        terrain.iter().enumerate()
+            .map(|(y, row)| (0..row.len()).map(|x| self.tile_at(terrain, x, y).unwrap_or(0)).collect())
+            .collect()
+    }
+
+    // After painting one terrain cell only it and its 8 neighbours can change.
+    // Returns the cells that were updated, e.g. to invalidate chunks.
+    pub fn update_around(&self, terrain: &[Vec<u32>], tiles: &mut [Vec<u32>], x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut changed = Vec::new();
+        for ny in y.saturating_sub(1)..=y + 1 {
+            for nx in x.saturating_sub(1)..=x + 1 {
+                if let (Some(tile), Some(cell)) = (self.tile_at(terrain, nx, ny), tiles.get_mut(ny).and_then(|row| row.get_mut(nx))) {
+                    if *cell != tile {
+                        *cell = tile;
+                        changed.push((nx, ny));
+                    }
+                }
+            }
+        }
+        changed
+    }
+}
@@ -0,0 +1,267 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::text::{Glyph, GlyphSource, SharedGlyphSource};
+
+use sdl2::image::LoadTexture;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture, TextureCreator};
+use sdl2::video::WindowContext;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+// Glyph of a .fnt file as it is stored there, before the page textures are loaded
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BitmapChar {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+    pub x_advance: i32,
+    pub page: usize,
+}
+
+// Contents of an AngelCode BMFont descriptor, text or binary
+#[derive(Clone, Debug, Default)]
+pub struct BitmapFontData {
+    pub face: String,
+    pub size: i32,
+    pub line_height: i32,
+    pub base: i32,
+    pub scale_w: u32,
+    pub scale_h: u32,
+    pub pages: Vec<String>, // Page image files, relative to the .fnt file
+    pub chars: Vec<BitmapChar>,
+    pub kernings: Vec<(u32, u32, i32)>,
+}
+
+#[allow(dead_code)]
+impl BitmapFontData {
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.starts_with(b"BMF") {
+            Self::parse_binary(bytes)
+        } else {
+            let text = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+            Self::parse_text(text)
+        }
+    }
+
+    // Lines of a tag followed by key=value pairs, e.g. char id=65 x=0 y=0 width=8 ...
+    pub fn parse_text(text: &str) -> Result<Self, String> {
+        let mut data = BitmapFontData::default();
+        for line in text.lines() {
+            let mut tokens = split_fields(line).into_iter();
+            let tag = match tokens.next() {
+                Some(tag) => tag,
+                None => continue,
+            };
+            let fields: HashMap<String, String> = tokens
+                .filter_map(|token| token.split_once('=').map(|(key, value)| (key.to_string(), value.trim_matches('"').to_string())))
+                .collect();
+            let int = |key: &str| fields.get(key).and_then(|value| value.parse::<i32>().ok()).unwrap_or(0);
+            match tag.as_str() {
+                "info" => {
+                    data.face = fields.get("face").cloned().unwrap_or_default();
+                    data.size = int("size").abs();
+                }
+                "common" => {
+                    data.line_height = int("lineHeight");
+                    data.base = int("base");
+                    data.scale_w = int("scaleW").max(0) as u32;
+                    data.scale_h = int("scaleH").max(0) as u32;
+                }
+                "page" => {
+                    let id = int("id").max(0) as usize;
+                    let file = fields.get("file").cloned().ok_or_else(|| format!("Page {} without a file", id))?;
+                    if data.pages.len() <= id {
+                        data.pages.resize(id + 1, String::new());
+                    }
+                    data.pages[id] = file;
+                }
+                "char" => data.chars.push(BitmapChar {
+                    id: int("id").max(0) as u32,
+                    x: int("x"),
+                    y: int("y"),
+                    width: int("width").max(0) as u32,
+                    height: int("height").max(0) as u32,
+                    x_offset: int("xoffset"),
+                    y_offset: int("yoffset"),
+                    x_advance: int("xadvance"),
+                    page: int("page").max(0) as usize,
+                }),
+                "kerning" => data.kernings.push((int("first").max(0) as u32, int("second").max(0) as u32, int("amount"))),
+                _ => {}
+            }
+        }
+        if data.pages.is_empty() {
+            return Err("Bitmap font has no pages".to_string());
+        }
+        Ok(data)
+    }
+
+    // Version 3 binary format: "BMF", version, then blocks of type, size and little endian data
+    pub fn parse_binary(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 4 || &bytes[0..3] != b"BMF" {
+            return Err("Not a binary BMFont file".to_string());
+        }
+        if bytes[3] != 3 {
+            return Err(format!("Unsupported binary BMFont version {}", bytes[3]));
+        }
+        let u16_at = |block: &[u8], at: usize| u16::from_le_bytes([block[at], block[at + 1]]);
+        let i16_at = |block: &[u8], at: usize| i16::from_le_bytes([block[at], block[at + 1]]);
+        let u32_at = |block: &[u8], at: usize| u32::from_le_bytes([block[at], block[at + 1], block[at + 2], block[at + 3]]);
+
+        let mut data = BitmapFontData::default();
+        let mut position = 4;
+        while position + 5 <= bytes.len() {
+            let block_type = bytes[position];
+            let size = u32_at(bytes, position + 1) as usize;
+            let start = position + 5;
+            let block = bytes.get(start..start + size).ok_or("Truncated binary BMFont block")?;
+            match block_type {
+                1 if size >= 14 => {
+                    data.size = (i16_at(block, 0) as i32).abs();
+                    data.face = null_terminated(&block[14..]).into_iter().next().unwrap_or_default();
+                }
+                2 if size >= 10 => {
+                    data.line_height = u16_at(block, 0) as i32;
+                    data.base = u16_at(block, 2) as i32;
+                    data.scale_w = u16_at(block, 4) as u32;
+                    data.scale_h = u16_at(block, 6) as u32;
+                }
+                3 => data.pages = null_terminated(block),
+                4 => {
+                    for record in block.chunks_exact(20) {
+                        data.chars.push(BitmapChar {
+                            id: u32_at(record, 0),
+                            x: u16_at(record, 4) as i32,
+                            y: u16_at(record, 6) as i32,
+                            width: u16_at(record, 8) as u32,
+                            height: u16_at(record, 10) as u32,
+                            x_offset: i16_at(record, 12) as i32,
+                            y_offset: i16_at(record, 14) as i32,
+                            x_advance: i16_at(record, 16) as i32,
+                            page: record[18] as usize,
+                        });
+                    }
+                }
+                5 => {
+                    for record in block.chunks_exact(10) {
+                        data.kernings.push((u32_at(record, 0), u32_at(record, 4), i16_at(record, 8) as i32));
+                    }
+                }
+                _ => {}
+            }
+            position = start + size;
+        }
+        if data.pages.is_empty() {
+            return Err("Bitmap font has no pages".to_string());
+        }
+        Ok(data)
+    }
+}
+
+// Splits on spaces outside of quotes, so face="Press Start 2P" stays one field
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for ch in line.chars() {
+        match ch {
+            '"' => {
+                quoted = !quoted;
+                current.push(ch);
+            }
+            ' ' | '\t' if !quoted => {
+                if !current.is_empty() {
+                    fields.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(ch),
+        }
+    }
+    if !current.is_empty() {
+        fields.push(current);
+    }
+    fields
+}
+
+fn null_terminated(block: &[u8]) -> Vec<String> {
+    block
+        .split(|&byte| byte == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect()
+}
+
+// Pixel font drawn straight from its page images, without any filtering.
+// Glyphs should be white in the pages so the text color can be applied.
+pub struct BitmapFont<'a> {
+    pub data: BitmapFontData,
+    pages: Vec<Texture<'a>>,
+    glyphs: HashMap<char, Glyph>,
+    kernings: HashMap<(char, char), i32>,
+}
+
+#[allow(dead_code)]
+impl<'a> BitmapFont<'a> {
+    pub fn load(texture_creator: &'a TextureCreator<WindowContext>, path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let data = BitmapFontData::parse(&bytes)?;
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        // Nearest neighbour sampling keeps the pixels sharp when the text is scaled
+        let scale_quality = sdl2::hint::get("SDL_RENDER_SCALE_QUALITY");
+        sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "0");
+        let pages: Result<Vec<Texture<'a>>, String> = data.pages.iter()
+            .map(|file| {
+                let mut page = texture_creator.load_texture(directory.join(file))?;
+                page.set_blend_mode(BlendMode::Blend);
+                Ok(page)
+            })
+            .collect();
+        sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", scale_quality.as_deref().unwrap_or("0"));
+        Ok(Self::from_data(data, pages?))
+    }
+
+    pub fn from_data(data: BitmapFontData, pages: Vec<Texture<'a>>) -> Self {
+        let glyphs = data.chars.iter()
+            .filter_map(|c| {
+                let ch = char::from_u32(c.id)?;
+                let src = if c.width > 0 && c.height > 0 { Some(Rect::new(c.x, c.y, c.width, c.height)) } else { None };
+                Some((ch, Glyph { page: c.page, src, offset: (c.x_offset, c.y_offset), advance: c.x_advance }))
+            })
+            .collect();
+        let kernings = data.kernings.iter()
+            .filter_map(|&(first, second, amount)| Some(((char::from_u32(first)?, char::from_u32(second)?), amount)))
+            .collect();
+        Self { data, pages, glyphs, kernings }
+    }
+
+    pub fn shared(self) -> SharedGlyphSource<'a> {
+        Rc::new(RefCell::new(self))
+    }
+}
+
+impl<'a> GlyphSource<'a> for BitmapFont<'a> {
+    fn glyph(&mut self, ch: char) -> Result<Option<Glyph>, String> {
+        Ok(self.glyphs.get(&ch).copied())
+    }
+
+    fn page_mut(&mut self, page: usize) -> Option<&mut Texture<'a>> {
+        self.pages.get_mut(page)
+    }
+
+    fn line_height(&self) -> i32 {
+        self.data.line_height
+    }
+
+    fn kerning(&self, left: char, right: char) -> i32 {
+        self.kernings.get(&(left, right)).copied().unwrap_or(0)
+    }
+}
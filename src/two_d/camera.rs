@@ -7,13 +7,29 @@ use crate::two_d::Rect;
 // use gl::types::*;
 use nalgebra::Vector2;
 
+use rand::Rng;
+
 use serde::{Deserialize, Serialize};
 use serde::ser::SerializeStruct;
 
 // camera
 pub struct Camera {
-    pub position: Vector2<i32>,
+    pub position: Vector2<i32>, // Top-left corner of the view in world space (at zoom 1.0)
     pub size: Vector2<u32>,
+    pub zoom: f32,
+    pub rotation: f32, // Degrees, clockwise
+    pub damping: f32, // Follow speed per second, 0.0 snaps to the target
+    pub dead_zone: Option<Rect>, // Relative to the view center in screen pixels, e.g. Rect::new(-50, -30, 100, 60)
+    pub bounds: Option<Rect>, // The view never leaves this world rect
+    pub trauma: f32, // 0.0..=1.0, shake strength is trauma squared
+    pub trauma_decay: f32, // Trauma removed per second
+    pub max_shake_offset: f32, // Pixels
+    pub max_shake_angle: f32, // Degrees
+    pub viewport: Option<Rect>, // Area of the window this camera draws into, None fills the window
+    pub render_order: i32, // Lower values are drawn first
+    exact_position: Vector2<f32>,
+    shake_offset: Vector2<f32>,
+    shake_angle: f32,
 }
 
 impl Serialize for Camera {
@@ -21,9 +37,11 @@ impl Serialize for Camera {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("Camera", 2)?;
+        let mut state = serializer.serialize_struct("Camera", 4)?;
         state.serialize_field("position", &[self.position.x, self.position.y])?;
         state.serialize_field("size", &[self.size.x, self.size.y])?;
+        state.serialize_field("zoom", &self.zoom)?;
+        state.serialize_field("rotation", &self.rotation)?;
         state.end()
     }
 }
@@ -35,7 +53,7 @@ impl<'de> Deserialize<'de> for Camera {
     {
         #[derive(Deserialize)]
         #[serde(field_identifier, rename_all = "lowercase")]
-        enum Field { Position, Size }
+        enum Field { Position, Size, Zoom, Rotation }
 
         struct CameraVisitor;
 
@@ -52,6 +70,8 @@ impl<'de> Deserialize<'de> for Camera {
             {
                 let mut position = None;
                 let mut size = None;
+                let mut zoom = None;
+                let mut rotation = None;
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Position => {
@@ -62,15 +82,25 @@ impl<'de> Deserialize<'de> for Camera {
                             let coords: [i32; 2] = map.next_value()?;
                             size = Some(Vector2::new(coords[0] as u32, coords[1] as u32));
                         }
+                        Field::Zoom => {
+                            zoom = Some(map.next_value()?);
+                        }
+                        Field::Rotation => {
+                            rotation = Some(map.next_value()?);
+                        }
                     }
                 }
                 let position = position.ok_or_else(|| serde::de::Error::missing_field("position"))?;
                 let size = size.ok_or_else(|| serde::de::Error::missing_field("size"))?;
-                Ok(Camera { position, size })
+                let mut camera = Camera::new(position, size);
+                // Older scenes only stored position and size
+                camera.zoom = zoom.unwrap_or(1.0);
+                camera.rotation = rotation.unwrap_or(0.0);
+                Ok(camera)
             }
         }
 
-        const FIELDS: &'static [&'static str] = &["position", "size"];
+        const FIELDS: &'static [&'static str] = &["position", "size", "zoom", "rotation"];
         deserializer.deserialize_struct("Camera", FIELDS, CameraVisitor)
     }
 }
@@ -78,25 +108,299 @@ impl<'de> Deserialize<'de> for Camera {
 #[allow(dead_code)]
 impl Camera {
     pub fn new(position: Vector2<i32>, size: Vector2<u32>) -> Self {
-        Self { position, size }
+        Self {
+            position,
+            size,
+            zoom: 1.0,
+            rotation: 0.0,
+            damping: 0.0,
+            dead_zone: None,
+            bounds: None,
+            trauma: 0.0,
+            trauma_decay: 1.0,
+            max_shake_offset: 12.0,
+            max_shake_angle: 3.0,
+            viewport: None,
+            render_order: 0,
+            exact_position: Vector2::new(position.x as f32, position.y as f32),
+            shake_offset: Vector2::zeros(),
+            shake_angle: 0.0,
+        }
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.max(0.01);
+        self.clamp_to_bounds();
+    }
+
+    pub fn set_rotation(&mut self, degrees: f32) {
+        self.rotation = degrees % 360.0;
+    }
+
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping.max(0.0);
+    }
+
+    pub fn set_dead_zone(&mut self, dead_zone: Option<Rect>) {
+        self.dead_zone = dead_zone;
+    }
+
+    pub fn set_bounds(&mut self, bounds: Option<Rect>) {
+        self.bounds = bounds;
+        self.clamp_to_bounds();
+    }
+
+    // The camera size follows the viewport so the view is not stretched
+    pub fn set_viewport(&mut self, viewport: Option<Rect>) {
+        if let Some(rect) = viewport {
+            self.size = Vector2::new(rect.width(), rect.height());
+        }
+        self.viewport = viewport;
+        self.clamp_to_bounds();
+    }
+
+    pub fn set_render_order(&mut self, render_order: i32) {
+        self.render_order = render_order;
+    }
+
+    // Window coordinates (e.g. the mouse) to world coordinates, taking the viewport offset into account
+    pub fn window_to_world(&self, window: Vector2<f32>) -> Vector2<f32> {
+        match self.viewport {
+            Some(viewport) => self.screen_to_world(window - Vector2::new(viewport.x() as f32, viewport.y() as f32)),
+            None => self.screen_to_world(window),
+        }
     }
 
+    // Snaps the view center to the target
     pub fn update(&mut self, target_position: Vector2<i32>) {
         self.position.x = target_position.x - self.size.x as i32 / 2;
         self.position.y = target_position.y - self.size.y as i32 / 2;
+        self.exact_position = Vector2::new(self.position.x as f32, self.position.y as f32);
+        self.clamp_to_bounds();
+    }
+
+    // Moves the view towards the target, respecting the dead zone, damping and bounds.
+    // Call once per frame, it also advances the screen shake.
+    pub fn follow(&mut self, target_position: Vector2<f32>, delta_time: f32) {
+        self.sync_exact_position();
+        let current = self.exact_center();
+
+        let mut desired = target_position;
+        if let Some(dead_zone) = self.dead_zone {
+            desired = current;
+            let left = current.x + dead_zone.x() as f32 / self.zoom;
+            let right = left + dead_zone.width() as f32 / self.zoom;
+            let top = current.y + dead_zone.y() as f32 / self.zoom;
+            let bottom = top + dead_zone.height() as f32 / self.zoom;
+            if target_position.x < left {
+                desired.x += target_position.x - left;
+            } else if target_position.x > right {
+                desired.x += target_position.x - right;
+            }
+            if target_position.y < top {
+                desired.y += target_position.y - top;
+            } else if target_position.y > bottom {
+                desired.y += target_position.y - bottom;
+            }
+        }
+
+        // Frame-rate independent exponential smoothing
+        let t = if self.damping <= 0.0 { 1.0 } else { 1.0 - (-self.damping * delta_time).exp() };
+        let center = current + (desired - current) * t;
+        self.exact_position = center - self.half_size();
+        self.round_position();
+        self.clamp_to_bounds();
+
+        self.update_shake(delta_time);
     }
 
+    // Adds shake, e.g. 0.3 for a hit and 0.8 for an explosion
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn update_shake(&mut self, delta_time: f32) {
+        self.trauma = (self.trauma - self.trauma_decay * delta_time).max(0.0);
+        let shake = self.trauma * self.trauma;
+        if shake > 0.0 {
+            let mut rng = rand::thread_rng();
+            self.shake_offset = Vector2::new(
+                self.max_shake_offset * shake * rng.gen_range(-1.0..=1.0),
+                self.max_shake_offset * shake * rng.gen_range(-1.0..=1.0),
+            );
+            self.shake_angle = self.max_shake_angle * shake * rng.gen_range(-1.0..=1.0);
+        } else {
+            self.shake_offset = Vector2::zeros();
+            self.shake_angle = 0.0;
+        }
+    }
+
+    // World position in the middle of the view
+    pub fn center(&self) -> Vector2<f32> {
+        Vector2::new(self.position.x as f32, self.position.y as f32) + self.half_size()
+    }
+
+    // Angle the view is turned by on screen, render_rotated copies the drawn view with it
+    pub fn render_angle(&self) -> f64 {
+        -(self.rotation + self.shake_angle) as f64
+    }
+
+    pub fn is_rotated(&self) -> bool {
+        self.rotation + self.shake_angle != 0.0
+    }
+
+    // Position in the unrotated view renderers draw into
+    pub fn world_to_view(&self, world: Vector2<f32>) -> Vector2<f32> {
+        (world - self.center()) * self.zoom + self.half_size() + self.shake_offset
+    }
+
+    // Position on screen once the view is rotated, e.g. for UI over the world or the light map
+    pub fn world_to_screen(&self, world: Vector2<f32>) -> Vector2<f32> {
+        let relative = self.rotate(world - self.center(), -(self.rotation + self.shake_angle));
+        relative * self.zoom + self.half_size() + self.shake_offset
+    }
+
+    pub fn screen_to_world(&self, screen: Vector2<f32>) -> Vector2<f32> {
+        let relative = (screen - self.half_size() - self.shake_offset) / self.zoom;
+        self.rotate(relative, self.rotation + self.shake_angle) + self.center()
+    }
+
+    // Rects are always axis aligned in the view, rotation is applied to the whole view by render_rotated
     pub fn transform_rect(&self, rect: &Rect) -> Option<sdl2::rect::Rect> {
-        // Calculate the relative position of the rect to the camera
-        let transformed_x = rect.x() - self.position.x;
-        let transformed_y = rect.y() - self.position.y;
-
-        // Create a new SDL Rect with the transformed position
-        Some(sdl2::rect::Rect::new(
-            transformed_x,
-            transformed_y,
-            rect.width(),
-            rect.height(),
-        ))
+        // Both edges go through the transform so neighbouring tiles stay seamless when zoomed
+        let top_left = self.world_to_view(Vector2::new(rect.x() as f32, rect.y() as f32));
+        let bottom_right = self.world_to_view(Vector2::new(
+            rect.x() as f32 + rect.width() as f32,
+            rect.y() as f32 + rect.height() as f32,
+        ));
+        let x = top_left.x.floor() as i32;
+        let y = top_left.y.floor() as i32;
+        let width = (bottom_right.x.floor() as i32 - x).max(1) as u32;
+        let height = (bottom_right.y.floor() as i32 - y).max(1) as u32;
+        Some(sdl2::rect::Rect::new(x, y, width, height))
+    }
+
+    // Side of the square texture render_rotated draws into, it covers the view at any angle
+    pub fn rotation_target_size(&self) -> u32 {
+        ((self.size.x as f32).hypot(self.size.y as f32).ceil() as u32).max(1)
+    }
+
+    pub fn create_rotation_target<'a>(&self, texture_creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>) -> Result<sdl2::render::Texture<'a>, String> {
+        let size = self.rotation_target_size();
+        let mut texture = texture_creator.create_texture_target(None, size, size).map_err(|e| e.to_string())?;
+        texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+        Ok(texture)
+    }
+
+    // Draws the view turned by its rotation and shake. draw renders the scene axis aligned into target
+    // with an unrotated camera covering the whole texture, which is then copied once around the view
+    // center, so tiles and sprites turn together without gaps. Unrotated views draw straight to the canvas.
+    pub fn render_rotated<F>(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, target: &mut sdl2::render::Texture, mut draw: F) -> Result<(), String>
+    where
+        F: FnMut(&mut sdl2::render::Canvas<sdl2::video::Window>, &Camera) -> Result<(), String>,
+    {
+        if !self.is_rotated() {
+            return draw(canvas, self);
+        }
+        let query = target.query();
+        let size = query.width.min(query.height);
+        let view = self.unrotated_view(size);
+        let mut result = Ok(());
+        canvas.with_texture_canvas(target, |target_canvas| {
+            target_canvas.set_draw_color(sdl2::pixels::Color::RGBA(0, 0, 0, 0));
+            target_canvas.clear();
+            result = draw(target_canvas, &view);
+        }).map_err(|e| e.to_string())?;
+        result?;
+
+        let center = self.half_size() + self.shake_offset;
+        let dest = sdl2::rect::Rect::from_center(sdl2::rect::Point::new(center.x.round() as i32, center.y.round() as i32), size, size);
+        canvas.copy_ex(target, Some(sdl2::rect::Rect::new(0, 0, size, size)), Some(dest), self.render_angle(), None, false, false)
+    }
+
+    // World space rect covering everything the view can see
+    pub fn visible_world_rect(&self) -> Rect {
+        let (width, height) = (self.size.x as f32, self.size.y as f32);
+        let corners = [
+            self.screen_to_world(Vector2::new(0.0, 0.0)),
+            self.screen_to_world(Vector2::new(width, 0.0)),
+            self.screen_to_world(Vector2::new(0.0, height)),
+            self.screen_to_world(Vector2::new(width, height)),
+        ];
+        let min_x = corners.iter().map(|c| c.x).fold(f32::MAX, f32::min);
+        let min_y = corners.iter().map(|c| c.y).fold(f32::MAX, f32::min);
+        let max_x = corners.iter().map(|c| c.x).fold(f32::MIN, f32::max);
+        let max_y = corners.iter().map(|c| c.y).fold(f32::MIN, f32::max);
+        Rect::new(
+            min_x.floor() as i32,
+            min_y.floor() as i32,
+            (max_x - min_x).ceil() as u32 + 1,
+            (max_y - min_y).ceil() as u32 + 1,
+        )
+    }
+
+    pub fn is_visible(&self, rect: &Rect) -> bool {
+        let view = self.visible_world_rect();
+        rect.x() < view.x() + view.width() as i32
+            && rect.x() + rect.width() as i32 > view.x()
+            && rect.y() < view.y() + view.height() as i32
+            && rect.y() + rect.height() as i32 > view.y()
+    }
+
+    // Same center and zoom, square, without rotation or shake
+    fn unrotated_view(&self, size: u32) -> Camera {
+        let center = self.center();
+        let half = size as f32 / 2.0;
+        let mut view = Camera::new(Vector2::new((center.x - half).round() as i32, (center.y - half).round() as i32), Vector2::new(size, size));
+        view.zoom = self.zoom;
+        view
+    }
+
+    // Center before rounding, follow and the bounds work from it so damping keeps its sub-pixel progress
+    fn exact_center(&self) -> Vector2<f32> {
+        self.exact_position + self.half_size()
+    }
+
+    fn round_position(&mut self) {
+        self.position = Vector2::new(self.exact_position.x.round() as i32, self.exact_position.y.round() as i32);
+    }
+
+    fn half_size(&self) -> Vector2<f32> {
+        Vector2::new(self.size.x as f32 / 2.0, self.size.y as f32 / 2.0)
+    }
+
+    fn rotate(&self, v: Vector2<f32>, degrees: f32) -> Vector2<f32> {
+        if degrees == 0.0 {
+            return v;
+        }
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Vector2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+    }
+
+    // Keeps sub-pixel movement unless the position was changed from outside
+    fn sync_exact_position(&mut self) {
+        if self.exact_position.x.round() as i32 != self.position.x || self.exact_position.y.round() as i32 != self.position.y {
+            self.exact_position = Vector2::new(self.position.x as f32, self.position.y as f32);
+        }
+    }
+
+    fn clamp_to_bounds(&mut self) {
+        if let Some(bounds) = self.bounds {
+            self.sync_exact_position();
+            let half_view = self.half_size() / self.zoom;
+            let half_size = self.half_size();
+            let mut center = self.exact_center();
+            let clamp_axis = |value: f32, min: f32, length: f32, half: f32| {
+                if length <= half * 2.0 {
+                    min + length / 2.0
+                } else {
+                    value.clamp(min + half, min + length - half)
+                }
+            };
+            center.x = clamp_axis(center.x, bounds.x() as f32, bounds.width() as f32, half_view.x);
+            center.y = clamp_axis(center.y, bounds.y() as f32, bounds.height() as f32, half_view.y);
+            self.exact_position = center - half_size;
+            self.round_position();
+        }
     }
 }
\ No newline at end of file
@@ -99,4 +99,36 @@ impl Camera3D {
         self.position = target_position;
         self.direction = target_direction;
     }
+
+    // Turns direction and plane together so the field of view stays the same
+    pub fn rotate(&mut self, angle: f64) {
+        let (sin, cos) = angle.sin_cos();
+        self.direction = Vector2::new(self.direction.x * cos - self.direction.y * sin, self.direction.x * sin + self.direction.y * cos);
+        self.plane = Vector2::new(self.plane.x * cos - self.plane.y * sin, self.plane.x * sin + self.plane.y * cos);
+    }
+
+    pub fn forward(&self) -> Vector2<f64> {
+        self.direction.try_normalize(f64::EPSILON).unwrap_or(Vector2::new(1.0, 0.0))
+    }
+
+    // Points to the right of the view, for strafing
+    pub fn right(&self) -> Vector2<f64> {
+        self.plane.try_normalize(f64::EPSILON).unwrap_or(Vector2::new(-self.forward().y, self.forward().x))
+    }
+
+    // Moves by delta (in cells) while keeping radius away from blocked cells.
+    // Each axis moves on its own, so running into a wall at an angle slides along it.
+    pub fn move_with_collision(&mut self, delta: Vector2<f64>, radius: f64, is_blocked: impl Fn(i32, i32) -> bool) {
+        let blocked = |x: f64, y: f64| {
+            [(-radius, -radius), (radius, -radius), (-radius, radius), (radius, radius)]
+                .iter()
+                .any(|(dx, dy)| is_blocked((x + dx).floor() as i32, (y + dy).floor() as i32))
+        };
+        if !blocked(self.position.x + delta.x, self.position.y) {
+            self.position.x += delta.x;
+        }
+        if !blocked(self.position.x, self.position.y + delta.y) {
+            self.position.y += delta.y;
+        }
+    }
 }
\ No newline at end of file
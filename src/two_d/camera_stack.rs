@@ -0,0 +1,188 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::camera::Camera;
+use crate::two_d::Rect;
+
+use sdl2::render::{Canvas, Texture};
+
+// One camera of the stack. Cameras with a target render into a texture (minimap, picture-in-picture)
+// instead of their viewport on the window.
+pub struct CameraView<'a> {
+    pub camera: Camera,
+    pub target: Option<Texture<'a>>,
+    pub clear_color: Option<sdl2::pixels::Color>,
+    pub enabled: bool,
+    pub rotation_target: Option<Texture<'a>>, // Window cameras only turn with this, see Camera::render_rotated
+}
+
+// Renders the same scene once per camera: split-screen, minimaps, picture-in-picture
+pub struct CameraStack<'a> {
+    pub views: Vec<CameraView<'a>>,
+}
+
+impl<'a> Default for CameraStack<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl<'a> CameraStack<'a> {
+    pub fn new() -> Self {
+        Self { views: Vec::new() }
+    }
+
+    // Creates one camera per player laid out with split_screen_viewports
+    pub fn split_screen(window_width: u32, window_height: u32, players: usize) -> Self {
+        let mut stack = Self::new();
+        for (index, viewport) in split_screen_viewports(window_width, window_height, players).into_iter().enumerate() {
+            let mut camera = Camera::new(nalgebra::Vector2::new(0, 0), nalgebra::Vector2::new(viewport.width(), viewport.height()));
+            camera.set_viewport(Some(viewport));
+            camera.set_render_order(index as i32);
+            stack.add_camera(camera);
+        }
+        stack
+    }
+
+    pub fn add_camera(&mut self, camera: Camera) -> usize {
+        self.views.push(CameraView { camera, target: None, clear_color: None, enabled: true, rotation_target: None });
+        self.views.len() - 1
+    }
+
+    // The camera renders into its own texture, sized like the camera
+    pub fn add_texture_camera(
+        &mut self,
+        mut camera: Camera,
+        texture_creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>,
+        clear_color: sdl2::pixels::Color,
+    ) -> Result<usize, String> {
+        camera.viewport = None;
+        let mut texture = texture_creator.create_texture_target(None, camera.size.x, camera.size.y).map_err(|e| e.to_string())?;
+        texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+        self.views.push(CameraView { camera, target: Some(texture), clear_color: Some(clear_color), enabled: true, rotation_target: None });
+        Ok(self.views.len() - 1)
+    }
+
+    pub fn camera(&self, index: usize) -> Option<&Camera> {
+        self.views.get(index).map(|view| &view.camera)
+    }
+
+    pub fn camera_mut(&mut self, index: usize) -> Option<&mut Camera> {
+        self.views.get_mut(index).map(|view| &mut view.camera)
+    }
+
+    pub fn set_clear_color(&mut self, index: usize, clear_color: Option<sdl2::pixels::Color>) {
+        if let Some(view) = self.views.get_mut(index) {
+            view.clear_color = clear_color;
+        }
+    }
+
+    // Lets a window camera draw its rotation and shake angle
+    pub fn enable_rotation(&mut self, index: usize, texture_creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>) -> Result<(), String> {
+        let view = self.views.get_mut(index).ok_or(format!("No camera {}", index))?;
+        view.rotation_target = Some(view.camera.create_rotation_target(texture_creator)?);
+        Ok(())
+    }
+
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        if let Some(view) = self.views.get_mut(index) {
+            view.enabled = enabled;
+        }
+    }
+
+    // Calls draw once per enabled camera in render order. Window cameras are clipped to their viewport,
+    // texture cameras draw into their target. Coordinates inside draw are relative to the view, rotated
+    // window cameras get an unrotated camera there and the view is turned afterwards.
+    pub fn render<F>(&mut self, canvas: &mut Canvas<sdl2::video::Window>, mut draw: F) -> Result<(), String>
+    where
+        F: FnMut(&mut Canvas<sdl2::video::Window>, &Camera) -> Result<(), String>,
+    {
+        let mut order: Vec<usize> = (0..self.views.len()).collect();
+        order.sort_by_key(|&index| self.views[index].camera.render_order);
+
+        for index in order {
+            let view = &mut self.views[index];
+            if !view.enabled {
+                continue;
+            }
+            let camera = &view.camera;
+            let clear_color = view.clear_color;
+
+            if let Some(target) = view.target.as_mut() {
+                let mut result = Ok(());
+                canvas.with_texture_canvas(target, |texture_canvas| {
+                    if let Some(color) = clear_color {
+                        texture_canvas.set_draw_color(color);
+                        texture_canvas.clear();
+                    }
+                    result = draw(texture_canvas, camera);
+                }).map_err(|e| e.to_string())?;
+                result?;
+            } else {
+                let viewport = camera.viewport.map(|rect| *rect.sdl_rect());
+                canvas.set_viewport(viewport);
+                // The clip rect is relative to the viewport
+                canvas.set_clip_rect(viewport.map(|rect| sdl2::rect::Rect::new(0, 0, rect.width(), rect.height())));
+                if let Some(color) = clear_color {
+                    canvas.set_draw_color(color);
+                    canvas.fill_rect(None)?;
+                }
+                let result = match view.rotation_target.as_mut() {
+                    Some(rotation_target) => camera.render_rotated(canvas, rotation_target, &mut draw),
+                    None => draw(canvas, camera),
+                };
+                canvas.set_clip_rect(None);
+                canvas.set_viewport(None);
+                result?;
+            }
+        }
+        Ok(())
+    }
+
+    // Draws the texture of a texture camera onto the window, e.g. a minimap in a corner
+    pub fn copy_target(&self, canvas: &mut Canvas<sdl2::video::Window>, index: usize, dest: sdl2::rect::Rect) -> Result<(), String> {
+        match self.views.get(index).and_then(|view| view.target.as_ref()) {
+            Some(texture) => canvas.copy(texture, None, dest),
+            None => Err(format!("Camera {} has no render target", index)),
+        }
+    }
+
+    // Topmost window camera whose viewport contains the point, useful for routing mouse input
+    pub fn view_at(&self, x: i32, y: i32) -> Option<usize> {
+        let mut order: Vec<usize> = (0..self.views.len()).collect();
+        order.sort_by_key(|&index| std::cmp::Reverse(self.views[index].camera.render_order));
+        order.into_iter().find(|&index| {
+            let view = &self.views[index];
+            view.enabled && view.target.is_none() && match view.camera.viewport {
+                Some(viewport) => viewport.sdl_rect().contains_point(sdl2::rect::Point::new(x, y)),
+                None => true,
+            }
+        })
+    }
+}
+
+// 1 player fills the window, 2 are side by side, 3 use two on top and a wide one below, 4 are quadrants
+pub fn split_screen_viewports(window_width: u32, window_height: u32, players: usize) -> Vec<Rect> {
+    let half_width = window_width / 2;
+    let half_height = window_height / 2;
+    match players {
+        0 => Vec::new(),
+        1 => vec![Rect::new(0, 0, window_width, window_height)],
+        2 => vec![
+            Rect::new(0, 0, half_width, window_height),
+            Rect::new(half_width as i32, 0, window_width - half_width, window_height),
+        ],
+        3 => vec![
+            Rect::new(0, 0, half_width, half_height),
+            Rect::new(half_width as i32, 0, window_width - half_width, half_height),
+            Rect::new(0, half_height as i32, window_width, window_height - half_height),
+        ],
+        _ => vec![
+            Rect::new(0, 0, half_width, half_height),
+            Rect::new(half_width as i32, 0, window_width - half_width, half_height),
+            Rect::new(0, half_height as i32, half_width, window_height - half_height),
+            Rect::new(half_width as i32, half_height as i32, window_width - half_width, window_height - half_height),
+        ],
+    }
+}
@@ -0,0 +1,162 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Canvas;
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+// Reads what has been drawn so far this frame. Call it before canvas.present().
+pub fn read_canvas(canvas: &Canvas<sdl2::video::Window>) -> Result<RgbaImage, String> {
+    let (width, height) = canvas.output_size()?;
+    let pixels = canvas.read_pixels(None, PixelFormatEnum::RGBA32)?;
+    let mut image = RgbaImage::from_raw(width, height, pixels).ok_or("Canvas pixels do not match the output size")?;
+    // The back buffer alpha is undefined, screenshots are always opaque
+    for pixel in image.pixels_mut() {
+        pixel[3] = 255;
+    }
+    Ok(image)
+}
+
+pub fn save_png(image: &RgbaImage, path: &Path) -> Result<(), String> {
+    if let Some(directory) = path.parent() {
+        if !directory.as_os_str().is_empty() {
+            std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+    }
+    image.save_with_format(path, image::ImageFormat::Png).map_err(|e| e.to_string())
+}
+
+// e.g. screenshot_2024-05-01_18-30-12-345.png, milliseconds keep quick presses apart
+pub fn timestamped_path(directory: &Path, prefix: &str, extension: &str) -> PathBuf {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S-%3f");
+    directory.join(format!("{}_{}.{}", prefix, timestamp, extension))
+}
+
+// Saves the canvas as a PNG in directory and returns the file that was written
+pub fn save_screenshot(canvas: &Canvas<sdl2::video::Window>, directory: &Path) -> Result<PathBuf, String> {
+    let image = read_canvas(canvas)?;
+    let path = timestamped_path(directory, "screenshot", "png");
+    save_png(&image, &path)?;
+    Ok(path)
+}
+
+// Keeps the last seconds of gameplay in memory so a clip can be saved after something happened
+pub struct GifRecorder {
+    pub seconds: f32,            // Length of the ring buffer
+    pub fps: u32,                // Frames captured per second, GIF delays are in 10ms steps so 50 is the maximum
+    pub downscale: u32,          // 2 stores every second pixel, keeps memory and file size down
+    pub quantization_speed: i32, // 1 (best palette) ..30 (fastest)
+    pub recording: bool,
+    frames: VecDeque<RgbaImage>,
+    time_since_capture: f32,
+}
+
+#[allow(dead_code)]
+impl GifRecorder {
+    pub fn new(seconds: f32, fps: u32) -> Self {
+        Self {
+            seconds,
+            fps: fps.clamp(1, 50),
+            downscale: 1,
+            quantization_speed: 10,
+            recording: true,
+            frames: VecDeque::new(),
+            time_since_capture: 0.0,
+        }
+    }
+
+    pub fn set_downscale(&mut self, downscale: u32) {
+        self.downscale = downscale.max(1);
+    }
+
+    pub fn set_quantization_speed(&mut self, speed: i32) {
+        self.quantization_speed = speed.clamp(1, 30);
+    }
+
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+        self.time_since_capture = 0.0;
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    // Seconds of gameplay currently in the buffer
+    pub fn duration(&self) -> f32 {
+        self.frames.len() as f32 / self.fps as f32
+    }
+
+    fn capacity(&self) -> usize {
+        ((self.seconds * self.fps as f32).ceil() as usize).max(1)
+    }
+
+    // Call once per frame before canvas.present(), only reads the canvas when a frame is due
+    pub fn capture(&mut self, canvas: &Canvas<sdl2::video::Window>, delta_time: f32) -> Result<(), String> {
+        if !self.recording {
+            return Ok(());
+        }
+        self.time_since_capture += delta_time;
+        let interval = 1.0 / self.fps as f32;
+        if !self.frames.is_empty() && self.time_since_capture < interval {
+            return Ok(());
+        }
+        // Do not try to catch up after a hitch, the clip just skips ahead
+        self.time_since_capture = (self.time_since_capture - interval).max(0.0).min(interval);
+
+        let mut image = read_canvas(canvas)?;
+        if self.downscale > 1 {
+            image = image::imageops::resize(
+                &image,
+                (image.width() / self.downscale).max(1),
+                (image.height() / self.downscale).max(1),
+                image::imageops::FilterType::Nearest,
+            );
+        }
+        self.frames.push_back(image);
+        while self.frames.len() > self.capacity() {
+            self.frames.pop_front();
+        }
+        Ok(())
+    }
+
+    // Writes the buffer as a looping GIF, every frame gets its own quantized 256 color palette
+    pub fn export_gif(&self, path: &Path) -> Result<(), String> {
+        if self.frames.is_empty() {
+            return Err("No frames recorded".to_string());
+        }
+        if let Some(directory) = path.parent() {
+            if !directory.as_os_str().is_empty() {
+                std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+            }
+        }
+        let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        let mut encoder = GifEncoder::new_with_speed(std::io::BufWriter::new(file), self.quantization_speed.clamp(1, 30));
+        encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+        let delay = Delay::from_numer_denom_ms(1000, self.fps);
+        for image in &self.frames {
+            encoder.encode_frame(Frame::from_parts(image.clone(), 0, 0, delay)).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    // Saves the buffer as clip_<timestamp>.gif in directory and returns the file that was written
+    pub fn save_gif(&self, directory: &Path) -> Result<PathBuf, String> {
+        let path = timestamped_path(directory, "clip", "gif");
+        self.export_gif(&path)?;
+        Ok(path)
+    }
+}
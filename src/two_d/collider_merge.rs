@@ -0,0 +1,135 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use sdl2::rect::Rect;
+
+use std::collections::HashMap;
+
+// Covers the solid cells with as few rects as the greedy pass finds: runs along a row first,
+// then grown down while the whole run below is solid too. Rects never overlap.
+pub fn merge_cells(solid: &[Vec<bool>], tile_size: (u32, u32)) -> Vec<Rect> {
+    let (tile_width, tile_height) = tile_size;
+    let is_solid = |x: usize, y: usize| solid.get(y).and_then(|row| row.get(x)).copied().unwrap_or(false);
+    let mut used: Vec<Vec<bool>> = solid.iter().map(|row| vec![false; row.len()]).collect();
+    let mut rects = Vec::new();
+    for y in 0..solid.len() {
+        for x in 0..solid[y].len() {
+            if !solid[y][x] || used[y][x] {
+                continue;
+            }
+            let mut width = 1;
+            while is_solid(x + width, y) && !used[y][x + width] {
+                width += 1;
+            }
+            let mut height = 1;
+            while (x..x + width).all(|cx| is_solid(cx, y + height) && !used[y + height][cx]) {
+                height += 1;
+            }
+            for row in used.iter_mut().skip(y).take(height) {
+                for cell in row.iter_mut().skip(x).take(width) {
+                    *cell = true;
+                }
+            }
+            rects.push(Rect::new(
+                x as i32 * tile_width as i32,
+                y as i32 * tile_height as i32,
+                width as u32 * tile_width,
+                height as u32 * tile_height,
+            ));
+        }
+    }
+    rects
+}
+
+// Closed outline around a solid region or a hole in it, corners only, in world pixels.
+// Points run clockwise around solid ground on screen, so the solid side is always on the right.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeChain {
+    pub points: Vec<(i32, i32)>,
+}
+
+#[allow(dead_code)]
+impl EdgeChain {
+    // Consecutive point pairs, including the one closing the loop
+    pub fn segments(&self) -> impl Iterator<Item = ((i32, i32), (i32, i32))> + '_ {
+        let count = self.points.len();
+        (0..count).map(move |i| (self.points[i], self.points[(i + 1) % count]))
+    }
+}
+
+// Outlines of the solid cells. Characters sliding along a wall never catch on inner corners
+// and lights only need these edges as occluders.
+pub fn outline_chains(solid: &[Vec<bool>], tile_size: (u32, u32)) -> Vec<EdgeChain> {
+    let is_solid = |x: i32, y: i32| {
+        x >= 0 && y >= 0 && solid.get(y as usize).and_then(|row| row.get(x as usize)).copied().unwrap_or(false)
+    };
+    // Directed unit edges between a solid and an empty cell, in grid corners
+    let mut outgoing: HashMap<(i32, i32), Vec<(i32, i32)>> = HashMap::new();
+    let mut edge_count = 0;
+    for (y, row) in solid.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            if !cell {
+                continue;
+            }
+            let (x, y) = (x as i32, y as i32);
+            let sides = [
+                (is_solid(x, y - 1), (x, y), (x + 1, y)),
+                (is_solid(x + 1, y), (x + 1, y), (x + 1, y + 1)),
+                (is_solid(x, y + 1), (x + 1, y + 1), (x, y + 1)),
+                (is_solid(x - 1, y), (x, y + 1), (x, y)),
+            ];
+            for (neighbour_solid, from, to) in sides {
+                if !neighbour_solid {
+                    outgoing.entry(from).or_default().push(to);
+                    edge_count += 1;
+                }
+            }
+        }
+    }
+
+    let mut chains = Vec::new();
+    let mut starts: Vec<(i32, i32)> = outgoing.keys().copied().collect();
+    starts.sort();
+    for start in starts {
+        while outgoing.get(&start).is_some_and(|ends| !ends.is_empty()) {
+            let mut corners = vec![start];
+            let mut current = start;
+            let mut direction = (0, 0);
+            for _ in 0..edge_count {
+                let ends = match outgoing.get_mut(&current) {
+                    Some(ends) if !ends.is_empty() => ends,
+                    _ => break,
+                };
+                // Two cells touching only at a corner: turn right to stay around the same cell
+                let right = (-direction.1, direction.0);
+                let index = ends.iter()
+                    .position(|&end| (end.0 - current.0, end.1 - current.1) == right)
+                    .or_else(|| ends.iter().position(|&end| (end.0 - current.0, end.1 - current.1) == direction))
+                    .unwrap_or(0);
+                let next = ends.swap_remove(index);
+                let next_direction = (next.0 - current.0, next.1 - current.1);
+                if next_direction != direction && current != start {
+                    corners.push(current);
+                }
+                direction = next_direction;
+                current = next;
+                if current == start {
+                    break;
+                }
+            }
+            // The start is only a corner if the path turns there
+            if corners.len() > 1 {
+                let first_direction = (corners[1].0 - start.0, corners[1].1 - start.1);
+                let first_direction = (first_direction.0.signum(), first_direction.1.signum());
+                if first_direction == direction {
+                    corners.remove(0);
+                }
+            }
+            let (tile_width, tile_height) = (tile_size.0 as i32, tile_size.1 as i32);
+            chains.push(EdgeChain {
+                points: corners.into_iter().map(|(x, y)| (x * tile_width, y * tile_height)).collect(),
+            });
+        }
+    }
+    chains
+}
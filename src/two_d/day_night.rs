@@ -0,0 +1,187 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::light::AmbientFilter;
+use crate::two_d::lighting::LightingSystem;
+
+use sdl2::pixels::Color;
+
+// Ambient light at one time of day. intensity is how strongly the color tints the scene,
+// 0.0 leaves it untouched (noon) and 1.0 uses the color as is (night).
+#[derive(Clone, Copy, Debug)]
+pub struct TimeKeyframe {
+    pub time: f32, // 0.0..1.0 of the day, 0.0 is midnight and 0.5 is noon
+    pub color: Color,
+    pub intensity: f32,
+}
+
+impl TimeKeyframe {
+    pub fn new(time: f32, color: Color, intensity: f32) -> Self {
+        Self { time, color, intensity }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DayNightEvent {
+    Dawn,
+    Dusk,
+}
+
+// Lights of a LightingSystem that are only on at night
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NightLight {
+    Point(usize),
+    Spot(usize),
+}
+
+pub struct DayNightCycle {
+    pub time_of_day: f32, // 0.0..1.0
+    pub day_length: f32,  // Seconds of game time for a full day
+    pub time_scale: f32,
+    pub paused: bool,
+    pub dawn: f32, // Time of day the Dawn event fires
+    pub dusk: f32, // Time of day the Dusk event fires
+    pub keyframes: Vec<TimeKeyframe>,
+    pub night_lights: Vec<NightLight>,
+}
+
+#[allow(dead_code)]
+impl DayNightCycle {
+    // Starts in the morning with a default gradient: dark blue nights, orange dawn and dusk, neutral days
+    pub fn new(day_length: f32) -> Self {
+        Self {
+            time_of_day: 0.3,
+            day_length,
+            time_scale: 1.0,
+            paused: false,
+            dawn: 0.25,
+            dusk: 0.8,
+            keyframes: vec![
+                TimeKeyframe::new(0.0, Color::RGB(25, 30, 70), 0.85),
+                TimeKeyframe::new(0.22, Color::RGB(60, 50, 110), 0.7),
+                TimeKeyframe::new(0.28, Color::RGB(255, 170, 120), 0.35),
+                TimeKeyframe::new(0.4, Color::RGB(255, 245, 230), 0.05),
+                TimeKeyframe::new(0.5, Color::RGB(255, 255, 255), 0.0),
+                TimeKeyframe::new(0.7, Color::RGB(255, 240, 210), 0.05),
+                TimeKeyframe::new(0.78, Color::RGB(250, 120, 80), 0.4),
+                TimeKeyframe::new(0.86, Color::RGB(70, 50, 120), 0.7),
+            ],
+            night_lights: Vec::new(),
+        }
+    }
+
+    // Replaces the gradient, keyframes may be given in any order
+    pub fn set_keyframes(&mut self, mut keyframes: Vec<TimeKeyframe>) {
+        keyframes.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(std::cmp::Ordering::Equal));
+        self.keyframes = keyframes;
+    }
+
+    pub fn set_time_of_day(&mut self, time_of_day: f32) {
+        self.time_of_day = time_of_day.rem_euclid(1.0);
+    }
+
+    // 0.0..24.0
+    pub fn hours(&self) -> f32 {
+        self.time_of_day * 24.0
+    }
+
+    pub fn set_hours(&mut self, hours: f32) {
+        self.set_time_of_day(hours / 24.0);
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_night(&self) -> bool {
+        if self.dawn < self.dusk {
+            self.time_of_day < self.dawn || self.time_of_day >= self.dusk
+        } else {
+            self.time_of_day >= self.dusk && self.time_of_day < self.dawn
+        }
+    }
+
+    pub fn add_night_light(&mut self, light: NightLight) {
+        self.night_lights.push(light);
+    }
+
+    // Advances the clock and returns the dawn/dusk events passed during this step
+    pub fn update(&mut self, delta_time: f32) -> Vec<DayNightEvent> {
+        let mut events = Vec::new();
+        if self.paused || self.day_length <= 0.0 {
+            return events;
+        }
+        let step = delta_time * self.time_scale / self.day_length;
+        let start = self.time_of_day;
+        let end = start + step;
+        // Every dawn and dusk between the old and new time, also when a step skips whole days
+        let mut crossed = Vec::new();
+        for day in 0..=(end.floor().max(0.0) as i32) {
+            for &(time, event) in [(self.dawn, DayNightEvent::Dawn), (self.dusk, DayNightEvent::Dusk)].iter() {
+                let time = time + day as f32;
+                if start < time && time <= end {
+                    crossed.push((time, event));
+                }
+            }
+        }
+        crossed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        events.extend(crossed.into_iter().map(|(_, event)| event));
+        self.time_of_day = end.rem_euclid(1.0);
+        events
+    }
+
+    // Color and intensity interpolated between the surrounding keyframes, wrapping around midnight
+    pub fn current(&self) -> (Color, f32) {
+        match self.keyframes.len() {
+            0 => return (Color::RGB(255, 255, 255), 0.0),
+            1 => return (self.keyframes[0].color, self.keyframes[0].intensity),
+            _ => {}
+        }
+        let time = self.time_of_day;
+        let next_index = self.keyframes.iter().position(|keyframe| keyframe.time > time).unwrap_or(0);
+        let previous_index = if next_index == 0 { self.keyframes.len() - 1 } else { next_index - 1 };
+        let previous = &self.keyframes[previous_index];
+        let next = &self.keyframes[next_index];
+
+        let span = (next.time - previous.time).rem_euclid(1.0);
+        let t = if span > 0.0 { (time - previous.time).rem_euclid(1.0) / span } else { 0.0 };
+        let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        (
+            Color::RGB(lerp(previous.color.r, next.color.r), lerp(previous.color.g, next.color.g), lerp(previous.color.b, next.color.b)),
+            previous.intensity + (next.intensity - previous.intensity) * t,
+        )
+    }
+
+    // Keyframe color faded towards white by the intensity, ready to multiply the scene with
+    pub fn ambient_color(&self) -> Color {
+        let (color, intensity) = self.current();
+        let fade = |channel: u8| (255.0 + (channel as f32 - 255.0) * intensity.clamp(0.0, 1.0)).round() as u8;
+        Color::RGB(fade(color.r), fade(color.g), fade(color.b))
+    }
+
+    pub fn apply_to_ambient_filter(&self, filter: &mut AmbientFilter) {
+        let (color, intensity) = self.current();
+        filter.set_color(color);
+        filter.intensity = intensity;
+    }
+
+    // Sets the ambient light and switches the night lights on or off
+    pub fn apply_to_lighting(&self, lighting: &mut LightingSystem) {
+        lighting.set_ambient(self.ambient_color());
+        let night = self.is_night();
+        for light in &self.night_lights {
+            match *light {
+                NightLight::Point(index) => {
+                    if let Some(light) = lighting.point_light_mut(index) {
+                        light.enabled = night;
+                    }
+                }
+                NightLight::Spot(index) => {
+                    if let Some(light) = lighting.spot_light_mut(index) {
+                        light.enabled = night;
+                    }
+                }
+            }
+        }
+    }
+}
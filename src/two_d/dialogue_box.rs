@@ -1,6 +1,8 @@
 extern crate sdl2;
 // extern  crate gl;
 
+use crate::two_d::nine_slice::NineSlice;
+use crate::two_d::text::{self, SharedGlyphSource, TextOptions};
 use crate::two_d::ui::RectWrapper;
 
 use sdl2::render::Canvas;
@@ -11,8 +13,10 @@ use serde::ser::SerializeStruct;
 pub struct DialogueTextBox<'a> {
     pub speaker: Option<String>,
     pub text: String,
-    pub font: std::sync::Arc<sdl2::ttf::Font<'a, 'static>>,
+    pub font: Option<std::sync::Arc<sdl2::ttf::Font<'a, 'static>>>, // None when the text only draws through glyph_source
     pub rect: sdl2::rect::Rect,
+    pub glyph_source: Option<SharedGlyphSource<'a>>,
+    pub options: TextOptions,
 }
 
 impl<'a> Serialize for DialogueTextBox<'a> {
@@ -31,23 +35,57 @@ impl<'a> Serialize for DialogueTextBox<'a> {
 #[allow(dead_code)]
 impl<'a> DialogueTextBox<'a> {
     pub fn new(speaker: Option<String>, text: String, font: std::sync::Arc<sdl2::ttf::Font<'a, 'static>>, rect: sdl2::rect::Rect) -> Self {
-        Self { speaker, text, font, rect }
+        Self { speaker, text, font: Some(font), rect, glyph_source: None, options: TextOptions::default() }
+    }
+
+    // For games without a TTF font, e.g. only a bitmap font
+    pub fn with_glyph_source(speaker: Option<String>, text: String, glyph_source: SharedGlyphSource<'a>, rect: sdl2::rect::Rect) -> Self {
+        let mut text_box = Self { speaker, text, font: None, rect, glyph_source: None, options: TextOptions::default() };
+        text_box.set_glyph_source(glyph_source);
+        text_box
+    }
+
+    // Cached glyphs or a bitmap font: the text wraps inside the box and may use [color=#f00], [b] and [wave] markup
+    pub fn set_glyph_source(&mut self, glyph_source: SharedGlyphSource<'a>) {
+        self.glyph_source = Some(glyph_source);
+        self.options.max_width = Some(self.rect.width().saturating_sub(20));
     }
 
     pub fn render(&self, canvas: &mut Canvas<sdl2::video::Window>) -> Result<(), String> {
+        self.render_with_background(canvas, None)
+    }
+
+    // A nine-slice frame replaces the flat background
+    pub fn render_with_background(&self, canvas: &mut Canvas<sdl2::video::Window>, background: Option<&NineSlice<'a>>) -> Result<(), String> {
         // Draw dialogue background
-        canvas.set_draw_color(sdl2::pixels::Color::RGBA(0, 0, 0, 128));  // semi-transparent black
-        canvas.fill_rect(self.rect)?;
-        
+        match background {
+            Some(background) => background.render(canvas, self.rect)?,
+            None => {
+                canvas.set_draw_color(sdl2::pixels::Color::RGBA(0, 0, 0, 128));  // semi-transparent black
+                canvas.fill_rect(self.rect)?;
+            }
+        }
+
+        if let Some(source) = &self.glyph_source {
+            let mut source = source.borrow_mut();
+            if let Some(ref speaker) = self.speaker {
+                let speaker_y = self.rect.y - source.line_height() - 4;
+                text::draw_text(canvas, &mut *source, speaker, self.rect.x, speaker_y, sdl2::pixels::Color::RGBA(255, 255, 255, 255), &TextOptions::default())?;
+            }
+            text::draw_text(canvas, &mut *source, &self.text, self.rect.x + 10, self.rect.y + 10, sdl2::pixels::Color::RGBA(255, 255, 255, 255), &self.options)?;
+            return Ok(());
+        }
+
+        let font = self.font.as_deref().ok_or_else(|| "DialogueTextBox has no font or glyph source".to_string())?;
         if let Some(ref speaker) = self.speaker {
-            let surface = self.font.render(&speaker).blended(sdl2::pixels::Color::RGBA(255, 255, 255, 0)).map_err(|e| e.to_string())?;
+            let surface = font.render(&speaker).blended(sdl2::pixels::Color::RGBA(255, 255, 255, 0)).map_err(|e| e.to_string())?;
             let texture_creator = canvas.texture_creator();
             let texture = texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string())?;
             canvas.copy(&texture, None, sdl2::rect::Rect::new(self.rect.x, self.rect.y - 20, 120, 20))?; 
             // Offset by 20 pixels, adjust accordingly
         }
     
-        let surface = self.font.render(&self.text).blended(sdl2::pixels::Color::RGBA(255, 255, 255, 255)).map_err(|e| e.to_string())?;
+        let surface = font.render(&self.text).blended(sdl2::pixels::Color::RGBA(255, 255, 255, 255)).map_err(|e| e.to_string())?;
         let texture_creator = canvas.texture_creator();
         let texture = texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string())?;
         canvas.copy(&texture, None, self.rect)?;
@@ -61,7 +99,7 @@ pub struct DialogueOption<'a> {
     pub text: String,
     pub action: fn(),
     pub rect: sdl2::rect::Rect,  // New field
-    pub font: std::sync::Arc<sdl2::ttf::Font<'a, 'static>>,  // New field
+    pub font: Option<std::sync::Arc<sdl2::ttf::Font<'a, 'static>>>,  // None when the box draws options through its glyph source
 }
 
 // struct DialogueOption {
@@ -73,6 +111,8 @@ pub struct DialogueBox<'a> {
     text_boxes: Vec<std::rc::Rc<DialogueTextBox<'a>>>,
     options: Vec<DialogueOption<'a>>,
     pub is_active: bool,
+    pub background: Option<NineSlice<'a>>,
+    pub glyph_source: Option<SharedGlyphSource<'a>>, // Options are drawn with it instead of their TTF font
 }
 
 #[allow(dead_code)]
@@ -82,9 +122,19 @@ impl<'a> DialogueBox<'a> {
             text_boxes: Vec::new(),
             options: Vec::new(),
             is_active: false,
+            background: None,
+            glyph_source: None,
         }
     }
 
+    pub fn set_glyph_source(&mut self, glyph_source: SharedGlyphSource<'a>) {
+        self.glyph_source = Some(glyph_source);
+    }
+
+    pub fn set_background(&mut self, background: NineSlice<'a>) {
+        self.background = Some(background);
+    }
+
     pub fn activate(&mut self) {
         self.is_active = true;
     }
@@ -108,15 +158,22 @@ impl<'a> DialogueBox<'a> {
     
         // Render each text box
         for text_box in &self.text_boxes {
-            text_box.render(canvas)?;
+            text_box.render_with_background(canvas, self.background.as_ref())?;
         }
     
         let mut option_y_offset = 90;  // Start rendering options 90 pixels below the text
         for option in &self.options {
-            let surface = option.font.render(&option.text).blended(sdl2::pixels::Color::RGBA(255, 0, 0, 255)).map_err(|e| e.to_string())?;
-            let texture_creator = canvas.texture_creator();
-            let texture = texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string())?;
-            canvas.copy(&texture, None, sdl2::rect::Rect::new(self.text_boxes[0].rect.x + 10, self.text_boxes[0].rect.y + option_y_offset, self.text_boxes[0].rect.width() - 20, 20))?;
+            let dest = sdl2::rect::Rect::new(self.text_boxes[0].rect.x + 10, self.text_boxes[0].rect.y + option_y_offset, self.text_boxes[0].rect.width() - 20, 20);
+            let color = sdl2::pixels::Color::RGBA(255, 0, 0, 255);
+            if let Some(source) = &self.glyph_source {
+                text::draw_text(canvas, &mut *source.borrow_mut(), &option.text, dest.x(), dest.y(), color, &TextOptions::default())?;
+            } else {
+                let font = option.font.as_deref().ok_or_else(|| "DialogueOption has no font and the box has no glyph source".to_string())?;
+                let surface = font.render(&option.text).blended(color).map_err(|e| e.to_string())?;
+                let texture_creator = canvas.texture_creator();
+                let texture = texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string())?;
+                canvas.copy(&texture, None, dest)?;
+            }
             option_y_offset += 25;  // Adjust this value to change the space between options
         }
         
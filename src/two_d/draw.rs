@@ -0,0 +1,441 @@
+extern crate sdl2;
+// extern  crate gl;
+
+// Vector primitives for debug drawing and UI. Everything is drawn with horizontal spans,
+// each pixel at most once, so translucent colors blend evenly.
+
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{Canvas, RenderTarget};
+
+pub fn circle<T: RenderTarget>(canvas: &mut Canvas<T>, cx: i32, cy: i32, radius: i32, color: Color) -> Result<(), String> {
+    canvas.set_draw_color(color);
+    let mut points = Vec::new();
+    midpoint_circle(radius, |x, y| {
+        for &(px, py) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)].iter() {
+            points.push(Point::new(cx + px, cy + py));
+        }
+    });
+    points.sort_by_key(|point| (point.y(), point.x()));
+    points.dedup();
+    canvas.draw_points(&points[..])
+}
+
+pub fn fill_circle<T: RenderTarget>(canvas: &mut Canvas<T>, cx: i32, cy: i32, radius: i32, color: Color) -> Result<(), String> {
+    if radius <= 0 {
+        canvas.set_draw_color(color);
+        return canvas.draw_point(Point::new(cx, cy));
+    }
+    // Widest span of every row, the octants reach the same rows several times
+    let mut half_widths = vec![0; (radius * 2 + 1) as usize];
+    midpoint_circle(radius, |x, y| {
+        for &(row, half) in [(y, x), (-y, x), (x, y), (-x, y)].iter() {
+            let entry = &mut half_widths[(row + radius) as usize];
+            *entry = (*entry).max(half);
+        }
+    });
+    fill_rows(canvas, cx, cy - radius, &half_widths, color)
+}
+
+pub fn ellipse<T: RenderTarget>(canvas: &mut Canvas<T>, cx: i32, cy: i32, rx: i32, ry: i32, color: Color) -> Result<(), String> {
+    canvas.set_draw_color(color);
+    let mut points = Vec::new();
+    midpoint_ellipse(rx, ry, |x, y| {
+        for &(px, py) in [(x, y), (-x, y), (x, -y), (-x, -y)].iter() {
+            points.push(Point::new(cx + px, cy + py));
+        }
+    });
+    points.sort_by_key(|point| (point.y(), point.x()));
+    points.dedup();
+    canvas.draw_points(&points[..])
+}
+
+pub fn fill_ellipse<T: RenderTarget>(canvas: &mut Canvas<T>, cx: i32, cy: i32, rx: i32, ry: i32, color: Color) -> Result<(), String> {
+    if rx <= 0 || ry <= 0 {
+        return line(canvas, Point::new(cx - rx.max(0), cy - ry.max(0)), Point::new(cx + rx.max(0), cy + ry.max(0)), color);
+    }
+    let mut half_widths = vec![0; (ry * 2 + 1) as usize];
+    midpoint_ellipse(rx, ry, |x, y| {
+        for &row in [y, -y].iter() {
+            let entry = &mut half_widths[(row + ry) as usize];
+            *entry = (*entry).max(x);
+        }
+    });
+    fill_rows(canvas, cx, cy - ry, &half_widths, color)
+}
+
+// Outline between two angles in degrees, clockwise from the positive x axis (screen y points down)
+pub fn arc<T: RenderTarget>(canvas: &mut Canvas<T>, cx: i32, cy: i32, radius: i32, start_angle: f32, end_angle: f32, color: Color) -> Result<(), String> {
+    canvas.set_draw_color(color);
+    let mut points = Vec::new();
+    midpoint_circle(radius, |x, y| {
+        for &(px, py) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)].iter() {
+            if angle_in_range((py as f32).atan2(px as f32).to_degrees(), start_angle, end_angle) {
+                points.push(Point::new(cx + px, cy + py));
+            }
+        }
+    });
+    points.sort_by_key(|point| (point.y(), point.x()));
+    points.dedup();
+    canvas.draw_points(&points[..])
+}
+
+// Arc with a stroke width and round ends
+#[allow(clippy::too_many_arguments)] // Same call shape as arc, plus the thickness
+pub fn thick_arc<T: RenderTarget>(canvas: &mut Canvas<T>, cx: i32, cy: i32, radius: i32, start_angle: f32, end_angle: f32, thickness: f32, color: Color) -> Result<(), String> {
+    let mut sweep = (end_angle - start_angle).rem_euclid(360.0);
+    if sweep == 0.0 && end_angle != start_angle {
+        sweep = 360.0;
+    }
+    let segments = ((sweep / 360.0) * radius as f32 * 0.75).ceil().max(2.0) as usize;
+    let points: Vec<(f32, f32)> = (0..=segments).map(|i| {
+        let angle = (start_angle + sweep * i as f32 / segments as f32).to_radians();
+        (cx as f32 + angle.cos() * radius as f32, cy as f32 + angle.sin() * radius as f32)
+    }).collect();
+    stroke(canvas, &points, thickness, color)
+}
+
+pub fn line<T: RenderTarget>(canvas: &mut Canvas<T>, start: Point, end: Point, color: Color) -> Result<(), String> {
+    canvas.set_draw_color(color);
+    canvas.draw_line(start, end)
+}
+
+// Line with a width and round caps
+pub fn thick_line<T: RenderTarget>(canvas: &mut Canvas<T>, start: Point, end: Point, thickness: f32, color: Color) -> Result<(), String> {
+    let points = [(start.x() as f32, start.y() as f32), (end.x() as f32, end.y() as f32)];
+    stroke(canvas, &points, thickness, color)
+}
+
+// Connected lines with round joins
+pub fn polyline<T: RenderTarget>(canvas: &mut Canvas<T>, points: &[Point], thickness: f32, color: Color) -> Result<(), String> {
+    if thickness <= 1.0 {
+        canvas.set_draw_color(color);
+        return canvas.draw_lines(points);
+    }
+    let points: Vec<(f32, f32)> = points.iter().map(|point| (point.x() as f32, point.y() as f32)).collect();
+    stroke(canvas, &points, thickness, color)
+}
+
+pub fn polygon<T: RenderTarget>(canvas: &mut Canvas<T>, points: &[Point], color: Color) -> Result<(), String> {
+    if points.is_empty() {
+        return Ok(());
+    }
+    let mut closed = points.to_vec();
+    closed.push(points[0]);
+    canvas.set_draw_color(color);
+    canvas.draw_lines(&closed[..])
+}
+
+// Scanline fill with the even-odd rule, works for concave and self-intersecting polygons
+pub fn fill_polygon<T: RenderTarget>(canvas: &mut Canvas<T>, points: &[Point], color: Color) -> Result<(), String> {
+    if points.len() < 3 {
+        return polygon(canvas, points, color);
+    }
+    canvas.set_draw_color(color);
+    let min_y = points.iter().map(|point| point.y()).min().unwrap_or(0);
+    let max_y = points.iter().map(|point| point.y()).max().unwrap_or(0);
+    let mut crossings = Vec::new();
+    for y in min_y..max_y {
+        let center_y = y as f32 + 0.5;
+        crossings.clear();
+        for i in 0..points.len() {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            let (ay, by) = (a.y() as f32, b.y() as f32);
+            if (ay <= center_y && by > center_y) || (by <= center_y && ay > center_y) {
+                crossings.push(a.x() as f32 + (center_y - ay) / (by - ay) * (b.x() - a.x()) as f32);
+            }
+        }
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        for span in crossings.chunks_exact(2) {
+            let start = (span[0] - 0.5).ceil() as i32;
+            let end = (span[1] - 0.5).floor() as i32;
+            if start <= end {
+                canvas.draw_line(Point::new(start, y), Point::new(end, y))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn rounded_rect<T: RenderTarget>(canvas: &mut Canvas<T>, rect: Rect, radius: i32, color: Color) -> Result<(), String> {
+    let radius = clamp_radius(rect, radius);
+    let (left, top) = (rect.x(), rect.y());
+    let (right, bottom) = (rect.x() + rect.width() as i32 - 1, rect.y() + rect.height() as i32 - 1);
+    canvas.set_draw_color(color);
+    let mut points = vec![
+        Point::new(left + radius, top), Point::new(right - radius, top),
+        Point::new(left + radius, bottom), Point::new(right - radius, bottom),
+        Point::new(left, top + radius), Point::new(left, bottom - radius),
+        Point::new(right, top + radius), Point::new(right, bottom - radius),
+    ];
+    canvas.draw_line(points[0], points[1])?;
+    canvas.draw_line(points[2], points[3])?;
+    canvas.draw_line(points[4], points[5])?;
+    canvas.draw_line(points[6], points[7])?;
+    points.clear();
+    midpoint_circle(radius, |x, y| {
+        for &(px, py) in [(x, y), (y, x)].iter() {
+            points.push(Point::new(right - radius + px, bottom - radius + py));
+            points.push(Point::new(left + radius - px, bottom - radius + py));
+            points.push(Point::new(right - radius + px, top + radius - py));
+            points.push(Point::new(left + radius - px, top + radius - py));
+        }
+    });
+    points.sort_by_key(|point| (point.y(), point.x()));
+    points.dedup();
+    canvas.draw_points(&points[..])
+}
+
+pub fn fill_rounded_rect<T: RenderTarget>(canvas: &mut Canvas<T>, rect: Rect, radius: i32, color: Color) -> Result<(), String> {
+    let radius = clamp_radius(rect, radius);
+    if radius == 0 {
+        canvas.set_draw_color(color);
+        return canvas.fill_rect(rect);
+    }
+    // Corner inset of the rows in the top half of a corner, the bottom half mirrors it
+    let mut half_widths = vec![0; (radius * 2 + 1) as usize];
+    midpoint_circle(radius, |x, y| {
+        for &(row, half) in [(y, x), (-y, x), (x, y), (-x, y)].iter() {
+            let entry = &mut half_widths[(row + radius) as usize];
+            *entry = (*entry).max(half);
+        }
+    });
+    canvas.set_draw_color(color);
+    let (left, right) = (rect.x() + radius, rect.x() + rect.width() as i32 - 1 - radius);
+    for row in 0..rect.height() as i32 {
+        let y = rect.y() + row;
+        let from_bottom = rect.height() as i32 - 1 - row;
+        let half = if row < radius {
+            half_widths[row as usize]
+        } else if from_bottom < radius {
+            half_widths[from_bottom as usize]
+        } else {
+            radius
+        };
+        canvas.draw_line(Point::new(left - half, y), Point::new(right + half, y))?;
+    }
+    Ok(())
+}
+
+pub fn quadratic_bezier<T: RenderTarget>(canvas: &mut Canvas<T>, p0: Point, p1: Point, p2: Point, thickness: f32, color: Color) -> Result<(), String> {
+    let points = [to_f32(p0), to_f32(p1), to_f32(p2)];
+    let length = distance(points[0], points[1]) + distance(points[1], points[2]);
+    let segments = (length / 4.0).ceil().max(1.0) as usize;
+    let curve: Vec<(f32, f32)> = (0..=segments).map(|i| {
+        let t = i as f32 / segments as f32;
+        let u = 1.0 - t;
+        (
+            u * u * points[0].0 + 2.0 * u * t * points[1].0 + t * t * points[2].0,
+            u * u * points[0].1 + 2.0 * u * t * points[1].1 + t * t * points[2].1,
+        )
+    }).collect();
+    curve_lines(canvas, &curve, thickness, color)
+}
+
+pub fn cubic_bezier<T: RenderTarget>(canvas: &mut Canvas<T>, p0: Point, p1: Point, p2: Point, p3: Point, thickness: f32, color: Color) -> Result<(), String> {
+    let points = [to_f32(p0), to_f32(p1), to_f32(p2), to_f32(p3)];
+    let length = distance(points[0], points[1]) + distance(points[1], points[2]) + distance(points[2], points[3]);
+    let segments = (length / 4.0).ceil().max(1.0) as usize;
+    let curve: Vec<(f32, f32)> = (0..=segments).map(|i| {
+        let t = i as f32 / segments as f32;
+        let u = 1.0 - t;
+        let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+        (
+            a * points[0].0 + b * points[1].0 + c * points[2].0 + d * points[3].0,
+            a * points[0].1 + b * points[1].1 + c * points[2].1 + d * points[3].1,
+        )
+    }).collect();
+    curve_lines(canvas, &curve, thickness, color)
+}
+
+// Calls plot for every point of the first octant (x from 0 up to the diagonal)
+fn midpoint_circle<F: FnMut(i32, i32)>(radius: i32, mut plot: F) {
+    if radius <= 0 {
+        plot(0, 0);
+        return;
+    }
+    let mut x = 0;
+    let mut y = radius;
+    let mut decision = 1 - radius;
+    while x <= y {
+        plot(x, y);
+        x += 1;
+        if decision < 0 {
+            decision += 2 * x + 1;
+        } else {
+            y -= 1;
+            decision += 2 * (x - y) + 1;
+        }
+    }
+}
+
+// Calls plot for every point of the first quadrant
+fn midpoint_ellipse<F: FnMut(i32, i32)>(rx: i32, ry: i32, mut plot: F) {
+    let (rx2, ry2) = (rx as i64 * rx as i64, ry as i64 * ry as i64);
+    let (mut x, mut y) = (0i64, ry as i64);
+    let (mut dx, mut dy) = (0i64, 2 * rx2 * y);
+
+    // Region 1: slope above -1
+    let mut decision = ry2 - rx2 * ry as i64 + rx2 / 4;
+    while dx < dy {
+        plot(x as i32, y as i32);
+        x += 1;
+        dx += 2 * ry2;
+        if decision < 0 {
+            decision += dx + ry2;
+        } else {
+            y -= 1;
+            dy -= 2 * rx2;
+            decision += dx - dy + ry2;
+        }
+    }
+
+    // Region 2: slope below -1
+    let mut decision = (ry2 as f64 * (x as f64 + 0.5).powi(2) + rx2 as f64 * ((y - 1) as f64).powi(2) - (rx2 * ry2) as f64) as i64;
+    while y >= 0 {
+        plot(x as i32, y as i32);
+        y -= 1;
+        dy -= 2 * rx2;
+        if decision > 0 {
+            decision += rx2 - dy;
+        } else {
+            x += 1;
+            dx += 2 * ry2;
+            decision += dx - dy + rx2;
+        }
+    }
+}
+
+fn fill_rows<T: RenderTarget>(canvas: &mut Canvas<T>, cx: i32, top: i32, half_widths: &[i32], color: Color) -> Result<(), String> {
+    canvas.set_draw_color(color);
+    for (row, &half) in half_widths.iter().enumerate() {
+        let y = top + row as i32;
+        canvas.draw_line(Point::new(cx - half, y), Point::new(cx + half, y))?;
+    }
+    Ok(())
+}
+
+// Fills every pixel closer than thickness / 2 to the polyline, which gives round caps and joins.
+// Each segment is a capsule (a quad with two round caps) whose span is computed on the rows it
+// covers, then the spans of a row are merged so overlapping segments draw each pixel once.
+fn stroke<T: RenderTarget>(canvas: &mut Canvas<T>, points: &[(f32, f32)], thickness: f32, color: Color) -> Result<(), String> {
+    if points.is_empty() {
+        return Ok(());
+    }
+    let radius = (thickness / 2.0).max(0.5);
+    let top = (points.iter().map(|point| point.1).fold(f32::MAX, f32::min) - radius).ceil() as i32;
+    let bottom = (points.iter().map(|point| point.1).fold(f32::MIN, f32::max) + radius).floor() as i32;
+    if bottom < top {
+        return Ok(());
+    }
+
+    let segments: Vec<((f32, f32), (f32, f32))> = if points.len() == 1 {
+        vec![(points[0], points[0])]
+    } else {
+        points.windows(2).map(|segment| (segment[0], segment[1])).collect()
+    };
+    let mut rows: Vec<Vec<(i32, i32)>> = vec![Vec::new(); (bottom - top + 1) as usize];
+    for (a, b) in segments {
+        let first = (a.1.min(b.1) - radius).ceil() as i32;
+        let last = (a.1.max(b.1) + radius).floor() as i32;
+        for y in first.max(top)..=last.min(bottom) {
+            if let Some((start, end)) = capsule_span(a, b, radius, y as f32) {
+                let (start, end) = (start.ceil() as i32, end.floor() as i32);
+                if start <= end {
+                    rows[(y - top) as usize].push((start, end));
+                }
+            }
+        }
+    }
+
+    canvas.set_draw_color(color);
+    for (row, spans) in rows.iter_mut().enumerate() {
+        let y = top + row as i32;
+        spans.sort_unstable();
+        let mut current: Option<(i32, i32)> = None;
+        for &(start, end) in spans.iter() {
+            current = match current {
+                Some((current_start, current_end)) if start <= current_end + 1 => Some((current_start, current_end.max(end))),
+                Some((current_start, current_end)) => {
+                    canvas.draw_line(Point::new(current_start, y), Point::new(current_end, y))?;
+                    Some((start, end))
+                }
+                None => Some((start, end)),
+            };
+        }
+        if let Some((start, end)) = current {
+            canvas.draw_line(Point::new(start, y), Point::new(end, y))?;
+        }
+    }
+    Ok(())
+}
+
+// Horizontal extent of the capsule around the segment a-b on row y. The capsule is convex,
+// so the extents of the two caps and the quad on that row overlap into one span.
+fn capsule_span(a: (f32, f32), b: (f32, f32), radius: f32, y: f32) -> Option<(f32, f32)> {
+    let mut span: Option<(f32, f32)> = None;
+    let mut include = |start: f32, end: f32| {
+        span = Some(match span {
+            Some((span_start, span_end)) => (span_start.min(start), span_end.max(end)),
+            None => (start, end),
+        });
+    };
+    for center in [a, b] {
+        let dy = y - center.1;
+        if dy.abs() <= radius {
+            let half = (radius * radius - dy * dy).sqrt();
+            include(center.0 - half, center.0 + half);
+        }
+    }
+    let length = distance(a, b);
+    if length > 0.0 {
+        let normal = (-(b.1 - a.1) / length * radius, (b.0 - a.0) / length * radius);
+        let corners = [
+            (a.0 + normal.0, a.1 + normal.1),
+            (b.0 + normal.0, b.1 + normal.1),
+            (b.0 - normal.0, b.1 - normal.1),
+            (a.0 - normal.0, a.1 - normal.1),
+        ];
+        for (index, &p) in corners.iter().enumerate() {
+            let q = corners[(index + 1) % corners.len()];
+            if p.1 == q.1 {
+                if p.1 == y {
+                    include(p.0.min(q.0), p.0.max(q.0));
+                }
+            } else if (p.1 <= y && q.1 >= y) || (q.1 <= y && p.1 >= y) {
+                let x = p.0 + (y - p.1) / (q.1 - p.1) * (q.0 - p.0);
+                include(x, x);
+            }
+        }
+    }
+    span
+}
+
+fn curve_lines<T: RenderTarget>(canvas: &mut Canvas<T>, curve: &[(f32, f32)], thickness: f32, color: Color) -> Result<(), String> {
+    if thickness <= 1.0 {
+        let points: Vec<Point> = curve.iter().map(|point| Point::new(point.0.round() as i32, point.1.round() as i32)).collect();
+        canvas.set_draw_color(color);
+        return canvas.draw_lines(&points[..]);
+    }
+    stroke(canvas, curve, thickness, color)
+}
+
+fn angle_in_range(angle: f32, start: f32, end: f32) -> bool {
+    let sweep = (end - start).rem_euclid(360.0);
+    if sweep == 0.0 {
+        return end != start; // Full circle when the angles differ by a multiple of 360
+    }
+    (angle - start).rem_euclid(360.0) <= sweep
+}
+
+fn clamp_radius(rect: Rect, radius: i32) -> i32 {
+    radius.max(0).min(rect.width() as i32 / 2).min(rect.height() as i32 / 2)
+}
+
+fn to_f32(point: Point) -> (f32, f32) {
+    (point.x() as f32, point.y() as f32)
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
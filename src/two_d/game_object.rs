@@ -6,6 +6,8 @@ use crate::two_d::physics::RigidBody;
 use crate::two_d::ai_system::BehaviourTreeNode;
 use crate::two_d::event::GEvent;
 use crate::two_d::event::KeyEvent;
+use crate::two_d::camera::Camera;
+use crate::two_d::sprite_effects::SpriteDrawParams;
 
 use std::path::Path;
 use sdl2::rect::Rect;
@@ -39,6 +41,10 @@ impl<'a> GameObject<'a> {
         self.texture_manager_anim.load_animation(tag, path, frame_width, frame_height, frame_delay, row)
     }
 
+    pub fn load_atlas(&mut self, path: &Path, frame_delay: u32) -> Result<(), String> {
+        self.texture_manager_anim.load_atlas(path, frame_delay)
+    }
+
     pub fn render_texture(&mut self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, scale: u32, flip_horizontal: bool) -> Result<(), String> {
         if let Some(tag) = &self.texture_manager_anim.current_animation {
             if let Some(animated_texture) = self.texture_manager_anim.animations.get(tag) {
@@ -60,6 +66,44 @@ impl<'a> GameObject<'a> {
     }
     
 
+    pub fn render_with_camera(&mut self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, camera: &Camera, scale: u32, flip_horizontal: bool) -> Result<(), String> {
+        let tag = self.texture_manager_anim.current_animation.clone().ok_or("No animation set")?;
+        let animated_texture = self.texture_manager_anim.animations.get(&tag).ok_or("Texture not loaded for the current animation tag")?;
+        let sprite_sheet = &animated_texture.sprite_sheet;
+        let world_rect = crate::two_d::Rect::new(self.position.x, self.position.y, sprite_sheet.frame_width * scale, sprite_sheet.frame_height * scale);
+        if !camera.is_visible(&world_rect) {
+            return Ok(());
+        }
+        let dest = camera.transform_rect(&world_rect).ok_or("Camera transform failed")?;
+        let flip = if flip_horizontal {
+            sdl2_sys::SDL_RendererFlip::SDL_FLIP_HORIZONTAL as u32
+        } else {
+            sdl2_sys::SDL_RendererFlip::SDL_FLIP_NONE as u32
+        };
+        self.texture_manager_anim.render_texture(canvas, dest, flip)
+    }
+
+    // Draws the current animation at its frame size with rotation, scale, tint and effects
+    pub fn render_texture_ex(&mut self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, params: &SpriteDrawParams) -> Result<(), String> {
+        let animated_texture = self.texture_manager_anim.current().ok_or("No animation set")?;
+        let sprite_sheet = &animated_texture.sprite_sheet;
+        let dest = sdl2::rect::Rect::new(self.position.x, self.position.y, sprite_sheet.frame_width, sprite_sheet.frame_height);
+        self.texture_manager_anim.render_texture_ex(canvas, dest, params)
+    }
+
+    pub fn render_with_camera_ex(&mut self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, camera: &Camera, params: &SpriteDrawParams) -> Result<(), String> {
+        let animated_texture = self.texture_manager_anim.current().ok_or("No animation set")?;
+        let sprite_sheet = &animated_texture.sprite_sheet;
+        let world_rect = crate::two_d::Rect::new(self.position.x, self.position.y, sprite_sheet.frame_width, sprite_sheet.frame_height);
+        // Culled by the scaled bounds so growing sprites do not pop out at the screen edges
+        let scaled = params.transform_dest(*world_rect.sdl_rect());
+        if !camera.is_visible(&crate::two_d::Rect::new(scaled.x(), scaled.y(), scaled.width(), scaled.height())) {
+            return Ok(());
+        }
+        let dest = camera.transform_rect(&world_rect).ok_or("Camera transform failed")?;
+        self.texture_manager_anim.render_texture_ex(canvas, dest, params)
+    }
+
     pub fn update_position(&mut self, event: GEvent, colliders: &Vec<Rect>, delta_time: f32) {
         let mut new_position = self.position;
 
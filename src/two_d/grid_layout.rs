@@ -0,0 +1,311 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::camera::Camera;
+use crate::two_d::tile::Tile;
+
+use nalgebra::Vector2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GridOrientation {
+    Orthogonal,
+    Isometric,          // Diamond, map x goes down-right and map y down-left
+    StaggeredIsometric, // Rows of diamonds, every other row shifted by half a tile
+    HexPointy,          // Pointy top, rows overlap by a quarter of the tile height
+    HexFlat,            // Flat top, columns overlap by a quarter of the tile width
+}
+
+// Which rows (pointy hex, staggered isometric) or columns (flat hex) are shifted by half a tile
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StaggerIndex {
+    Odd,
+    Even,
+}
+
+// Axial hex coordinates. The map itself stores hexes in offset coordinates (column, row),
+// axial ones make neighbours and distances simple.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+const HEX_DIRECTIONS: [(i32, i32); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+#[allow(dead_code)]
+impl Hex {
+    pub fn new(q: i32, r: i32) -> Self {
+        Self { q, r }
+    }
+
+    pub fn s(&self) -> i32 {
+        -self.q - self.r
+    }
+
+    pub fn distance(&self, other: Hex) -> i32 {
+        ((self.q - other.q).abs() + (self.r - other.r).abs() + (self.s() - other.s()).abs()) / 2
+    }
+
+    pub fn neighbours(&self) -> [Hex; 6] {
+        HEX_DIRECTIONS.map(|(dq, dr)| Hex::new(self.q + dq, self.r + dr))
+    }
+
+    // Nearest hex to fractional axial coordinates
+    pub fn round(q: f32, r: f32) -> Self {
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        Self::new(rq as i32, rr as i32)
+    }
+
+    // Offset coordinates for pointy (rows shifted) or flat (columns shifted) maps
+    pub fn to_offset(&self, orientation: GridOrientation, stagger: StaggerIndex) -> (i32, i32) {
+        let shift = |n: i32| match stagger {
+            StaggerIndex::Odd => (n - (n & 1)) / 2,
+            StaggerIndex::Even => (n + (n & 1)) / 2,
+        };
+        match orientation {
+            GridOrientation::HexFlat => (self.q, self.r + shift(self.q)),
+            _ => (self.q + shift(self.r), self.r),
+        }
+    }
+
+    pub fn from_offset(x: i32, y: i32, orientation: GridOrientation, stagger: StaggerIndex) -> Self {
+        let shift = |n: i32| match stagger {
+            StaggerIndex::Odd => (n - (n & 1)) / 2,
+            StaggerIndex::Even => (n + (n & 1)) / 2,
+        };
+        match orientation {
+            GridOrientation::HexFlat => Hex::new(x, y - shift(x)),
+            _ => Hex::new(x - shift(y), y),
+        }
+    }
+}
+
+// How map cells are placed in the world. Tile sizes are the bounding box of one tile image:
+// a diamond for isometric maps, a hexagon for hex maps.
+#[derive(Clone, Copy, Debug)]
+pub struct GridLayout {
+    pub orientation: GridOrientation,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub stagger: StaggerIndex,
+    pub origin: (f32, f32), // World position of the map's top left
+}
+
+#[allow(dead_code)]
+impl GridLayout {
+    pub fn new(orientation: GridOrientation, tile_width: u32, tile_height: u32) -> Self {
+        Self {
+            orientation,
+            tile_width: tile_width.max(1),
+            tile_height: tile_height.max(1),
+            stagger: StaggerIndex::Odd,
+            origin: (0.0, 0.0),
+        }
+    }
+
+    pub fn set_stagger(&mut self, stagger: StaggerIndex) {
+        self.stagger = stagger;
+    }
+
+    pub fn set_origin(&mut self, x: f32, y: f32) {
+        self.origin = (x, y);
+    }
+
+    fn size(&self) -> (f32, f32) {
+        (self.tile_width as f32, self.tile_height as f32)
+    }
+
+    fn is_shifted(&self, n: i32) -> bool {
+        match self.stagger {
+            StaggerIndex::Odd => n & 1 == 1,
+            StaggerIndex::Even => n & 1 == 0,
+        }
+    }
+
+    // Diamond coordinates of a staggered isometric cell, neighbours and distances work on these
+    fn staggered_to_diamond(&self, x: i32, y: i32) -> (i32, i32) {
+        let difference = match self.stagger {
+            StaggerIndex::Odd => 2 * x + (y & 1),
+            StaggerIndex::Even => 2 * x - (y & 1),
+        };
+        let a = (y + difference).div_euclid(2);
+        (a, y - a)
+    }
+
+    fn diamond_to_staggered(&self, a: i32, b: i32) -> (i32, i32) {
+        let column = match self.stagger {
+            StaggerIndex::Odd => (a - b).div_euclid(2),
+            StaggerIndex::Even => (a - b + 1).div_euclid(2),
+        };
+        (column, a + b)
+    }
+
+    pub fn to_hex(&self, x: i32, y: i32) -> Hex {
+        Hex::from_offset(x, y, self.orientation, self.stagger)
+    }
+
+    pub fn from_hex(&self, hex: Hex) -> (i32, i32) {
+        hex.to_offset(self.orientation, self.stagger)
+    }
+
+    // World position of the center of a cell
+    pub fn tile_center(&self, x: i32, y: i32) -> (f32, f32) {
+        let (w, h) = self.size();
+        let (cx, cy) = match self.orientation {
+            GridOrientation::Orthogonal => (x as f32 * w + w / 2.0, y as f32 * h + h / 2.0),
+            GridOrientation::Isometric => ((x - y) as f32 * w / 2.0 + w / 2.0, (x + y) as f32 * h / 2.0 + h / 2.0),
+            GridOrientation::StaggeredIsometric => {
+                let shift = if self.is_shifted(y) { w / 2.0 } else { 0.0 };
+                (x as f32 * w + w / 2.0 + shift, y as f32 * h / 2.0 + h / 2.0)
+            }
+            GridOrientation::HexPointy => {
+                let shift = if self.is_shifted(y) { w / 2.0 } else { 0.0 };
+                (x as f32 * w + w / 2.0 + shift, y as f32 * h * 0.75 + h / 2.0)
+            }
+            GridOrientation::HexFlat => {
+                let shift = if self.is_shifted(x) { h / 2.0 } else { 0.0 };
+                (x as f32 * w * 0.75 + w / 2.0, y as f32 * h + h / 2.0 + shift)
+            }
+        };
+        (cx + self.origin.0, cy + self.origin.1)
+    }
+
+    // Where the tile image of a cell goes
+    pub fn tile_rect(&self, x: i32, y: i32) -> crate::two_d::Rect {
+        let (cx, cy) = self.tile_center(x, y);
+        crate::two_d::Rect::new(
+            (cx - self.tile_width as f32 / 2.0).round() as i32,
+            (cy - self.tile_height as f32 / 2.0).round() as i32,
+            self.tile_width,
+            self.tile_height,
+        )
+    }
+
+    // Cell under a world position, following the diamond and hexagon edges rather than the bounding boxes
+    pub fn world_to_tile(&self, world_x: f32, world_y: f32) -> (i32, i32) {
+        let (w, h) = self.size();
+        let (x, y) = (world_x - self.origin.0, world_y - self.origin.1);
+        let diamond = |x: f32, y: f32| {
+            let (u, v) = ((x - w / 2.0) / w, y / h);
+            ((v + u).floor() as i32, (v - u).floor() as i32)
+        };
+        match self.orientation {
+            GridOrientation::Orthogonal => ((x / w).floor() as i32, (y / h).floor() as i32),
+            GridOrientation::Isometric => diamond(x, y),
+            GridOrientation::StaggeredIsometric => {
+                // Staggered rows are a diamond map seen at a different angle
+                let (a, b) = match self.stagger {
+                    StaggerIndex::Odd => diamond(x, y),
+                    StaggerIndex::Even => diamond(x - w / 2.0, y),
+                };
+                self.diamond_to_staggered(a, b)
+            }
+            GridOrientation::HexPointy => {
+                let origin_x = if self.stagger == StaggerIndex::Even { w } else { w / 2.0 };
+                let r = (y - h / 2.0) / (h * 0.75);
+                let q = (x - origin_x) / w - r / 2.0;
+                self.from_hex(Hex::round(q, r))
+            }
+            GridOrientation::HexFlat => {
+                let origin_y = if self.stagger == StaggerIndex::Even { h } else { h / 2.0 };
+                let q = (x - w / 2.0) / (w * 0.75);
+                let r = (y - origin_y) / h - q / 2.0;
+                self.from_hex(Hex::round(q, r))
+            }
+        }
+    }
+
+    // Cell under the mouse, for picking with a camera
+    pub fn screen_to_tile(&self, camera: &Camera, screen_x: i32, screen_y: i32) -> (i32, i32) {
+        let world = camera.screen_to_world(Vector2::new(screen_x as f32, screen_y as f32));
+        self.world_to_tile(world.x, world.y)
+    }
+
+    // Cells sharing an edge: 4 on square and isometric maps, 6 on hex maps
+    pub fn neighbours(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
+        match self.orientation {
+            GridOrientation::Orthogonal | GridOrientation::Isometric => vec![(x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)],
+            GridOrientation::StaggeredIsometric => {
+                let (a, b) = self.staggered_to_diamond(x, y);
+                [(a, b - 1), (a + 1, b), (a, b + 1), (a - 1, b)].iter()
+                    .map(|&(a, b)| self.diamond_to_staggered(a, b))
+                    .collect()
+            }
+            GridOrientation::HexPointy | GridOrientation::HexFlat => {
+                self.to_hex(x, y).neighbours().iter().map(|&hex| self.from_hex(hex)).collect()
+            }
+        }
+    }
+
+    // Steps between two cells moving only to neighbours
+    pub fn distance(&self, from: (i32, i32), to: (i32, i32)) -> i32 {
+        match self.orientation {
+            GridOrientation::Orthogonal | GridOrientation::Isometric => (from.0 - to.0).abs() + (from.1 - to.1).abs(),
+            GridOrientation::StaggeredIsometric => {
+                let (a1, b1) = self.staggered_to_diamond(from.0, from.1);
+                let (a2, b2) = self.staggered_to_diamond(to.0, to.1);
+                (a1 - a2).abs() + (b1 - b2).abs()
+            }
+            GridOrientation::HexPointy | GridOrientation::HexFlat => self.to_hex(from.0, from.1).distance(self.to_hex(to.0, to.1)),
+        }
+    }
+
+    // Cells of a width x height map back to front, so overlapping tiles and objects standing on them
+    // are painted correctly. Sprites on a cell should be drawn right after it.
+    pub fn draw_order(&self, width: u32, height: u32) -> Vec<(i32, i32)> {
+        let mut cells: Vec<(i32, i32)> = (0..height as i32).flat_map(|y| (0..width as i32).map(move |x| (x, y))).collect();
+        cells.sort_by(|&a, &b| {
+            let (ax, ay) = self.tile_center(a.0, a.1);
+            let (bx, by) = self.tile_center(b.0, b.1);
+            ay.total_cmp(&by).then(ax.total_cmp(&bx))
+        });
+        cells
+    }
+
+    // Size of the area covered by a width x height map
+    pub fn map_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let (w, h) = (self.tile_width, self.tile_height);
+        match self.orientation {
+            GridOrientation::Orthogonal => (width * w, height * h),
+            GridOrientation::Isometric => ((width + height) * w / 2, (width + height) * h / 2),
+            GridOrientation::StaggeredIsometric => (width * w + w / 2, (height + 1) * h / 2),
+            GridOrientation::HexPointy => (width * w + w / 2, height * h * 3 / 4 + h / 4),
+            GridOrientation::HexFlat => (width * w * 3 / 4 + w / 4, height * h + h / 2),
+        }
+    }
+
+    // Draws a Tile map in this layout, textures[id] is drawn into the bounding box of each cell.
+    // On isometric maps the diamond starts at x = -(height - 1) * tile_width / 2, set_origin moves it.
+    pub fn render_tile_map(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, tile: &Tile, camera: Option<&Camera>) -> Result<(), String> {
+        let height = tile.tile_map.len() as u32;
+        let width = tile.tile_map.iter().map(|row| row.len()).max().unwrap_or(0) as u32;
+        for (x, y) in self.draw_order(width, height) {
+            let tile_id = match tile.tile_at(x as usize, y as usize) {
+                Some(tile_id) => tile.display_tile(tile_id),
+                None => continue,
+            };
+            let texture = match tile.textures.get(tile_id as usize).and_then(|manager| manager.texture.as_ref()) {
+                Some(texture) => texture,
+                None => continue,
+            };
+            let world_rect = self.tile_rect(x, y);
+            let dest = match camera {
+                Some(camera) if !camera.is_visible(&world_rect) => continue,
+                Some(camera) => match camera.transform_rect(&world_rect) {
+                    Some(dest) => dest,
+                    None => continue,
+                },
+                None => *world_rect.sdl_rect(),
+            };
+            canvas.copy(texture, None, dest)?;
+        }
+        Ok(())
+    }
+}
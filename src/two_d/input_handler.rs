@@ -6,6 +6,8 @@ use sdl2::keyboard::Keycode;
 use sdl2::EventPump;
 use sdl2::controller::GameController;
 
+use crate::two_d::virtual_resolution::{window_to_virtual, window_to_virtual_clamped, scaled_rect, ScalingMode, VirtualResolution};
+
 // Input handler
 pub struct InputHandler {
     event_pump: EventPump,
@@ -13,6 +15,8 @@ pub struct InputHandler {
     keys_pressed: HashSet<Keycode>,
     mouse_button_pressed: bool,
     mouse_position: (i32, i32),
+    mouse_in_view: bool, // False while the mouse is over the black bars around a virtual resolution
+    virtual_resolution: Option<((u32, u32), ScalingMode)>, // Mouse events are reported in this space when set
 }
 
 #[allow(dead_code)]
@@ -35,6 +39,8 @@ impl InputHandler {
             keys_pressed: HashSet::new(),
             mouse_button_pressed: false,
             mouse_position: (0, 0),
+            mouse_in_view: true,
+            virtual_resolution: None,
         })
     }
 
@@ -43,7 +49,12 @@ impl InputHandler {
         
         let mut events = Vec::new();
 
-        for event in self.event_pump.poll_iter() {
+        let polled: Vec<Event> = self.event_pump.poll_iter().collect();
+        for event in polled {
+            let event = match self.map_event(event) {
+                Some(event) => event,
+                None => continue,
+            };
             match event {
                 Event::KeyDown { keycode: Some(keycode), .. } => {
                     self.keys_pressed.insert(keycode);
@@ -85,7 +96,114 @@ impl InputHandler {
         self.mouse_position
     }
 
+    // Report mouse events in virtual resolution coordinates. The window and renderer output sizes
+    // are read when events arrive, so resizing and high-DPI windows need no extra calls.
+    pub fn set_virtual_resolution(&mut self, width: u32, height: u32, mode: ScalingMode) {
+        self.virtual_resolution = Some(((width, height), mode));
+    }
+
+    // Same as set_virtual_resolution with the size and mode of screen, call again after set_mode
+    pub fn follow_virtual_resolution(&mut self, screen: &VirtualResolution) {
+        self.set_virtual_resolution(screen.width, screen.height, screen.mode);
+    }
+
+    pub fn clear_virtual_resolution(&mut self) {
+        self.virtual_resolution = None;
+        self.mouse_in_view = true;
+    }
+
+    // Events as poll_events reports them, with the same virtual mouse coordinates
     pub fn get_event(&mut self) -> Option<Event> {
-        self.event_pump.poll_event()
+        loop {
+            let event = self.event_pump.poll_event()?;
+            if let Some(event) = self.map_event(event) {
+                return Some(event);
+            }
+        }
+    }
+
+    // None for events that are ignored with a virtual resolution set
+    fn map_event(&mut self, event: Event) -> Option<Event> {
+        match self.virtual_resolution {
+            Some((virtual_size, mode)) => self.map_mouse_event(event, virtual_size, mode),
+            None => Some(event),
+        }
     }
-}
\ No newline at end of file
+
+    // Moves mouse positions into virtual coordinates. Presses on the black bars are ignored,
+    // motion and releases there are moved to the nearest edge of the view so drags still end.
+    // The wheel has no position and is dropped while the mouse is outside the view.
+    fn map_mouse_event(&mut self, event: Event, virtual_size: (u32, u32), mode: ScalingMode) -> Option<Event> {
+        match event {
+            Event::MouseMotion { timestamp, window_id, which, mousestate, x, y, xrel, yrel } => {
+                let ((x, y), in_view) = map_window_position(window_id, (x, y), virtual_size, mode).unwrap_or(((x, y), true));
+                self.mouse_in_view = in_view;
+                let (xrel, yrel) = scale_relative(window_id, (xrel, yrel), virtual_size, mode);
+                Some(Event::MouseMotion { timestamp, window_id, which, mousestate, x, y, xrel, yrel })
+            }
+            Event::MouseButtonDown { timestamp, window_id, which, mouse_btn, clicks, x, y } => {
+                let ((x, y), in_view) = map_window_position(window_id, (x, y), virtual_size, mode).unwrap_or(((x, y), true));
+                self.mouse_in_view = in_view;
+                if !in_view {
+                    return None;
+                }
+                Some(Event::MouseButtonDown { timestamp, window_id, which, mouse_btn, clicks, x, y })
+            }
+            Event::MouseButtonUp { timestamp, window_id, which, mouse_btn, clicks, x, y } => {
+                let ((x, y), in_view) = map_window_position(window_id, (x, y), virtual_size, mode).unwrap_or(((x, y), true));
+                self.mouse_in_view = in_view;
+                Some(Event::MouseButtonUp { timestamp, window_id, which, mouse_btn, clicks, x, y })
+            }
+            Event::MouseWheel { .. } if !self.mouse_in_view => None,
+            event => Some(event),
+        }
+    }
+}
+
+// Window size in points and renderer output size in pixels. Mouse events use points, rendering
+// uses pixels, they differ on high-DPI displays.
+fn window_and_output_size(window_id: u32) -> Option<((u32, u32), (u32, u32))> {
+    unsafe {
+        let window = sdl2_sys::SDL_GetWindowFromID(window_id);
+        if window.is_null() {
+            return None;
+        }
+        let renderer = sdl2_sys::SDL_GetRenderer(window);
+        if renderer.is_null() {
+            return None;
+        }
+        let (mut window_width, mut window_height) = (0, 0);
+        sdl2_sys::SDL_GetWindowSize(window, &mut window_width, &mut window_height);
+        let (mut output_width, mut output_height) = (0, 0);
+        if sdl2_sys::SDL_GetRendererOutputSize(renderer, &mut output_width, &mut output_height) != 0 {
+            return None;
+        }
+        Some((
+            (window_width.max(1) as u32, window_height.max(1) as u32),
+            (output_width.max(1) as u32, output_height.max(1) as u32),
+        ))
+    }
+}
+
+// Virtual position clamped to the view and whether it was inside the view, None without a renderer
+fn map_window_position(window_id: u32, position: (i32, i32), virtual_size: (u32, u32), mode: ScalingMode) -> Option<((i32, i32), bool)> {
+    let (window_size, output_size) = window_and_output_size(window_id)?;
+    let pixel = (
+        (position.0 as i64 * output_size.0 as i64 / window_size.0 as i64) as i32,
+        (position.1 as i64 * output_size.1 as i64 / window_size.1 as i64) as i32,
+    );
+    let in_view = window_to_virtual(pixel, virtual_size, output_size, mode).is_some();
+    Some((window_to_virtual_clamped(pixel, virtual_size, output_size, mode), in_view))
+}
+
+fn scale_relative(window_id: u32, delta: (i32, i32), virtual_size: (u32, u32), mode: ScalingMode) -> (i32, i32) {
+    match window_and_output_size(window_id) {
+        Some((window_size, output_size)) => {
+            let rect = scaled_rect(virtual_size, output_size, mode);
+            let scale_x = output_size.0 as f32 / window_size.0 as f32 * virtual_size.0 as f32 / rect.width() as f32;
+            let scale_y = output_size.1 as f32 / window_size.1 as f32 * virtual_size.1 as f32 / rect.height() as f32;
+            ((delta.0 as f32 * scale_x).round() as i32, (delta.1 as f32 * scale_y).round() as i32)
+        }
+        None => delta,
+    }
+}
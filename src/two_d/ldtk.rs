@@ -0,0 +1,551 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::camera::Camera;
+use crate::two_d::collider_merge::merge_cells;
+use crate::two_d::text::parse_hex_color;
+
+use serde_json::Value;
+
+use sdl2::image::LoadTexture;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::video::WindowContext;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// Value of an entity or level custom field
+#[derive(Clone, Debug, PartialEq)]
+pub enum LdtkField {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String), // Also enums, multilines and file paths
+    Color(Color),
+    Point(i32, i32),   // Grid cell
+    EntityRef(String), // iid of the referenced entity
+    Array(Vec<LdtkField>),
+}
+
+#[allow(dead_code)]
+impl LdtkField {
+    fn parse(kind: &str, value: &Value) -> Self {
+        if value.is_null() {
+            return LdtkField::Null;
+        }
+        if let Some(inner) = kind.strip_prefix("Array<").and_then(|k| k.strip_suffix('>')) {
+            let items = value.as_array().map(|a| a.as_slice()).unwrap_or(&[]);
+            return LdtkField::Array(items.iter().map(|item| LdtkField::parse(inner, item)).collect());
+        }
+        match kind {
+            "Bool" => LdtkField::Bool(value.as_bool().unwrap_or(false)),
+            "Int" => LdtkField::Int(value.as_i64().unwrap_or(0)),
+            "Float" => LdtkField::Float(value.as_f64().unwrap_or(0.0)),
+            "Color" => value.as_str().and_then(parse_hex_color).map(LdtkField::Color).unwrap_or(LdtkField::Null),
+            "Point" => LdtkField::Point(value["cx"].as_i64().unwrap_or(0) as i32, value["cy"].as_i64().unwrap_or(0) as i32),
+            "EntityRef" => LdtkField::EntityRef(value["entityIid"].as_str().unwrap_or("").to_string()),
+            _ => match value {
+                Value::String(text) => LdtkField::String(text.clone()),
+                other => LdtkField::String(other.to_string()),
+            },
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            LdtkField::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            LdtkField::Int(value) => Some(*value),
+            LdtkField::Float(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            LdtkField::Int(value) => Some(*value as f64),
+            LdtkField::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            LdtkField::String(value) | LdtkField::EntityRef(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> &[LdtkField] {
+        match self {
+            LdtkField::Array(items) => items,
+            _ => &[],
+        }
+    }
+}
+
+pub type LdtkFields = HashMap<String, LdtkField>;
+
+fn parse_fields(value: &Value) -> LdtkFields {
+    value.as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter()
+        .map(|field| {
+            let kind = field["__type"].as_str().unwrap_or("");
+            (field["__identifier"].as_str().unwrap_or("").to_string(), LdtkField::parse(kind, &field["__value"]))
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct LdtkTileset {
+    pub uid: i64,
+    pub identifier: String,
+    pub image: Option<PathBuf>, // Resolved against the project file
+    pub width: u32,
+    pub height: u32,
+    pub grid_size: u32,
+    pub spacing: u32,
+    pub padding: u32,
+    pub custom_data: HashMap<u32, String>, // Per tile id, as typed in the tileset editor
+    pub enum_tags: HashMap<u32, Vec<String>>,
+}
+
+// A placed tile, positions are in pixels inside the layer
+#[derive(Clone, Copy, Debug)]
+pub struct LdtkTile {
+    pub x: i32,
+    pub y: i32,
+    pub src_x: i32,
+    pub src_y: i32,
+    pub id: u32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub alpha: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct LdtkEntity {
+    pub identifier: String,
+    pub iid: String,
+    pub grid: (i32, i32),
+    pub position: (i32, i32), // Pixels inside the level, where the pivot is
+    pub pivot: (f32, f32),
+    pub width: u32,
+    pub height: u32,
+    pub tags: Vec<String>,
+    pub fields: LdtkFields,
+}
+
+#[allow(dead_code)]
+impl LdtkEntity {
+    pub fn field(&self, name: &str) -> Option<&LdtkField> {
+        self.fields.get(name)
+    }
+
+    // Rect in level pixels with the pivot taken into account
+    pub fn bounds(&self) -> Rect {
+        Rect::new(
+            self.position.0 - (self.width as f32 * self.pivot.0).round() as i32,
+            self.position.1 - (self.height as f32 * self.pivot.1).round() as i32,
+            self.width.max(1),
+            self.height.max(1),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LdtkLayerKind {
+    IntGrid, // Can also hold auto-layer tiles generated from its values
+    Entities,
+    Tiles,
+    AutoLayer,
+}
+
+#[derive(Clone, Debug)]
+pub struct LdtkLayer {
+    pub identifier: String,
+    pub kind: LdtkLayerKind,
+    pub grid_size: u32,
+    pub width: u32, // In cells
+    pub height: u32,
+    pub offset: (i32, i32),
+    pub opacity: f32,
+    pub visible: bool,
+    pub tileset_uid: Option<i64>,
+    pub int_grid: Vec<i32>, // Row by row, 0 is empty
+    pub tiles: Vec<LdtkTile>,
+    pub entities: Vec<LdtkEntity>,
+}
+
+#[allow(dead_code)]
+impl LdtkLayer {
+    fn parse(value: &Value) -> Self {
+        let kind = match value["__type"].as_str().unwrap_or("") {
+            "IntGrid" => LdtkLayerKind::IntGrid,
+            "Entities" => LdtkLayerKind::Entities,
+            "AutoLayer" => LdtkLayerKind::AutoLayer,
+            _ => LdtkLayerKind::Tiles,
+        };
+        let pair = |v: &Value| (v[0].as_i64().unwrap_or(0) as i32, v[1].as_i64().unwrap_or(0) as i32);
+        let mut tiles = Vec::new();
+        for key in ["gridTiles", "autoLayerTiles"] {
+            for tile in value[key].as_array().map(|a| a.as_slice()).unwrap_or(&[]) {
+                let (x, y) = pair(&tile["px"]);
+                let (src_x, src_y) = pair(&tile["src"]);
+                let flip = tile["f"].as_u64().unwrap_or(0);
+                tiles.push(LdtkTile {
+                    x,
+                    y,
+                    src_x,
+                    src_y,
+                    id: tile["t"].as_u64().unwrap_or(0) as u32,
+                    flip_x: flip & 1 != 0,
+                    flip_y: flip & 2 != 0,
+                    alpha: tile["a"].as_f64().unwrap_or(1.0) as f32,
+                });
+            }
+        }
+        let entities = value["entityInstances"].as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter()
+            .map(|entity| LdtkEntity {
+                identifier: entity["__identifier"].as_str().unwrap_or("").to_string(),
+                iid: entity["iid"].as_str().unwrap_or("").to_string(),
+                grid: pair(&entity["__grid"]),
+                position: pair(&entity["px"]),
+                pivot: (entity["__pivot"][0].as_f64().unwrap_or(0.0) as f32, entity["__pivot"][1].as_f64().unwrap_or(0.0) as f32),
+                width: entity["width"].as_u64().unwrap_or(0) as u32,
+                height: entity["height"].as_u64().unwrap_or(0) as u32,
+                tags: entity["__tags"].as_array().map(|tags| tags.iter().filter_map(|t| t.as_str().map(String::from)).collect()).unwrap_or_default(),
+                fields: parse_fields(&entity["fieldInstances"]),
+            })
+            .collect();
+        Self {
+            identifier: value["__identifier"].as_str().unwrap_or("").to_string(),
+            kind,
+            grid_size: value["__gridSize"].as_u64().unwrap_or(16) as u32,
+            width: value["__cWid"].as_u64().unwrap_or(0) as u32,
+            height: value["__cHei"].as_u64().unwrap_or(0) as u32,
+            offset: (value["__pxTotalOffsetX"].as_i64().unwrap_or(0) as i32, value["__pxTotalOffsetY"].as_i64().unwrap_or(0) as i32),
+            opacity: value["__opacity"].as_f64().unwrap_or(1.0) as f32,
+            visible: value["visible"].as_bool().unwrap_or(true),
+            tileset_uid: value["__tilesetDefUid"].as_i64(),
+            int_grid: value["intGridCsv"].as_array().map(|values| values.iter().map(|v| v.as_i64().unwrap_or(0) as i32).collect()).unwrap_or_default(),
+            tiles,
+            entities,
+        }
+    }
+
+    pub fn int_value(&self, x: u32, y: u32) -> i32 {
+        if x >= self.width || y >= self.height {
+            return 0;
+        }
+        self.int_grid.get((y * self.width + x) as usize).copied().unwrap_or(0)
+    }
+
+    // IntGrid values as rows, ready for Tile::tile_map
+    pub fn grid(&self) -> Vec<Vec<u32>> {
+        (0..self.height)
+            .map(|y| (0..self.width).map(|x| self.int_value(x, y).max(0) as u32).collect())
+            .collect()
+    }
+
+    // Cells whose value is in solid, merged into larger rects, in level pixels
+    pub fn colliders(&self, solid: &[i32]) -> Vec<Rect> {
+        let grid: Vec<Vec<bool>> = (0..self.height)
+            .map(|y| (0..self.width).map(|x| solid.contains(&self.int_value(x, y))).collect())
+            .collect();
+        merge_cells(&grid, (self.grid_size, self.grid_size)).into_iter()
+            .map(|rect| Rect::new(rect.x() + self.offset.0, rect.y() + self.offset.1, rect.width(), rect.height()))
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LdtkDirection {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+    Above, // Other depth at the same place
+    Below,
+    Overlap,
+}
+
+impl LdtkDirection {
+    fn parse(dir: &str) -> Option<Self> {
+        Some(match dir {
+            "n" => LdtkDirection::North,
+            "s" => LdtkDirection::South,
+            "e" => LdtkDirection::East,
+            "w" => LdtkDirection::West,
+            "ne" => LdtkDirection::NorthEast,
+            "nw" => LdtkDirection::NorthWest,
+            "se" => LdtkDirection::SouthEast,
+            "sw" => LdtkDirection::SouthWest,
+            ">" => LdtkDirection::Above,
+            "<" => LdtkDirection::Below,
+            "o" => LdtkDirection::Overlap,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LdtkLevel {
+    pub identifier: String,
+    pub iid: String,
+    pub uid: i64,
+    pub world_x: i32,
+    pub world_y: i32,
+    pub world_depth: i32,
+    pub width: u32,
+    pub height: u32,
+    pub background: Option<Color>,
+    pub fields: LdtkFields,
+    pub layers: Vec<LdtkLayer>, // Top-most first, as in LDtk
+    pub neighbours: Vec<(String, LdtkDirection)>, // (level iid, side)
+}
+
+#[allow(dead_code)]
+impl LdtkLevel {
+    fn parse(value: &Value) -> Self {
+        Self {
+            identifier: value["identifier"].as_str().unwrap_or("").to_string(),
+            iid: value["iid"].as_str().unwrap_or("").to_string(),
+            uid: value["uid"].as_i64().unwrap_or(0),
+            world_x: value["worldX"].as_i64().unwrap_or(0) as i32,
+            world_y: value["worldY"].as_i64().unwrap_or(0) as i32,
+            world_depth: value["worldDepth"].as_i64().unwrap_or(0) as i32,
+            width: value["pxWid"].as_u64().unwrap_or(0) as u32,
+            height: value["pxHei"].as_u64().unwrap_or(0) as u32,
+            background: value["__bgColor"].as_str().or_else(|| value["bgColor"].as_str()).and_then(parse_hex_color),
+            fields: parse_fields(&value["fieldInstances"]),
+            layers: value["layerInstances"].as_array().map(|layers| layers.iter().map(LdtkLayer::parse).collect()).unwrap_or_default(),
+            neighbours: value["__neighbours"].as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter()
+                .filter_map(|n| Some((n["levelIid"].as_str()?.to_string(), LdtkDirection::parse(n["dir"].as_str()?)?)))
+                .collect(),
+        }
+    }
+
+    pub fn layer(&self, identifier: &str) -> Option<&LdtkLayer> {
+        self.layers.iter().find(|layer| layer.identifier == identifier)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&LdtkField> {
+        self.fields.get(name)
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = &LdtkEntity> {
+        self.layers.iter().flat_map(|layer| layer.entities.iter())
+    }
+
+    // e.g. level.entities_named("Enemy") to spawn every enemy of a room
+    pub fn entities_named<'b>(&'b self, identifier: &'b str) -> impl Iterator<Item = &'b LdtkEntity> + 'b {
+        self.entities().filter(move |entity| entity.identifier == identifier)
+    }
+
+    pub fn world_rect(&self) -> Rect {
+        Rect::new(self.world_x, self.world_y, self.width.max(1), self.height.max(1))
+    }
+
+    // Colliders of an IntGrid layer in world pixels, so rooms can be loaded side by side
+    pub fn world_colliders(&self, layer: &str, solid: &[i32]) -> Vec<Rect> {
+        self.layer(layer).map(|layer| layer.colliders(solid)).unwrap_or_default().into_iter()
+            .map(|rect| Rect::new(rect.x() + self.world_x, rect.y() + self.world_y, rect.width(), rect.height()))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LdtkProject {
+    pub world_layout: String, // Free, GridVania, LinearHorizontal or LinearVertical
+    pub tilesets: Vec<LdtkTileset>,
+    pub int_grid_values: HashMap<String, Vec<(i32, String)>>, // Per layer identifier: (value, name)
+    pub levels: Vec<LdtkLevel>,                               // All worlds, in project order
+}
+
+#[allow(dead_code)]
+impl LdtkProject {
+    // Levels saved in separate .ldtkl files are loaded too
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::parse(&text, path.parent().unwrap_or_else(|| Path::new(""))).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(text: &str, directory: &Path) -> Result<Self, String> {
+        let root: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let defs = &root["defs"];
+        let tilesets = defs["tilesets"].as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter()
+            .map(|tileset| LdtkTileset {
+                uid: tileset["uid"].as_i64().unwrap_or(0),
+                identifier: tileset["identifier"].as_str().unwrap_or("").to_string(),
+                image: tileset["relPath"].as_str().map(|rel| directory.join(rel)),
+                width: tileset["pxWid"].as_u64().unwrap_or(0) as u32,
+                height: tileset["pxHei"].as_u64().unwrap_or(0) as u32,
+                grid_size: tileset["tileGridSize"].as_u64().unwrap_or(16) as u32,
+                spacing: tileset["spacing"].as_u64().unwrap_or(0) as u32,
+                padding: tileset["padding"].as_u64().unwrap_or(0) as u32,
+                custom_data: tileset["customData"].as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter()
+                    .map(|d| (d["tileId"].as_u64().unwrap_or(0) as u32, d["data"].as_str().unwrap_or("").to_string()))
+                    .collect(),
+                enum_tags: tileset["enumTags"].as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter()
+                    .flat_map(|tag| {
+                        let name = tag["enumValueId"].as_str().unwrap_or("").to_string();
+                        tag["tileIds"].as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter()
+                            .map(move |id| (id.as_u64().unwrap_or(0) as u32, name.clone()))
+                            .collect::<Vec<_>>()
+                    })
+                    .fold(HashMap::new(), |mut tags: HashMap<u32, Vec<String>>, (id, name)| {
+                        tags.entry(id).or_default().push(name);
+                        tags
+                    }),
+            })
+            .collect();
+        let int_grid_values = defs["layers"].as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter()
+            .filter(|layer| layer["type"] == "IntGrid")
+            .map(|layer| {
+                let values = layer["intGridValues"].as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter()
+                    .map(|v| (v["value"].as_i64().unwrap_or(0) as i32, v["identifier"].as_str().unwrap_or("").to_string()))
+                    .collect();
+                (layer["identifier"].as_str().unwrap_or("").to_string(), values)
+            })
+            .collect();
+
+        // Multi-world projects keep their levels inside worlds
+        let mut level_values: Vec<&Value> = root["levels"].as_array().map(|a| a.iter().collect()).unwrap_or_default();
+        let mut world_layout = root["worldLayout"].as_str().unwrap_or("Free").to_string();
+        for world in root["worlds"].as_array().map(|a| a.as_slice()).unwrap_or(&[]) {
+            if let Some(layout) = world["worldLayout"].as_str() {
+                world_layout = layout.to_string();
+            }
+            level_values.extend(world["levels"].as_array().map(|a| a.as_slice()).unwrap_or(&[]));
+        }
+        let mut levels = Vec::with_capacity(level_values.len());
+        for value in level_values {
+            match value["externalRelPath"].as_str() {
+                Some(rel) if value["layerInstances"].is_null() => {
+                    let path = directory.join(rel);
+                    let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                    let external: Value = serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+                    levels.push(LdtkLevel::parse(&external));
+                }
+                _ => levels.push(LdtkLevel::parse(value)),
+            }
+        }
+        Ok(Self { world_layout, tilesets, int_grid_values, levels })
+    }
+
+    pub fn level(&self, identifier: &str) -> Option<&LdtkLevel> {
+        self.levels.iter().find(|level| level.identifier == identifier)
+    }
+
+    pub fn level_by_iid(&self, iid: &str) -> Option<&LdtkLevel> {
+        self.levels.iter().find(|level| level.iid == iid)
+    }
+
+    pub fn tileset(&self, uid: i64) -> Option<&LdtkTileset> {
+        self.tilesets.iter().find(|tileset| tileset.uid == uid)
+    }
+
+    // Value of a named IntGrid entry, e.g. int_grid_value("Collisions", "wall")
+    pub fn int_grid_value(&self, layer: &str, name: &str) -> Option<i32> {
+        self.int_grid_values.get(layer)?.iter().find(|(_, n)| n == name).map(|(value, _)| *value)
+    }
+
+    pub fn neighbours<'b>(&'b self, level: &'b LdtkLevel) -> impl Iterator<Item = (&'b LdtkLevel, LdtkDirection)> + 'b {
+        level.neighbours.iter().filter_map(move |(iid, direction)| Some((self.level_by_iid(iid)?, *direction)))
+    }
+
+    // Level containing a world position, on the given depth
+    pub fn level_at(&self, x: i32, y: i32, depth: i32) -> Option<&LdtkLevel> {
+        self.levels.iter().find(|level| level.world_depth == depth && level.world_rect().contains_point((x, y)))
+    }
+
+    // Levels touching a world rect, e.g. the camera view grown by a margin to stream rooms in early
+    pub fn levels_in(&self, rect: Rect, depth: i32) -> impl Iterator<Item = &LdtkLevel> {
+        self.levels.iter().filter(move |level| level.world_depth == depth && level.world_rect().has_intersection(rect))
+    }
+}
+
+// Draws the tile and auto-layers of LDtk levels at their world position
+pub struct LdtkRenderer<'a> {
+    textures: HashMap<i64, Texture<'a>>, // Per tileset uid
+}
+
+#[allow(dead_code)]
+impl<'a> LdtkRenderer<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>, project: &LdtkProject) -> Result<Self, String> {
+        let mut textures = HashMap::new();
+        for tileset in &project.tilesets {
+            // Internal icon tilesets have no image
+            if let Some(image) = &tileset.image {
+                textures.insert(tileset.uid, texture_creator.load_texture(image)?);
+            }
+        }
+        Ok(Self { textures })
+    }
+
+    pub fn render_level(&mut self, canvas: &mut Canvas<sdl2::video::Window>, project: &LdtkProject, level: &LdtkLevel, camera: Option<&Camera>) -> Result<(), String> {
+        if let Some(color) = level.background {
+            let world_rect = crate::two_d::Rect::new(level.world_x, level.world_y, level.width.max(1), level.height.max(1));
+            let dest = match camera {
+                Some(camera) => camera.transform_rect(&world_rect),
+                None => Some(*world_rect.sdl_rect()),
+            };
+            if let Some(dest) = dest {
+                canvas.set_draw_color(color);
+                canvas.fill_rect(dest)?;
+            }
+        }
+        // LDtk lists the top-most layer first
+        for layer in level.layers.iter().rev().filter(|layer| layer.visible && !layer.tiles.is_empty()) {
+            let tileset = match layer.tileset_uid.and_then(|uid| project.tileset(uid)) {
+                Some(tileset) => tileset,
+                None => continue,
+            };
+            let texture = match self.textures.get_mut(&tileset.uid) {
+                Some(texture) => texture,
+                None => continue,
+            };
+            let size = tileset.grid_size;
+            for tile in &layer.tiles {
+                let world_rect = crate::two_d::Rect::new(
+                    level.world_x + layer.offset.0 + tile.x,
+                    level.world_y + layer.offset.1 + tile.y,
+                    size,
+                    size,
+                );
+                let dest = match camera {
+                    Some(camera) if !camera.is_visible(&world_rect) => continue,
+                    Some(camera) => match camera.transform_rect(&world_rect) {
+                        Some(dest) => dest,
+                        None => continue,
+                    },
+                    None => *world_rect.sdl_rect(),
+                };
+                texture.set_alpha_mod(((layer.opacity * tile.alpha).clamp(0.0, 1.0) * 255.0) as u8);
+                canvas.copy_ex(texture, Some(Rect::new(tile.src_x, tile.src_y, size, size)), Some(dest), 0.0, None, tile.flip_x, tile.flip_y)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Every level overlapping the camera view, for worlds where rooms are visible next to each other
+    pub fn render_visible(&mut self, canvas: &mut Canvas<sdl2::video::Window>, project: &LdtkProject, camera: &Camera, depth: i32) -> Result<(), String> {
+        let view = camera.visible_world_rect();
+        for level in project.levels_in(*view.sdl_rect(), depth) {
+            self.render_level(canvas, project, level, Some(camera))?;
+        }
+        Ok(())
+    }
+}
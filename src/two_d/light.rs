@@ -9,6 +9,7 @@ pub struct PointLight {
     pub radius: f32,
     pub intensity: f32,
     pub color: two_d::Color,
+    pub enabled: bool, // Disabled lights are skipped by LightMap and LightingSystem
 }
 
 #[allow(dead_code)]
@@ -19,6 +20,7 @@ impl PointLight {
             radius,
             intensity,
             color,
+            enabled: true,
         }
     }
 
@@ -48,6 +50,8 @@ pub struct SpotLight {
     pub distance: f32,      // Maximum distance the light can reach.
     pub intensity: f32,
     pub color: two_d::Color,
+    pub edge_softness: f32, // Degrees over which the light fades out at the edge of the cone.
+    pub enabled: bool,
 }
 
 #[allow(dead_code)]
@@ -60,6 +64,32 @@ impl SpotLight {
             distance,
             intensity,
             color,
+            edge_softness: 10.0,
+            enabled: true,
+        }
+    }
+
+    pub fn set_edge_softness(&mut self, edge_softness: f32) {
+        self.edge_softness = edge_softness.max(0.0);
+    }
+
+    // How much light reaches a point at offset from the light: 1.0 inside the cone,
+    // fading to 0.0 over edge_softness degrees at the cutoff angle
+    pub fn cone_attenuation(&self, offset: nalgebra::Vector2<f32>) -> f32 {
+        let length = offset.norm() * self.direction.norm();
+        if length <= f32::EPSILON {
+            return 1.0;
+        }
+        let angle = (offset.dot(&self.direction) / length).clamp(-1.0, 1.0).acos().to_degrees();
+        let half_angle = self.cutoff_angle / 2.0;
+        let inner = half_angle - self.edge_softness;
+        if angle <= inner {
+            1.0
+        } else if angle >= half_angle {
+            0.0
+        } else {
+            let t = (half_angle - angle) / (half_angle - inner);
+            t * t * (3.0 - 2.0 * t)
         }
     }
 
@@ -87,16 +117,22 @@ impl SpotLight {
 
 pub struct AmbientFilter {
     pub intensity: f32,
+    pub color: sdl2::pixels::Color, // Tint applied to the light texture, white keeps its own colors
 }
 #[allow(dead_code)]
 impl AmbientFilter {
     pub fn new(intensity: f32) -> Self {
-        AmbientFilter { intensity }
+        AmbientFilter { intensity, color: sdl2::pixels::Color::RGB(255, 255, 255) }
+    }
+
+    pub fn set_color(&mut self, color: sdl2::pixels::Color) {
+        self.color = color;
     }
 
     pub fn render(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, light_texture: &mut sdl2::render::Texture) {
         let alpha_value = (self.intensity * 255.0).clamp(0.0, 255.0) as u8;
         light_texture.set_alpha_mod(alpha_value);
+        light_texture.set_color_mod(self.color.r, self.color.g, self.color.b);
         
         // Cover the entire scene with the ambient light
         canvas.copy(light_texture, None, None).unwrap();
@@ -0,0 +1,156 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::camera::Camera;
+use crate::two_d::light::{PointLight, SpotLight};
+use crate::two_d::shadow::{visibility_polygon, Occluders};
+
+use nalgebra::Vector2;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::{BlendMode, Canvas, Texture};
+
+// Screen sized buffer of light. It starts at the ambient color, lights are added on the CPU
+// and the result multiplies the scene (Mod blending): white keeps the scene, black hides it.
+pub struct LightMap<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub ambient: sdl2::pixels::Color,
+    light: Vec<[f32; 3]>,
+    pixels: Vec<u8>,
+    texture: Texture<'a>,
+}
+
+#[allow(dead_code)]
+impl<'a> LightMap<'a> {
+    pub fn new(
+        texture_creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, String> {
+        let mut texture = texture_creator.create_texture_streaming(PixelFormatEnum::RGBA32, width, height).map_err(|e| e.to_string())?;
+        texture.set_blend_mode(BlendMode::Mod);
+        let ambient = sdl2::pixels::Color::RGB(0, 0, 0);
+        Ok(Self {
+            width,
+            height,
+            ambient,
+            light: vec![[0.0; 3]; (width * height) as usize],
+            pixels: vec![255; (width * height * 4) as usize],
+            texture,
+        })
+    }
+
+    pub fn set_ambient(&mut self, ambient: sdl2::pixels::Color) {
+        self.ambient = ambient;
+    }
+
+    // Resets every pixel to the ambient color, call once per frame before adding lights
+    pub fn clear(&mut self) {
+        let ambient = [self.ambient.r as f32 / 255.0, self.ambient.g as f32 / 255.0, self.ambient.b as f32 / 255.0];
+        for pixel in self.light.iter_mut() {
+            *pixel = ambient;
+        }
+    }
+
+    // Point light blocked by the occluders. Light and occluders are in world space,
+    // the camera maps them to the screen (None means world and screen are the same).
+    pub fn add_point_light(&mut self, light: &PointLight, occluders: &Occluders, camera: Option<&Camera>) {
+        if !light.enabled {
+            return;
+        }
+        let polygon = visibility_polygon(light.position, light.radius, occluders);
+        let color = [light.color.r() as f32 / 255.0, light.color.g() as f32 / 255.0, light.color.b() as f32 / 255.0];
+        let (origin, polygon, radius) = match camera {
+            Some(camera) => (
+                camera.world_to_screen(light.position),
+                polygon.iter().map(|&point| camera.world_to_screen(point)).collect(),
+                light.radius * camera.zoom,
+            ),
+            None => (light.position, polygon, light.radius),
+        };
+        self.add_light_polygon(&polygon, origin, radius, color, light.intensity, |_| 1.0);
+    }
+
+    // Spot light with its cone cutoff, blocked by the occluders like add_point_light
+    pub fn add_spot_light(&mut self, light: &SpotLight, occluders: &Occluders, camera: Option<&Camera>) {
+        if !light.enabled {
+            return;
+        }
+        let polygon = visibility_polygon(light.position, light.distance, occluders);
+        let color = [light.color.r() as f32 / 255.0, light.color.g() as f32 / 255.0, light.color.b() as f32 / 255.0];
+        let (origin, polygon, radius, rotation) = match camera {
+            Some(camera) => (
+                camera.world_to_screen(light.position),
+                polygon.iter().map(|&point| camera.world_to_screen(point)).collect(),
+                light.distance * camera.zoom,
+                camera.render_angle().to_radians() as f32,
+            ),
+            None => (light.position, polygon, light.distance, 0.0),
+        };
+        // Offsets are in screen space, turn them back so they compare with the world space direction
+        let (sin, cos) = (-rotation).sin_cos();
+        self.add_light_polygon(&polygon, origin, radius, color, light.intensity, |offset| {
+            light.cone_attenuation(Vector2::new(offset.x * cos - offset.y * sin, offset.x * sin + offset.y * cos))
+        });
+    }
+
+    // Adds light inside a screen space polygon with a quadratic falloff from origin to radius.
+    // attenuation gets the offset from the origin and scales the light, e.g. for spot light cones.
+    pub fn add_light_polygon<F>(&mut self, polygon: &[Vector2<f32>], origin: Vector2<f32>, radius: f32, color: [f32; 3], intensity: f32, attenuation: F)
+    where
+        F: Fn(Vector2<f32>) -> f32,
+    {
+        if polygon.len() < 3 || radius <= 0.0 || intensity <= 0.0 {
+            return;
+        }
+        let min_y = polygon.iter().map(|point| point.y).fold(f32::MAX, f32::min).max(0.0) as i32;
+        let max_y = polygon.iter().map(|point| point.y).fold(f32::MIN, f32::max).min(self.height as f32 - 1.0) as i32;
+
+        // Scanline fill, the polygon edges crossing the center of each row give the lit spans
+        let mut crossings = Vec::new();
+        for y in min_y..=max_y {
+            let center_y = y as f32 + 0.5;
+            crossings.clear();
+            for i in 0..polygon.len() {
+                let a = polygon[i];
+                let b = polygon[(i + 1) % polygon.len()];
+                if (a.y <= center_y && b.y > center_y) || (b.y <= center_y && a.y > center_y) {
+                    crossings.push(a.x + (center_y - a.y) / (b.y - a.y) * (b.x - a.x));
+                }
+            }
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+            for span in crossings.chunks(2) {
+                if span.len() < 2 {
+                    continue;
+                }
+                let start = (span[0] - 0.5).ceil().max(0.0) as i32;
+                let end = (span[1] - 0.5).floor().min(self.width as f32 - 1.0) as i32;
+                for x in start..=end {
+                    let offset = Vector2::new(x as f32 + 0.5, center_y) - origin;
+                    let falloff = (1.0 - offset.norm() / radius).max(0.0);
+                    let strength = falloff * falloff * intensity * attenuation(offset);
+                    if strength <= 0.0 {
+                        continue;
+                    }
+                    let pixel = &mut self.light[(y as u32 * self.width + x as u32) as usize];
+                    pixel[0] += color[0] * strength;
+                    pixel[1] += color[1] * strength;
+                    pixel[2] += color[2] * strength;
+                }
+            }
+        }
+    }
+
+    // Multiplies everything drawn so far with the light map
+    pub fn render(&mut self, canvas: &mut Canvas<sdl2::video::Window>) -> Result<(), String> {
+        for (pixel, light) in self.pixels.chunks_exact_mut(4).zip(self.light.iter()) {
+            pixel[0] = (light[0].min(1.0) * 255.0) as u8;
+            pixel[1] = (light[1].min(1.0) * 255.0) as u8;
+            pixel[2] = (light[2].min(1.0) * 255.0) as u8;
+            pixel[3] = 255;
+        }
+        self.texture.update(None, &self.pixels, (self.width * 4) as usize).map_err(|e| e.to_string())?;
+        canvas.copy(&self.texture, None, sdl2::rect::Rect::new(0, 0, self.width, self.height))
+    }
+}
@@ -0,0 +1,91 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::camera::Camera;
+use crate::two_d::light::{PointLight, SpotLight};
+use crate::two_d::light_map::LightMap;
+use crate::two_d::shadow::Occluders;
+
+use sdl2::render::Canvas;
+
+// Owns the lights of a scene and draws all of them with one light map,
+// so there is no darkness texture or light PNG to manage per light
+pub struct LightingSystem<'a> {
+    pub point_lights: Vec<PointLight>,
+    pub spot_lights: Vec<SpotLight>,
+    pub occluders: Occluders, // Empty means no shadows
+    pub enabled: bool,
+    light_map: LightMap<'a>,
+}
+
+#[allow(dead_code)]
+impl<'a> LightingSystem<'a> {
+    pub fn new(
+        texture_creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            point_lights: Vec::new(),
+            spot_lights: Vec::new(),
+            occluders: Occluders::new(),
+            enabled: true,
+            light_map: LightMap::new(texture_creator, width, height)?,
+        })
+    }
+
+    // Color of unlit areas, black hides them completely
+    pub fn set_ambient(&mut self, ambient: sdl2::pixels::Color) {
+        self.light_map.set_ambient(ambient);
+    }
+
+    pub fn ambient(&self) -> sdl2::pixels::Color {
+        self.light_map.ambient
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn set_occluders(&mut self, occluders: Occluders) {
+        self.occluders = occluders;
+    }
+
+    pub fn add_point_light(&mut self, light: PointLight) -> usize {
+        self.point_lights.push(light);
+        self.point_lights.len() - 1
+    }
+
+    pub fn add_spot_light(&mut self, light: SpotLight) -> usize {
+        self.spot_lights.push(light);
+        self.spot_lights.len() - 1
+    }
+
+    pub fn point_light_mut(&mut self, index: usize) -> Option<&mut PointLight> {
+        self.point_lights.get_mut(index)
+    }
+
+    pub fn spot_light_mut(&mut self, index: usize) -> Option<&mut SpotLight> {
+        self.spot_lights.get_mut(index)
+    }
+
+    pub fn clear_lights(&mut self) {
+        self.point_lights.clear();
+        self.spot_lights.clear();
+    }
+
+    // Call after drawing the scene and before the UI. Lights are in world space when a camera is given.
+    pub fn render(&mut self, canvas: &mut Canvas<sdl2::video::Window>, camera: Option<&Camera>) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        self.light_map.clear();
+        for light in &self.point_lights {
+            self.light_map.add_point_light(light, &self.occluders, camera);
+        }
+        for light in &self.spot_lights {
+            self.light_map.add_spot_light(light, &self.occluders, camera);
+        }
+        self.light_map.render(canvas)
+    }
+}
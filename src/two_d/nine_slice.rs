@@ -0,0 +1,150 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use sdl2::image::LoadTexture;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget, Texture, TextureCreator};
+use sdl2::video::WindowContext;
+
+use std::path::Path;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SliceMode {
+    Stretch,
+    Tile, // Repeats the slice at its original size, the last one is cut off
+}
+
+// A frame image split into 3x3 parts by four insets. Corners keep their size,
+// edges and center grow with the destination so borders never get distorted.
+#[derive(Clone)]
+pub struct NineSlice<'a> {
+    pub texture: Rc<Texture<'a>>, // Shared, one frame image is usually used by many widgets
+    pub region: Rect,             // Part of the texture holding the frame, e.g. a sprite in an atlas
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub edge_mode: SliceMode,
+    pub center_mode: SliceMode,
+    pub draw_center: bool,
+}
+
+#[allow(dead_code)]
+impl<'a> NineSlice<'a> {
+    pub fn new(texture: Rc<Texture<'a>>, left: u32, top: u32, right: u32, bottom: u32) -> Self {
+        let query = texture.query();
+        Self {
+            texture,
+            region: Rect::new(0, 0, query.width, query.height),
+            left,
+            top,
+            right,
+            bottom,
+            edge_mode: SliceMode::Stretch,
+            center_mode: SliceMode::Stretch,
+            draw_center: true,
+        }
+    }
+
+    pub fn load(texture_creator: &'a TextureCreator<WindowContext>, path: &Path, left: u32, top: u32, right: u32, bottom: u32) -> Result<Self, String> {
+        let texture = texture_creator.load_texture(path)?;
+        Ok(Self::new(Rc::new(texture), left, top, right, bottom))
+    }
+
+    pub fn set_region(&mut self, region: Rect) {
+        self.region = region;
+    }
+
+    pub fn set_edge_mode(&mut self, edge_mode: SliceMode) {
+        self.edge_mode = edge_mode;
+    }
+
+    pub fn set_center_mode(&mut self, center_mode: SliceMode) {
+        self.center_mode = center_mode;
+    }
+
+    // Frames with a transparent center only need the border drawn
+    pub fn set_draw_center(&mut self, draw_center: bool) {
+        self.draw_center = draw_center;
+    }
+
+    // Smallest size the frame can be drawn at without shrinking its corners
+    pub fn min_size(&self) -> (u32, u32) {
+        (self.left + self.right, self.top + self.bottom)
+    }
+
+    pub fn render<T: RenderTarget>(&self, canvas: &mut Canvas<T>, dest: Rect) -> Result<(), String> {
+        let region = self.region;
+        let left = self.left.min(region.width());
+        let right = self.right.min(region.width() - left);
+        let top = self.top.min(region.height());
+        let bottom = self.bottom.min(region.height() - top);
+
+        // When dest is smaller than the borders, the borders shrink proportionally
+        let (dest_left, dest_right) = fit_insets(left, right, dest.width());
+        let (dest_top, dest_bottom) = fit_insets(top, bottom, dest.height());
+
+        let src_columns = [(0, left), (left, region.width() - left - right), (region.width() - right, right)];
+        let src_rows = [(0, top), (top, region.height() - top - bottom), (region.height() - bottom, bottom)];
+        let dest_columns = [(0, dest_left), (dest_left, dest.width() - dest_left - dest_right), (dest.width() - dest_right, dest_right)];
+        let dest_rows = [(0, dest_top), (dest_top, dest.height() - dest_top - dest_bottom), (dest.height() - dest_bottom, dest_bottom)];
+
+        for row in 0..3 {
+            for column in 0..3 {
+                if row == 1 && column == 1 && !self.draw_center {
+                    continue;
+                }
+                let (src_x, src_width) = src_columns[column];
+                let (src_y, src_height) = src_rows[row];
+                let (dest_x, dest_width) = dest_columns[column];
+                let (dest_y, dest_height) = dest_rows[row];
+                if src_width == 0 || src_height == 0 || dest_width == 0 || dest_height == 0 {
+                    continue;
+                }
+                let src = Rect::new(region.x() + src_x as i32, region.y() + src_y as i32, src_width, src_height);
+                let target = Rect::new(dest.x() + dest_x as i32, dest.y() + dest_y as i32, dest_width, dest_height);
+                let mode = if row == 1 && column == 1 { self.center_mode } else { self.edge_mode };
+                // Corners are always stretched (to their own size, or smaller when the frame is tiny)
+                let tile_x = mode == SliceMode::Tile && column == 1;
+                let tile_y = mode == SliceMode::Tile && row == 1;
+                self.copy_tiled(canvas, src, target, tile_x, tile_y)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn copy_tiled<T: RenderTarget>(&self, canvas: &mut Canvas<T>, src: Rect, dest: Rect, tile_x: bool, tile_y: bool) -> Result<(), String> {
+        if !tile_x && !tile_y {
+            return canvas.copy(&self.texture, src, dest);
+        }
+        let step_x = if tile_x { src.width() } else { dest.width() };
+        let step_y = if tile_y { src.height() } else { dest.height() };
+        let mut y = 0;
+        while y < dest.height() {
+            let height = step_y.min(dest.height() - y);
+            let src_height = if tile_y { height } else { src.height() };
+            let mut x = 0;
+            while x < dest.width() {
+                let width = step_x.min(dest.width() - x);
+                let src_width = if tile_x { width } else { src.width() };
+                canvas.copy(
+                    &self.texture,
+                    Rect::new(src.x(), src.y(), src_width, src_height),
+                    Rect::new(dest.x() + x as i32, dest.y() + y as i32, width, height),
+                )?;
+                x += width;
+            }
+            y += height;
+        }
+        Ok(())
+    }
+}
+
+fn fit_insets(first: u32, second: u32, size: u32) -> (u32, u32) {
+    if first + second <= size {
+        return (first, second);
+    }
+    let first = (first as u64 * size as u64 / (first + second) as u64) as u32;
+    (first, size - first)
+}
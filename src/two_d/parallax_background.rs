@@ -21,9 +21,14 @@ impl<'a> ParallaxLayer<'a> {
     }
 
     pub fn render(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, camera: &Camera) -> Result<(), String> {
-        let mut dest = sdl2::rect::Rect::new(0, 0, self.texture_manager.texture.as_ref().unwrap().query().width, self.texture_manager.texture.as_ref().unwrap().query().height);
-        dest.x = (self.offset - camera.position.x as f32 * self.speed) as i32;
-        while dest.x < camera.position.x + camera.size.x as i32 {
+        let query = self.texture_manager.texture.as_ref().ok_or("Texture not loaded")?.query();
+        // Layers scroll with their own speed but are scaled with the camera zoom
+        let width = ((query.width as f32 * camera.zoom).round() as u32).max(1);
+        let height = ((query.height as f32 * camera.zoom).round() as u32).max(1);
+        let scroll = (self.offset - camera.position.x as f32 * self.speed) * camera.zoom;
+        let mut dest = sdl2::rect::Rect::new(0, 0, width, height);
+        dest.x = scroll.rem_euclid(width as f32) as i32 - width as i32;
+        while dest.x < camera.size.x as i32 {
             self.texture_manager.render_texture(canvas, dest)?;
             dest.x += dest.width() as i32;
         }
@@ -57,27 +57,15 @@ impl Particle {
     }
 
     pub fn render<T: sdl2::render::RenderTarget>(&self, canvas: &mut Canvas<T>) {
-        canvas.set_draw_color(sdl2::pixels::Color::RGBA(self.color.r(), self.color.g(), self.color.b(), self.alpha));
+        let color = sdl2::pixels::Color::RGBA(self.color.r(), self.color.g(), self.color.b(), self.alpha);
+        canvas.set_draw_color(color);
         match self.shape {
             ParticleShape::Rect => {
                 canvas.fill_rect(Rect::new(self.x as i32, self.y as i32, self.size, self.size)).unwrap();
             }
             ParticleShape::Circle => {
-                let center_x = self.x as f32;
-                let center_y = self.y as f32;
-                let radius = self.size as f32 / 2.0;
-                // Draw filled circle
-                for angle in 0..360 {
-                    let radian = angle as f32 * std::f32::consts::PI / 180.0;
-                    let x = radian.cos() * radius + center_x;
-                    let y = radian.sin() * radius + center_y;
-                    // Calculate the topmost point for the vertical line to fill the circle
-                    let top_y = center_y - (y - center_y).abs();
-                    for fill_y in top_y as i32..=y as i32 {
-                        canvas.draw_point(sdl2::rect::Point::new(x as i32, fill_y)).unwrap();
-                    }
-                }
-
+                let radius = self.size as i32 / 2;
+                two_d::draw::fill_circle(canvas, self.x as i32, self.y as i32, radius, color).unwrap();
             }
         }
     }    
@@ -0,0 +1,276 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Canvas;
+use sdl2::surface::Surface;
+
+use std::path::Path;
+
+// 3D color lookup table in the Adobe/Resolve .cube format
+#[derive(Clone, Debug)]
+pub struct Lut3D {
+    pub title: String,
+    pub size: usize,
+    pub domain_min: [f32; 3],
+    pub domain_max: [f32; 3],
+    pub data: Vec<[f32; 3]>, // size^3 entries, red changes fastest
+}
+
+#[allow(dead_code)]
+impl Lut3D {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_cube_str(&text)
+    }
+
+    pub fn from_cube_str(text: &str) -> Result<Self, String> {
+        let mut title = String::new();
+        let mut size = 0;
+        let mut domain_min = [0.0, 0.0, 0.0];
+        let mut domain_max = [1.0, 1.0, 1.0];
+        let mut data = Vec::new();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let keyword = parts.next().unwrap_or("");
+            match keyword {
+                "TITLE" => title = line["TITLE".len()..].trim().trim_matches('"').to_string(),
+                "LUT_3D_SIZE" => {
+                    size = parts.next().and_then(|value| value.parse().ok())
+                        .ok_or(format!("Line {}: invalid LUT_3D_SIZE", line_number + 1))?;
+                }
+                "LUT_1D_SIZE" => return Err("1D LUTs are not supported".to_string()),
+                "DOMAIN_MIN" => domain_min = parse_triplet(parts, line_number)?,
+                "DOMAIN_MAX" => domain_max = parse_triplet(parts, line_number)?,
+                "LUT_3D_INPUT_RANGE" => {
+                    let range: Vec<f32> = parts.filter_map(|value| value.parse().ok()).collect();
+                    if range.len() != 2 {
+                        return Err(format!("Line {}: invalid LUT_3D_INPUT_RANGE", line_number + 1));
+                    }
+                    domain_min = [range[0]; 3];
+                    domain_max = [range[1]; 3];
+                }
+                _ if keyword.parse::<f32>().is_ok() => {
+                    data.push(parse_triplet(line.split_whitespace(), line_number)?);
+                }
+                _ => {} // Unknown keywords are ignored like most tools do
+            }
+        }
+
+        if size < 2 {
+            return Err("Missing LUT_3D_SIZE".to_string());
+        }
+        if data.len() != size * size * size {
+            return Err(format!("Expected {} LUT entries, found {}", size * size * size, data.len()));
+        }
+        Ok(Self { title, size, domain_min, domain_max, data })
+    }
+
+    // A LUT that leaves colors unchanged, useful as a starting point
+    pub fn identity(size: usize) -> Self {
+        let size = size.max(2);
+        let mut data = Vec::with_capacity(size * size * size);
+        let max = (size - 1) as f32;
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.push([r as f32 / max, g as f32 / max, b as f32 / max]);
+                }
+            }
+        }
+        Self { title: String::new(), size, domain_min: [0.0; 3], domain_max: [1.0; 3], data }
+    }
+
+    // Trilinear interpolation between the 8 surrounding entries, input and output are 0.0..=1.0
+    pub fn sample(&self, color: [f32; 3]) -> [f32; 3] {
+        let max = (self.size - 1) as f32;
+        let mut low = [0usize; 3];
+        let mut high = [0usize; 3];
+        let mut fraction = [0.0f32; 3];
+        for i in 0..3 {
+            let range = (self.domain_max[i] - self.domain_min[i]).max(f32::EPSILON);
+            let t = ((color[i] - self.domain_min[i]) / range).clamp(0.0, 1.0) * max;
+            low[i] = t.floor() as usize;
+            high[i] = (low[i] + 1).min(self.size - 1);
+            fraction[i] = t - low[i] as f32;
+        }
+
+        let entry = |r: usize, g: usize, b: usize| self.data[r + g * self.size + b * self.size * self.size];
+        let mut result = [0.0f32; 3];
+        for (i, value) in result.iter_mut().enumerate() {
+            let c00 = lerp(entry(low[0], low[1], low[2])[i], entry(high[0], low[1], low[2])[i], fraction[0]);
+            let c10 = lerp(entry(low[0], high[1], low[2])[i], entry(high[0], high[1], low[2])[i], fraction[0]);
+            let c01 = lerp(entry(low[0], low[1], high[2])[i], entry(high[0], low[1], high[2])[i], fraction[0]);
+            let c11 = lerp(entry(low[0], high[1], high[2])[i], entry(high[0], high[1], high[2])[i], fraction[0]);
+            let c0 = lerp(c00, c10, fraction[1]);
+            let c1 = lerp(c01, c11, fraction[1]);
+            *value = lerp(c0, c1, fraction[2]);
+        }
+        result
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum PostFilter {
+    ColorGrade { lut: Lut3D, strength: f32 }, // strength 1.0 applies the LUT fully
+    Vignette { strength: f32, radius: f32, softness: f32 }, // radius and softness relative to the half diagonal
+    Scanlines { intensity: f32, spacing: u32 }, // Darkens every spacing-th row
+    Crt { scanline_intensity: f32, mask_intensity: f32 }, // Scanlines plus an RGB aperture grille
+    Desaturate { amount: f32 }, // 1.0 is grayscale
+    Posterize { levels: u32 },
+}
+
+// Filters applied in order to the finished frame. Everything runs on the CPU over RGBA32 pixels,
+// so it works with the software renderer and on surfaces without a window.
+#[derive(Clone, Debug)]
+pub struct PostProcess {
+    pub filters: Vec<PostFilter>,
+    pub enabled: bool,
+}
+
+impl Default for PostProcess {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl PostProcess {
+    pub fn new() -> Self {
+        Self { filters: Vec::new(), enabled: true }
+    }
+
+    pub fn add_filter(&mut self, filter: PostFilter) {
+        self.filters.push(filter);
+    }
+
+    pub fn clear_filters(&mut self) {
+        self.filters.clear();
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    // pixels are RGBA32 (r, g, b, a bytes in memory order), pitch is the length of one row in bytes
+    pub fn apply_to_buffer(&self, pixels: &mut [u8], width: u32, height: u32, pitch: usize) {
+        if !self.enabled {
+            return;
+        }
+        for filter in &self.filters {
+            for y in 0..height as usize {
+                for x in 0..width as usize {
+                    let offset = y * pitch + x * 4;
+                    if offset + 3 >= pixels.len() {
+                        continue;
+                    }
+                    let color = [
+                        pixels[offset] as f32 / 255.0,
+                        pixels[offset + 1] as f32 / 255.0,
+                        pixels[offset + 2] as f32 / 255.0,
+                    ];
+                    let color = apply_filter(filter, color, x as u32, y as u32, width, height);
+                    pixels[offset] = to_byte(color[0]);
+                    pixels[offset + 1] = to_byte(color[1]);
+                    pixels[offset + 2] = to_byte(color[2]);
+                }
+            }
+        }
+    }
+
+    // Surfaces in other formats are converted to RGBA32 first
+    pub fn apply_to_surface(&self, surface: &mut Surface) -> Result<(), String> {
+        if surface.pixel_format_enum() != PixelFormatEnum::RGBA32 {
+            *surface = surface.convert_format(PixelFormatEnum::RGBA32)?;
+        }
+        let (width, height, pitch) = (surface.width(), surface.height(), surface.pitch() as usize);
+        surface.with_lock_mut(|pixels| self.apply_to_buffer(pixels, width, height, pitch));
+        Ok(())
+    }
+
+    // Reads back the current frame, filters it and draws it over the whole canvas. Call right before present().
+    pub fn apply_to_canvas(&self, canvas: &mut Canvas<sdl2::video::Window>) -> Result<(), String> {
+        if !self.enabled || self.filters.is_empty() {
+            return Ok(());
+        }
+        let (width, height) = canvas.output_size()?;
+        let mut pixels = canvas.read_pixels(None, PixelFormatEnum::RGBA32)?;
+        let pitch = width as usize * 4;
+        self.apply_to_buffer(&mut pixels, width, height, pitch);
+
+        let surface = Surface::from_data(&mut pixels, width, height, pitch as u32, PixelFormatEnum::RGBA32)?;
+        let texture_creator = canvas.texture_creator();
+        let texture = texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string())?;
+        canvas.copy(&texture, None, None)
+    }
+}
+
+fn apply_filter(filter: &PostFilter, color: [f32; 3], x: u32, y: u32, width: u32, height: u32) -> [f32; 3] {
+    match filter {
+        PostFilter::ColorGrade { lut, strength } => {
+            let graded = lut.sample(color);
+            [lerp(color[0], graded[0], *strength), lerp(color[1], graded[1], *strength), lerp(color[2], graded[2], *strength)]
+        }
+        PostFilter::Vignette { strength, radius, softness } => {
+            let dx = (x as f32 + 0.5) / width as f32 - 0.5;
+            let dy = (y as f32 + 0.5) / height as f32 - 0.5;
+            // 1.0 at the corners
+            let distance = (dx * dx + dy * dy).sqrt() / 0.5f32.hypot(0.5);
+            let t = smoothstep(*radius, *radius + softness.max(f32::EPSILON), distance);
+            scale(color, 1.0 - t * strength)
+        }
+        PostFilter::Scanlines { intensity, spacing } => {
+            if y.is_multiple_of((*spacing).max(1)) {
+                scale(color, 1.0 - intensity)
+            } else {
+                color
+            }
+        }
+        PostFilter::Crt { scanline_intensity, mask_intensity } => {
+            let color = if y % 2 == 1 { scale(color, 1.0 - scanline_intensity) } else { color };
+            // Each column keeps one channel at full strength and dims the other two
+            let mut mask = [1.0 - mask_intensity; 3];
+            mask[(x % 3) as usize] = 1.0;
+            [color[0] * mask[0], color[1] * mask[1], color[2] * mask[2]]
+        }
+        PostFilter::Desaturate { amount } => {
+            let luma = 0.299 * color[0] + 0.587 * color[1] + 0.114 * color[2];
+            [lerp(color[0], luma, *amount), lerp(color[1], luma, *amount), lerp(color[2], luma, *amount)]
+        }
+        PostFilter::Posterize { levels } => {
+            let steps = ((*levels).max(2) - 1) as f32;
+            [(color[0] * steps).round() / steps, (color[1] * steps).round() / steps, (color[2] * steps).round() / steps]
+        }
+    }
+}
+
+fn parse_triplet<'s>(mut parts: impl Iterator<Item = &'s str>, line_number: usize) -> Result<[f32; 3], String> {
+    let mut values = [0.0f32; 3];
+    for value in values.iter_mut() {
+        *value = parts.next().and_then(|part| part.parse().ok())
+            .ok_or(format!("Line {}: expected three numbers", line_number + 1))?;
+    }
+    Ok(values)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn scale(color: [f32; 3], factor: f32) -> [f32; 3] {
+    [color[0] * factor, color[1] * factor, color[2] * factor]
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
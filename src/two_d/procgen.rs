@@ -0,0 +1,528 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use sdl2::rect::Rect;
+
+use std::collections::VecDeque;
+
+// Cell values of generated grids, the same ones Tile::from_generated_map treats as floor and wall
+pub const FLOOR: u32 = 0;
+pub const WALL: u32 = 2;
+
+pub type Grid = Vec<Vec<u32>>;
+
+// Output of the generators. Positions are cells (x, y), the grid is indexed grid[y][x].
+#[derive(Clone, Debug)]
+pub struct GeneratedMap {
+    pub grid: Grid,
+    pub rooms: Vec<Rect>,             // In cells, only BSP dungeons have rooms
+    pub start: Option<(usize, usize)>, // Suggested player start
+}
+
+#[allow(dead_code)]
+impl GeneratedMap {
+    pub fn width(&self) -> usize {
+        self.grid.first().map_or(0, |row| row.len())
+    }
+
+    pub fn height(&self) -> usize {
+        self.grid.len()
+    }
+
+    // Floor cell farthest (in steps) from the start, e.g. for the exit or a ladder
+    pub fn place_exit(&self) -> Option<(usize, usize)> {
+        place_exit(&self.grid, self.start?)
+    }
+
+    pub fn place_spawns(&self, count: usize, min_distance: u32, seed: u64) -> Vec<(usize, usize)> {
+        match self.start {
+            Some(start) => place_spawns(&self.grid, count, start, min_distance, seed),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn walled_grid(width: usize, height: usize) -> Grid {
+    vec![vec![WALL; width]; height]
+}
+
+// Rooms in the leaves of a binary space partition, connected by L-shaped corridors between siblings
+pub struct BspGenerator {
+    pub width: usize,
+    pub height: usize,
+    pub min_leaf_size: usize, // Smallest partition, rooms fit inside with a wall around them
+    pub min_room_size: usize,
+    pub seed: u64,
+}
+
+#[allow(dead_code)]
+impl BspGenerator {
+    pub fn new(width: usize, height: usize, seed: u64) -> Self {
+        Self { width, height, min_leaf_size: 8, min_room_size: 4, seed }
+    }
+
+    pub fn set_min_leaf_size(&mut self, min_leaf_size: usize) {
+        self.min_leaf_size = min_leaf_size.max(3);
+    }
+
+    pub fn set_min_room_size(&mut self, min_room_size: usize) {
+        self.min_room_size = min_room_size.max(1);
+    }
+
+    pub fn generate(&self) -> GeneratedMap {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut grid = walled_grid(self.width, self.height);
+        let mut rooms = Vec::new();
+        if self.width >= 3 && self.height >= 3 {
+            self.split(&mut rng, &mut grid, &mut rooms, (0, 0, self.width, self.height));
+        }
+        let start = rooms.first().map(|room: &Rect| (room.center().x() as usize, room.center().y() as usize));
+        GeneratedMap { grid, rooms, start }
+    }
+
+    // Returns the rooms made inside the leaf so the caller can connect them to the other half
+    fn split(&self, rng: &mut StdRng, grid: &mut Grid, rooms: &mut Vec<Rect>, leaf: (usize, usize, usize, usize)) -> Vec<usize> {
+        let (x, y, width, height) = leaf;
+        let min = self.min_leaf_size.max(3);
+        let can_split_x = width >= min * 2;
+        let can_split_y = height >= min * 2;
+        if !can_split_x && !can_split_y {
+            return self.make_room(rng, grid, rooms, leaf).into_iter().collect();
+        }
+        // Split across the longer side so leaves stay roughly square
+        let split_x = match (can_split_x, can_split_y) {
+            (true, false) => true,
+            (false, true) => false,
+            _ if width as f32 / height as f32 >= 1.25 => true,
+            _ if height as f32 / width as f32 >= 1.25 => false,
+            _ => rng.gen_bool(0.5),
+        };
+        let (first, second) = if split_x {
+            let at = rng.gen_range(min..=width - min);
+            ((x, y, at, height), (x + at, y, width - at, height))
+        } else {
+            let at = rng.gen_range(min..=height - min);
+            ((x, y, width, at), (x, y + at, width, height - at))
+        };
+        let first_rooms = self.split(rng, grid, rooms, first);
+        let second_rooms = self.split(rng, grid, rooms, second);
+        if let (Some(&a), Some(&b)) = (first_rooms.choose(rng), second_rooms.choose(rng)) {
+            let (from, to) = (rooms[a].center(), rooms[b].center());
+            carve_corridor(grid, (from.x() as usize, from.y() as usize), (to.x() as usize, to.y() as usize), rng.gen_bool(0.5));
+        }
+        first_rooms.into_iter().chain(second_rooms).collect()
+    }
+
+    fn make_room(&self, rng: &mut StdRng, grid: &mut Grid, rooms: &mut Vec<Rect>, leaf: (usize, usize, usize, usize)) -> Option<usize> {
+        let (x, y, width, height) = leaf;
+        // One cell of wall on every side of the room
+        let (max_width, max_height) = (width.checked_sub(2)?, height.checked_sub(2)?);
+        if max_width == 0 || max_height == 0 {
+            return None;
+        }
+        let room_width = rng.gen_range(self.min_room_size.min(max_width)..=max_width);
+        let room_height = rng.gen_range(self.min_room_size.min(max_height)..=max_height);
+        let room_x = x + 1 + rng.gen_range(0..=max_width - room_width);
+        let room_y = y + 1 + rng.gen_range(0..=max_height - room_height);
+        for row in grid.iter_mut().skip(room_y).take(room_height) {
+            for cell in row.iter_mut().skip(room_x).take(room_width) {
+                *cell = FLOOR;
+            }
+        }
+        rooms.push(Rect::new(room_x as i32, room_y as i32, room_width as u32, room_height as u32));
+        Some(rooms.len() - 1)
+    }
+}
+
+fn carve_corridor(grid: &mut Grid, from: (usize, usize), to: (usize, usize), horizontal_first: bool) {
+    let corner = if horizontal_first { (to.0, from.1) } else { (from.0, to.1) };
+    for (a, b) in [(from, corner), (corner, to)] {
+        for y in a.1.min(b.1)..=a.1.max(b.1) {
+            for x in a.0.min(b.0)..=a.0.max(b.0) {
+                if let Some(cell) = grid.get_mut(y).and_then(|row| row.get_mut(x)) {
+                    *cell = FLOOR;
+                }
+            }
+        }
+    }
+}
+
+// Random walk carving floor until a share of the map is open, gives winding connected caves
+pub struct DrunkardWalk {
+    pub width: usize,
+    pub height: usize,
+    pub fill_ratio: f32, // 0.0..1.0 of the inner cells to open up
+    pub max_steps: usize,
+    pub start: Option<(usize, usize)>, // None starts in the middle
+    pub seed: u64,
+}
+
+#[allow(dead_code)]
+impl DrunkardWalk {
+    pub fn new(width: usize, height: usize, seed: u64) -> Self {
+        Self { width, height, fill_ratio: 0.4, max_steps: width * height * 20, start: None, seed }
+    }
+
+    pub fn set_fill_ratio(&mut self, fill_ratio: f32) {
+        self.fill_ratio = fill_ratio.clamp(0.0, 1.0);
+    }
+
+    pub fn set_start(&mut self, x: usize, y: usize) {
+        self.start = Some((x, y));
+    }
+
+    pub fn generate(&self) -> GeneratedMap {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut grid = walled_grid(self.width, self.height);
+        if self.width < 3 || self.height < 3 {
+            return GeneratedMap { grid, rooms: Vec::new(), start: None };
+        }
+        // The border always stays wall
+        let clamp = |x: usize, y: usize| (x.clamp(1, self.width - 2), y.clamp(1, self.height - 2));
+        let start = match self.start {
+            Some((x, y)) => clamp(x, y),
+            None => clamp(self.width / 2, self.height / 2),
+        };
+        let target = (((self.width - 2) * (self.height - 2)) as f32 * self.fill_ratio).ceil() as usize;
+        let (mut x, mut y) = start;
+        grid[y][x] = FLOOR;
+        let mut open = 1;
+        for _ in 0..self.max_steps {
+            if open >= target {
+                break;
+            }
+            let (dx, dy) = [(1, 0), (-1, 0), (0, 1), (0, -1)][rng.gen_range(0..4)];
+            let (nx, ny) = clamp((x as i32 + dx).max(0) as usize, (y as i32 + dy).max(0) as usize);
+            x = nx;
+            y = ny;
+            if grid[y][x] != FLOOR {
+                grid[y][x] = FLOOR;
+                open += 1;
+            }
+        }
+        GeneratedMap { grid, rooms: Vec::new(), start: Some(start) }
+    }
+}
+
+// Random noise smoothed by cellular automata rules into organic caves
+pub struct CaveGenerator {
+    pub width: usize,
+    pub height: usize,
+    pub fill_probability: f64, // Chance of a cell starting as wall
+    pub iterations: usize,
+    pub birth_limit: usize,    // Floor becomes wall with at least this many wall neighbours
+    pub survival_limit: usize, // Wall stays wall with at least this many wall neighbours
+    pub keep_largest: bool,    // Fills every cave but the largest so the map is connected
+    pub seed: u64,
+}
+
+#[allow(dead_code)]
+impl CaveGenerator {
+    pub fn new(width: usize, height: usize, seed: u64) -> Self {
+        Self {
+            width,
+            height,
+            fill_probability: 0.45,
+            iterations: 5,
+            birth_limit: 5,
+            survival_limit: 4,
+            keep_largest: true,
+            seed,
+        }
+    }
+
+    pub fn set_fill_probability(&mut self, fill_probability: f64) {
+        self.fill_probability = fill_probability.clamp(0.0, 1.0);
+    }
+
+    pub fn set_iterations(&mut self, iterations: usize) {
+        self.iterations = iterations;
+    }
+
+    pub fn set_limits(&mut self, birth_limit: usize, survival_limit: usize) {
+        self.birth_limit = birth_limit;
+        self.survival_limit = survival_limit;
+    }
+
+    pub fn set_keep_largest(&mut self, keep_largest: bool) {
+        self.keep_largest = keep_largest;
+    }
+
+    pub fn generate(&self) -> GeneratedMap {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let (width, height) = (self.width, self.height);
+        let border = |x: usize, y: usize| x == 0 || y == 0 || x + 1 >= width || y + 1 >= height;
+        let mut grid: Grid = (0..height)
+            .map(|y| (0..width).map(|x| if border(x, y) || rng.gen_bool(self.fill_probability) { WALL } else { FLOOR }).collect())
+            .collect();
+
+        for _ in 0..self.iterations {
+            let previous = grid.clone();
+            for (y, row) in grid.iter_mut().enumerate() {
+                for (x, cell) in row.iter_mut().enumerate() {
+                    if border(x, y) {
+                        continue;
+                    }
+                    // Cells outside the map count as wall
+                    let mut walls = 0;
+                    for dy in -1i32..=1 {
+                        for dx in -1i32..=1 {
+                            if dx == 0 && dy == 0 {
+                                continue;
+                            }
+                            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                            if nx < 0 || ny < 0 || previous.get(ny as usize).and_then(|row| row.get(nx as usize)).is_none_or(|&neighbour| neighbour == WALL) {
+                                walls += 1;
+                            }
+                        }
+                    }
+                    let wall = if previous[y][x] == WALL { walls >= self.survival_limit } else { walls >= self.birth_limit };
+                    *cell = if wall { WALL } else { FLOOR };
+                }
+            }
+        }
+
+        let caves = regions(&grid);
+        if self.keep_largest {
+            for cave in caves.iter().skip(1) {
+                for &(x, y) in cave {
+                    grid[y][x] = WALL;
+                }
+            }
+        }
+        let start = caves.first().and_then(|cave| cave.choose(&mut rng)).copied();
+        GeneratedMap { grid, rooms: Vec::new(), start }
+    }
+}
+
+// Connected floor areas, largest first
+pub fn regions(grid: &Grid) -> Vec<Vec<(usize, usize)>> {
+    let mut seen: Vec<Vec<bool>> = grid.iter().map(|row| vec![false; row.len()]).collect();
+    let mut regions = Vec::new();
+    for (y, row) in grid.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            if cell == WALL || seen[y][x] {
+                continue;
+            }
+            let mut region = Vec::new();
+            let mut queue = VecDeque::from([(x, y)]);
+            seen[y][x] = true;
+            while let Some((cx, cy)) = queue.pop_front() {
+                region.push((cx, cy));
+                for (nx, ny) in neighbours(grid, cx, cy) {
+                    if !seen[ny][nx] {
+                        seen[ny][nx] = true;
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+            regions.push(region);
+        }
+    }
+    regions.sort_by_key(|region| std::cmp::Reverse(region.len()));
+    regions
+}
+
+// Walkable 4-neighbours of a cell, everything but walls can be walked on
+fn neighbours(grid: &Grid, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+    [(1i32, 0i32), (-1, 0), (0, 1), (0, -1)].into_iter().filter_map(move |(dx, dy)| {
+        let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+        if nx < 0 || ny < 0 {
+            return None;
+        }
+        match grid.get(ny as usize).and_then(|row| row.get(nx as usize)) {
+            Some(&cell) if cell != WALL => Some((nx as usize, ny as usize)),
+            _ => None,
+        }
+    })
+}
+
+// Steps from a cell to every reachable cell, None where it cannot be reached
+pub fn distance_map(grid: &Grid, from: (usize, usize)) -> Vec<Vec<Option<u32>>> {
+    let mut distances: Vec<Vec<Option<u32>>> = grid.iter().map(|row| vec![None; row.len()]).collect();
+    if grid.get(from.1).and_then(|row| row.get(from.0)).is_none_or(|&cell| cell == WALL) {
+        return distances;
+    }
+    distances[from.1][from.0] = Some(0);
+    let mut queue = VecDeque::from([from]);
+    while let Some((x, y)) = queue.pop_front() {
+        let distance = distances[y][x].unwrap_or(0);
+        for (nx, ny) in neighbours(grid, x, y) {
+            if distances[ny][nx].is_none() {
+                distances[ny][nx] = Some(distance + 1);
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    distances
+}
+
+pub fn floor_cells(grid: &Grid) -> Vec<(usize, usize)> {
+    grid.iter().enumerate()
+        .flat_map(|(y, row)| row.iter().enumerate().filter(|(_, &cell)| cell == FLOOR).map(move |(x, _)| (x, y)))
+        .collect()
+}
+
+// Reachable floor cell farthest from the start
+pub fn place_exit(grid: &Grid, start: (usize, usize)) -> Option<(usize, usize)> {
+    let distances = distance_map(grid, start);
+    floor_cells(grid).into_iter()
+        .filter_map(|(x, y)| distances[y][x].map(|distance| (distance, (x, y))))
+        .filter(|&(distance, _)| distance > 0)
+        .max_by_key(|&(distance, _)| distance)
+        .map(|(_, cell)| cell)
+}
+
+// Distinct reachable floor cells at least min_distance steps from the start, e.g. for enemies
+pub fn place_spawns(grid: &Grid, count: usize, start: (usize, usize), min_distance: u32, seed: u64) -> Vec<(usize, usize)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let distances = distance_map(grid, start);
+    let mut candidates: Vec<(usize, usize)> = floor_cells(grid).into_iter()
+        .filter(|&(x, y)| distances[y][x].is_some_and(|distance| distance >= min_distance.max(1)))
+        .collect();
+    candidates.shuffle(&mut rng);
+    candidates.truncate(count);
+    candidates
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoiseKind {
+    Value,
+    Perlin,
+    Simplex,
+}
+
+// Seeded 2D noise with octaves (fractal Brownian motion), samples are 0.0..1.0
+pub struct NoiseField {
+    pub kind: NoiseKind,
+    pub scale: f32, // Cells per noise period of the first octave
+    pub octaves: u32,
+    pub persistence: f32, // Amplitude factor per octave
+    pub lacunarity: f32,  // Frequency factor per octave
+    permutation: Vec<usize>,
+}
+
+#[allow(dead_code)]
+impl NoiseField {
+    pub fn new(kind: NoiseKind, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut table: Vec<usize> = (0..256).collect();
+        table.shuffle(&mut rng);
+        let permutation = table.iter().chain(table.iter()).copied().collect();
+        Self { kind, scale: 16.0, octaves: 4, persistence: 0.5, lacunarity: 2.0, permutation }
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0.0001);
+    }
+
+    pub fn set_octaves(&mut self, octaves: u32, persistence: f32, lacunarity: f32) {
+        self.octaves = octaves.max(1);
+        self.persistence = persistence;
+        self.lacunarity = lacunarity;
+    }
+
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        let (mut frequency, mut amplitude) = (1.0 / self.scale, 1.0);
+        let (mut total, mut max) = (0.0, 0.0);
+        for _ in 0..self.octaves {
+            total += self.single(x * frequency, y * frequency) * amplitude;
+            max += amplitude;
+            amplitude *= self.persistence;
+            frequency *= self.lacunarity;
+        }
+        ((total / max) * 0.5 + 0.5).clamp(0.0, 1.0)
+    }
+
+    pub fn field(&self, width: usize, height: usize) -> Vec<Vec<f32>> {
+        (0..height).map(|y| (0..width).map(|x| self.sample(x as f32, y as f32)).collect()).collect()
+    }
+
+    // Tile ids by height bands: bands are (upper limit, tile), e.g. [(0.3, water), (0.6, grass), (1.0, rock)]
+    pub fn grid(&self, width: usize, height: usize, bands: &[(f32, u32)]) -> Grid {
+        self.field(width, height).into_iter()
+            .map(|row| row.into_iter()
+                .map(|value| bands.iter().find(|(limit, _)| value <= *limit).or(bands.last()).map_or(FLOOR, |(_, tile)| *tile))
+                .collect())
+            .collect()
+    }
+
+    fn hash(&self, x: i32, y: i32) -> usize {
+        self.permutation[self.permutation[(x & 255) as usize] + (y & 255) as usize]
+    }
+
+    // -1.0..1.0
+    fn single(&self, x: f32, y: f32) -> f32 {
+        match self.kind {
+            NoiseKind::Value => self.value(x, y),
+            NoiseKind::Perlin => self.perlin(x, y),
+            NoiseKind::Simplex => self.simplex(x, y),
+        }
+    }
+
+    fn value(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+        let (u, v) = (fade(x - x0 as f32), fade(y - y0 as f32));
+        let corner = |cx: i32, cy: i32| self.hash(cx, cy) as f32 / 127.5 - 1.0;
+        lerp(lerp(corner(x0, y0), corner(x0 + 1, y0), u), lerp(corner(x0, y0 + 1), corner(x0 + 1, y0 + 1), u), v)
+    }
+
+    fn perlin(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+        let (u, v) = (fade(fx), fade(fy));
+        let corner = |cx: i32, cy: i32, dx: f32, dy: f32| gradient(self.hash(cx, cy), dx, dy);
+        let top = lerp(corner(x0, y0, fx, fy), corner(x0 + 1, y0, fx - 1.0, fy), u);
+        let bottom = lerp(corner(x0, y0 + 1, fx, fy - 1.0), corner(x0 + 1, y0 + 1, fx - 1.0, fy - 1.0), u);
+        // Gradient noise peaks at about +-0.7, stretch it to the same range as the others
+        (lerp(top, bottom, v) * 1.4).clamp(-1.0, 1.0)
+    }
+
+    fn simplex(&self, x: f32, y: f32) -> f32 {
+        let f2 = 0.5 * (3.0f32.sqrt() - 1.0);
+        let g2 = (3.0 - 3.0f32.sqrt()) / 6.0;
+        let s = (x + y) * f2;
+        let (i, j) = ((x + s).floor() as i32, (y + s).floor() as i32);
+        let t = (i + j) as f32 * g2;
+        let (x0, y0) = (x - (i as f32 - t), y - (j as f32 - t));
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let corners = [
+            (x0, y0, i, j),
+            (x0 - i1 as f32 + g2, y0 - j1 as f32 + g2, i + i1, j + j1),
+            (x0 - 1.0 + 2.0 * g2, y0 - 1.0 + 2.0 * g2, i + 1, j + 1),
+        ];
+        let mut total = 0.0;
+        for (dx, dy, cx, cy) in corners {
+            let falloff = 0.5 - dx * dx - dy * dy;
+            if falloff > 0.0 {
+                total += falloff.powi(4) * gradient(self.hash(cx, cy), dx, dy);
+            }
+        }
+        (70.0 * total).clamp(-1.0, 1.0)
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn gradient(hash: usize, x: f32, y: f32) -> f32 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
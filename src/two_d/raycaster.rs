@@ -0,0 +1,439 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::camera3d::Camera3D;
+use crate::two_d::tile::Tile;
+
+use nalgebra::Vector2;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::video::WindowContext;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+// Texture kept on the CPU, the raycaster samples single pixels from it
+#[derive(Clone, Debug)]
+pub struct RaycastTexture {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+#[allow(dead_code)]
+impl RaycastTexture {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let image = image::open(path).map_err(|e| format!("{}: {}", path.display(), e))?.to_rgba8();
+        let (width, height) = image.dimensions();
+        Ok(Self { width, height, pixels: image.pixels().map(|pixel| pixel.0).collect() })
+    }
+
+    pub fn from_color(color: Color) -> Self {
+        Self { width: 1, height: 1, pixels: vec![[color.r, color.g, color.b, color.a]] }
+    }
+
+    // u and v in 0.0..1.0, wrapping
+    pub fn sample(&self, u: f64, v: f64) -> [u8; 4] {
+        let x = ((u.rem_euclid(1.0) * self.width as f64) as u32).min(self.width - 1);
+        let y = ((v.rem_euclid(1.0) * self.height as f64) as u32).min(self.height - 1);
+        self.pixels[(x + y * self.width) as usize]
+    }
+}
+
+// Billboard always facing the camera, position in cells
+#[derive(Clone, Debug)]
+pub struct RaycastSprite {
+    pub position: Vector2<f64>,
+    pub texture: usize,
+    pub scale: f64,           // 1.0 is as tall as a wall
+    pub vertical_offset: f64, // In wall heights, positive moves it down, e.g. 0.25 for an item on the floor
+}
+
+#[allow(dead_code)]
+impl RaycastSprite {
+    pub fn new(position: Vector2<f64>, texture: usize) -> Self {
+        Self { position, texture, scale: 1.0, vertical_offset: 0.0 }
+    }
+}
+
+// Sliding door in the middle of its cell, open goes from 0.0 (closed) to 1.0
+#[derive(Clone, Debug)]
+pub struct RaycastDoor {
+    pub open: f64,
+    pub target: f64,
+    pub speed: f64, // Openings per second
+}
+
+impl Default for RaycastDoor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl RaycastDoor {
+    pub fn new() -> Self {
+        Self { open: 0.0, target: 0.0, speed: 1.5 }
+    }
+
+    pub fn is_passable(&self) -> bool {
+        self.open > 0.9
+    }
+}
+
+struct RayHit {
+    distance: f64,
+    tile: u32,
+    cell: (i32, i32),
+    side: usize,   // 0 crossed a vertical grid line, 1 a horizontal one
+    wall_x: f64,   // Where along the wall the ray hit, 0.0..1.0
+}
+
+// Draws a Tile grid from the first person view of a Camera3D, one cell per tile.
+// Everything is drawn into a CPU buffer at width x height and stretched to the camera size,
+// so a lower resolution is a cheap way to go faster.
+pub struct Raycaster<'a> {
+    width: u32,
+    height: u32,
+    pub textures: Vec<RaycastTexture>,
+    pub wall_textures: HashMap<u32, usize>,  // Tile id -> texture
+    pub floor_textures: HashMap<u32, usize>, // Tile id of the floor cell -> texture
+    pub ceiling_texture: Option<usize>,
+    pub wall_color: Color,    // Walls without a texture
+    pub floor_color: Color,
+    pub ceiling_color: Color,
+    pub fog_color: Color,
+    pub fog_start: f64, // Cells, fog begins here
+    pub fog_end: f64,   // and fully covers everything from here on
+    pub max_distance: f64,
+    pub shade_sides: bool, // Darkens walls facing north and south for contrast
+    pub collision_radius: f64,
+    pub doors: HashMap<(usize, usize), RaycastDoor>,
+    z_buffer: Vec<f64>,
+    pixels: Vec<u8>,
+    texture: Texture<'a>,
+    texture_creator: &'a TextureCreator<WindowContext>,
+}
+
+#[allow(dead_code)]
+impl<'a> Raycaster<'a> {
+    pub fn new(
+        texture_creator: &'a TextureCreator<WindowContext>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, String> {
+        let texture = texture_creator.create_texture_streaming(PixelFormatEnum::RGBA32, width, height).map_err(|e| e.to_string())?;
+        Ok(Self {
+            width,
+            height,
+            textures: Vec::new(),
+            wall_textures: HashMap::new(),
+            floor_textures: HashMap::new(),
+            ceiling_texture: None,
+            wall_color: Color::RGB(128, 128, 128),
+            floor_color: Color::RGB(60, 60, 60),
+            ceiling_color: Color::RGB(30, 30, 40),
+            fog_color: Color::RGB(0, 0, 0),
+            fog_start: 4.0,
+            fog_end: 16.0,
+            max_distance: 64.0,
+            shade_sides: true,
+            collision_radius: 0.2,
+            doors: HashMap::new(),
+            z_buffer: vec![f64::INFINITY; width as usize],
+            pixels: vec![0; (width * height * 4) as usize],
+            texture,
+            texture_creator,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Reallocates the buffers for a new internal resolution
+    pub fn set_resolution(&mut self, width: u32, height: u32) -> Result<(), String> {
+        if (width, height) == (self.width, self.height) {
+            return Ok(());
+        }
+        self.texture = self.texture_creator.create_texture_streaming(PixelFormatEnum::RGBA32, width, height).map_err(|e| e.to_string())?;
+        self.width = width;
+        self.height = height;
+        self.z_buffer = vec![f64::INFINITY; width as usize];
+        self.pixels = vec![0; (width * height * 4) as usize];
+        Ok(())
+    }
+
+    pub fn add_texture(&mut self, texture: RaycastTexture) -> usize {
+        self.textures.push(texture);
+        self.textures.len() - 1
+    }
+
+    pub fn load_texture(&mut self, path: &Path) -> Result<usize, String> {
+        Ok(self.add_texture(RaycastTexture::load(path)?))
+    }
+
+    pub fn set_wall_texture(&mut self, tile_id: u32, texture: usize) {
+        self.wall_textures.insert(tile_id, texture);
+    }
+
+    pub fn set_floor_texture(&mut self, tile_id: u32, texture: usize) {
+        self.floor_textures.insert(tile_id, texture);
+    }
+
+    pub fn set_ceiling_texture(&mut self, texture: Option<usize>) {
+        self.ceiling_texture = texture;
+    }
+
+    pub fn set_fog(&mut self, color: Color, start: f64, end: f64) {
+        self.fog_color = color;
+        self.fog_start = start;
+        self.fog_end = end.max(start);
+    }
+
+    // The cell is drawn as a door with the wall texture of its tile
+    pub fn add_door(&mut self, x: usize, y: usize) {
+        self.doors.insert((x, y), RaycastDoor::new());
+    }
+
+    pub fn door_at(&self, x: usize, y: usize) -> Option<&RaycastDoor> {
+        self.doors.get(&(x, y))
+    }
+
+    pub fn set_door_open(&mut self, x: usize, y: usize, open: bool) {
+        if let Some(door) = self.doors.get_mut(&(x, y)) {
+            door.target = if open { 1.0 } else { 0.0 };
+        }
+    }
+
+    pub fn toggle_door(&mut self, x: usize, y: usize) {
+        if let Some(door) = self.doors.get_mut(&(x, y)) {
+            door.target = if door.target > 0.5 { 0.0 } else { 1.0 };
+        }
+    }
+
+    // Toggles the door right in front of the camera, returns its cell
+    pub fn interact(&mut self, camera: &Camera3D, reach: f64) -> Option<(usize, usize)> {
+        let forward = camera.forward();
+        let steps = (reach / 0.1).ceil() as usize;
+        for step in 1..=steps {
+            let point = camera.position + forward * (step as f64 * 0.1).min(reach);
+            let cell = (point.x.floor() as usize, point.y.floor() as usize);
+            if point.x >= 0.0 && point.y >= 0.0 && self.doors.contains_key(&cell) {
+                self.toggle_door(cell.0, cell.1);
+                return Some(cell);
+            }
+        }
+        None
+    }
+
+    // Animates the doors, delta_time in seconds
+    pub fn update(&mut self, delta_time: f64) {
+        for door in self.doors.values_mut() {
+            let step = door.speed * delta_time;
+            door.open = if door.open < door.target { (door.open + step).min(door.target) } else { (door.open - step).max(door.target) };
+        }
+    }
+
+    // Solid tiles, closed doors and everything outside the map
+    pub fn is_blocked(&self, tile: &Tile, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 {
+            return true;
+        }
+        let (x, y) = (x as usize, y as usize);
+        if let Some(door) = self.doors.get(&(x, y)) {
+            return !door.is_passable();
+        }
+        tile.tile_at(x, y).is_none_or(|tile_id| tile.config.is_solid(tile_id))
+    }
+
+    // forward and strafe in cells, sliding along walls
+    pub fn move_camera(&self, camera: &mut Camera3D, tile: &Tile, forward: f64, strafe: f64) {
+        let delta = camera.forward() * forward + camera.right() * strafe;
+        camera.move_with_collision(delta, self.collision_radius, |x, y| self.is_blocked(tile, x, y));
+    }
+
+    pub fn render(&mut self, canvas: &mut Canvas<sdl2::video::Window>, camera: &Camera3D, tile: &Tile, sprites: &[RaycastSprite]) -> Result<(), String> {
+        self.render_floor_and_ceiling(camera, tile);
+        self.render_walls(camera, tile);
+        self.render_sprites(camera, sprites);
+        self.texture.update(None, &self.pixels, (self.width * 4) as usize).map_err(|e| e.to_string())?;
+        canvas.copy(&self.texture, None, sdl2::rect::Rect::new(0, 0, camera.size.x, camera.size.y))
+    }
+
+    fn fog(&self, color: [u8; 4], distance: f64) -> [u8; 4] {
+        let amount = if self.fog_end > self.fog_start { ((distance - self.fog_start) / (self.fog_end - self.fog_start)).clamp(0.0, 1.0) } else { 0.0 };
+        let mix = |value: u8, fog: u8| (value as f64 + (fog as f64 - value as f64) * amount) as u8;
+        [mix(color[0], self.fog_color.r), mix(color[1], self.fog_color.g), mix(color[2], self.fog_color.b), 255]
+    }
+
+    fn put(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let index = ((x + y * self.width) * 4) as usize;
+        self.pixels[index..index + 4].copy_from_slice(&color);
+    }
+
+    // Casts one ray per screen row through the floor plane, the ceiling mirrors it
+    fn render_floor_and_ceiling(&mut self, camera: &Camera3D, tile: &Tile) {
+        let (width, height) = (self.width, self.height);
+        let left = camera.direction - camera.plane;
+        let right = camera.direction + camera.plane;
+        let camera_height = 0.5 * height as f64;
+        let floor_color = [self.floor_color.r, self.floor_color.g, self.floor_color.b, 255];
+        let ceiling_color = [self.ceiling_color.r, self.ceiling_color.g, self.ceiling_color.b, 255];
+        for y in height / 2..height {
+            let row = y as f64 - height as f64 / 2.0 + 0.5;
+            let distance = camera_height / row;
+            let step = (right - left) * distance / width as f64;
+            let mut point = camera.position + left * distance;
+            for x in 0..width {
+                let (cell_x, cell_y) = (point.x.floor(), point.y.floor());
+                let (u, v) = (point.x - cell_x, point.y - cell_y);
+                let floor_texture = if cell_x >= 0.0 && cell_y >= 0.0 {
+                    tile.tile_at(cell_x as usize, cell_y as usize).and_then(|tile_id| self.floor_textures.get(&tile_id))
+                } else {
+                    None
+                };
+                let floor = floor_texture.map_or(floor_color, |&texture| self.textures[texture].sample(u, v));
+                let ceiling = self.ceiling_texture.map_or(ceiling_color, |texture| self.textures[texture].sample(u, v));
+                let (floor, ceiling) = (self.fog(floor, distance), self.fog(ceiling, distance));
+                self.put(x, y, floor);
+                self.put(x, height - y - 1, ceiling);
+                point += step;
+            }
+        }
+    }
+
+    fn render_walls(&mut self, camera: &Camera3D, tile: &Tile) {
+        let (width, height) = (self.width, self.height);
+        for x in 0..width {
+            let camera_x = 2.0 * x as f64 / width as f64 - 1.0;
+            let ray = camera.direction + camera.plane * camera_x;
+            let hit = match self.cast(camera.position, ray, tile) {
+                Some(hit) => hit,
+                None => {
+                    self.z_buffer[x as usize] = f64::INFINITY;
+                    continue;
+                }
+            };
+            self.z_buffer[x as usize] = hit.distance;
+
+            let line_height = (height as f64 / hit.distance.max(0.0001)) as i64;
+            let top = height as i64 / 2 - line_height / 2;
+            let texture = self.wall_textures.get(&hit.tile).copied();
+            // Doors slide sideways, the texture moves with them
+            let u = match self.doors.get(&(hit.cell.0 as usize, hit.cell.1 as usize)) {
+                Some(door) => hit.wall_x - door.open,
+                None if (hit.side == 0 && ray.x > 0.0) || (hit.side == 1 && ray.y < 0.0) => 1.0 - hit.wall_x,
+                None => hit.wall_x,
+            };
+            let wall_color = [self.wall_color.r, self.wall_color.g, self.wall_color.b, 255];
+            let shade = if self.shade_sides && hit.side == 1 { 0.75 } else { 1.0 };
+            for y in top.max(0)..(top + line_height).min(height as i64) {
+                let v = (y - top) as f64 / line_height.max(1) as f64;
+                let color = texture.map_or(wall_color, |texture| self.textures[texture].sample(u, v));
+                let color = [(color[0] as f64 * shade) as u8, (color[1] as f64 * shade) as u8, (color[2] as f64 * shade) as u8, 255];
+                let color = self.fog(color, hit.distance);
+                self.put(x, y as u32, color);
+            }
+        }
+    }
+
+    // DDA through the grid until a solid tile or a closed part of a door
+    fn cast(&self, origin: Vector2<f64>, ray: Vector2<f64>, tile: &Tile) -> Option<RayHit> {
+        let mut cell = (origin.x.floor() as i32, origin.y.floor() as i32);
+        let delta = Vector2::new((1.0 / ray.x).abs(), (1.0 / ray.y).abs());
+        let step = (if ray.x < 0.0 { -1 } else { 1 }, if ray.y < 0.0 { -1 } else { 1 });
+        let mut side_distance = Vector2::new(
+            if ray.x < 0.0 { origin.x - cell.0 as f64 } else { cell.0 as f64 + 1.0 - origin.x } * delta.x,
+            if ray.y < 0.0 { origin.y - cell.1 as f64 } else { cell.1 as f64 + 1.0 - origin.y } * delta.y,
+        );
+        loop {
+            let side = if side_distance.x < side_distance.y {
+                side_distance.x += delta.x;
+                cell.0 += step.0;
+                0
+            } else {
+                side_distance.y += delta.y;
+                cell.1 += step.1;
+                1
+            };
+            let distance = if side == 0 { side_distance.x - delta.x } else { side_distance.y - delta.y };
+            if distance > self.max_distance {
+                return None;
+            }
+            if cell.0 < 0 || cell.1 < 0 {
+                return None;
+            }
+            let tile_id = tile.tile_at(cell.0 as usize, cell.1 as usize)?;
+
+            if let Some(door) = self.doors.get(&(cell.0 as usize, cell.1 as usize)) {
+                // The door sits half a cell further in, the ray may leave through the side before reaching it
+                let distance = distance + if side == 0 { delta.x } else { delta.y } * 0.5;
+                let point = origin + ray * distance;
+                let (along, inside) = if side == 0 { (point.y, point.y.floor() as i32 == cell.1) } else { (point.x, point.x.floor() as i32 == cell.0) };
+                let wall_x = along - along.floor();
+                if inside && wall_x >= door.open {
+                    return Some(RayHit { distance, tile: tile_id, cell, side, wall_x });
+                }
+                continue;
+            }
+
+            if tile.config.is_solid(tile_id) {
+                let point = origin + ray * distance;
+                let along = if side == 0 { point.y } else { point.x };
+                return Some(RayHit { distance, tile: tile_id, cell, side, wall_x: along - along.floor() });
+            }
+        }
+    }
+
+    // Farthest first, each column only where the sprite is closer than the wall
+    fn render_sprites(&mut self, camera: &Camera3D, sprites: &[RaycastSprite]) {
+        let (width, height) = (self.width as i64, self.height as i64);
+        let mut order: Vec<(f64, &RaycastSprite)> = sprites.iter()
+            .filter(|sprite| sprite.texture < self.textures.len())
+            .map(|sprite| ((sprite.position - camera.position).norm_squared(), sprite))
+            .collect();
+        order.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let determinant = camera.plane.x * camera.direction.y - camera.direction.x * camera.plane.y;
+        if determinant.abs() < f64::EPSILON {
+            return;
+        }
+        for (_, sprite) in order {
+            let relative = sprite.position - camera.position;
+            // Camera space: x across the screen, depth into it
+            let across = (camera.direction.y * relative.x - camera.direction.x * relative.y) / determinant;
+            let depth = (-camera.plane.y * relative.x + camera.plane.x * relative.y) / determinant;
+            if depth <= 0.1 {
+                continue;
+            }
+            let screen_x = ((width as f64 / 2.0) * (1.0 + across / depth)) as i64;
+            let size = ((height as f64 / depth) * sprite.scale).abs() as i64;
+            if size <= 0 {
+                continue;
+            }
+            let offset = (sprite.vertical_offset * height as f64 / depth) as i64;
+            let top = height / 2 - size / 2 + offset;
+            let left = screen_x - size / 2;
+            for x in left.max(0)..(left + size).min(width) {
+                if depth >= self.z_buffer[x as usize] {
+                    continue;
+                }
+                let u = (x - left) as f64 / size as f64;
+                for y in top.max(0)..(top + size).min(height) {
+                    let v = (y - top) as f64 / size as f64;
+                    let color = self.textures[sprite.texture].sample(u, v);
+                    if color[3] < 128 {
+                        continue;
+                    }
+                    let color = self.fog(color, depth);
+                    self.put(x as u32, y as u32, color);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::animated_texture::AnimatedTexture;
+use crate::two_d::camera::Camera;
+use crate::two_d::texture_manager::TextureManager;
+use crate::two_d::texture_manager_anim::TextureManagerAnim;
+use crate::two_d::Rect;
+
+use sdl2::render::{Canvas, Texture};
+
+// Settings of one render layer. Layers are drawn in ascending id order,
+// the same way the editor sorts images by their layer.
+#[derive(Clone, Debug)]
+pub struct RenderLayer {
+    pub id: i32,
+    pub name: String,
+    pub visible: bool,
+    pub parallax: f32, // 1.0 moves with the camera, 0.0 stays fixed on screen
+    pub y_sort: bool,  // Draw commands with a lower bottom edge first (top-down games)
+}
+
+impl RenderLayer {
+    pub fn new(id: i32, name: &str) -> Self {
+        Self { id, name: name.to_string(), visible: true, parallax: 1.0, y_sort: false }
+    }
+}
+
+// Layer configuration, kept across frames
+#[derive(Clone, Debug, Default)]
+pub struct RenderLayers {
+    pub layers: Vec<RenderLayer>,
+}
+
+#[allow(dead_code)]
+impl RenderLayers {
+    pub fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    pub fn add_layer(&mut self, layer: RenderLayer) {
+        match self.layers.iter_mut().find(|existing| existing.id == layer.id) {
+            Some(existing) => *existing = layer,
+            None => self.layers.push(layer),
+        }
+    }
+
+    pub fn layer(&self, id: i32) -> Option<&RenderLayer> {
+        self.layers.iter().find(|layer| layer.id == id)
+    }
+
+    pub fn layer_by_name(&self, name: &str) -> Option<&RenderLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    pub fn set_visible(&mut self, id: i32, visible: bool) {
+        self.layer_mut(id).visible = visible;
+    }
+
+    pub fn set_parallax(&mut self, id: i32, parallax: f32) {
+        self.layer_mut(id).parallax = parallax;
+    }
+
+    pub fn set_y_sort(&mut self, id: i32, y_sort: bool) {
+        self.layer_mut(id).y_sort = y_sort;
+    }
+
+    // Layers that were never added behave like a visible layer without y-sorting
+    fn layer_mut(&mut self, id: i32) -> &mut RenderLayer {
+        if let Some(index) = self.layers.iter().position(|layer| layer.id == id) {
+            return &mut self.layers[index];
+        }
+        self.layers.push(RenderLayer::new(id, ""));
+        self.layers.last_mut().unwrap()
+    }
+}
+
+// 'q is how long the queued borrows live (one frame), 'a the lifetime of the textures themselves
+pub enum DrawKind<'q, 'a: 'q> {
+    Texture {
+        texture: &'q Texture<'a>,
+        src: Option<sdl2::rect::Rect>,
+        angle: f64,
+        flip_horizontal: bool,
+        flip_vertical: bool,
+    },
+    Animation {
+        animation: &'q AnimatedTexture<'a>,
+        flip_horizontal: bool,
+    },
+    FillRect {
+        color: sdl2::pixels::Color,
+    },
+}
+
+// Source rect, rotation and flips of a queued texture
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TextureDrawOptions {
+    pub src: Option<sdl2::rect::Rect>,
+    pub angle: f64,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+}
+
+pub struct DrawCommand<'q, 'a: 'q> {
+    pub layer: i32,
+    pub z: i32,
+    pub sort_y: i32, // Used by y-sorted layers, defaults to the bottom edge of dest
+    pub dest: Rect,  // World coordinates when flushed with a camera, screen coordinates otherwise
+    pub kind: DrawKind<'q, 'a>,
+}
+
+// Draw commands collected during one frame and drawn together in layer/z order
+#[derive(Default)]
+pub struct RenderQueue<'q, 'a: 'q> {
+    pub commands: Vec<DrawCommand<'q, 'a>>,
+}
+
+#[allow(dead_code)]
+impl<'q, 'a: 'q> RenderQueue<'q, 'a> {
+    pub fn new() -> Self {
+        Self { commands: Vec::new() }
+    }
+
+    pub fn push(&mut self, command: DrawCommand<'q, 'a>) {
+        self.commands.push(command);
+    }
+
+    pub fn draw_texture(&mut self, layer: i32, z: i32, texture_manager: &'q TextureManager<'a>, dest: Rect) -> Result<(), String> {
+        let texture = texture_manager.texture.as_ref().ok_or("Texture not loaded")?;
+        self.push(DrawCommand {
+            layer,
+            z,
+            sort_y: dest.y() + dest.height() as i32,
+            dest,
+            kind: DrawKind::Texture { texture, src: None, angle: 0.0, flip_horizontal: false, flip_vertical: false },
+        });
+        Ok(())
+    }
+
+    pub fn draw_texture_ex(&mut self, layer: i32, z: i32, texture: &'q Texture<'a>, dest: Rect, options: TextureDrawOptions) {
+        let TextureDrawOptions { src, angle, flip_horizontal, flip_vertical } = options;
+        self.push(DrawCommand {
+            layer,
+            z,
+            sort_y: dest.y() + dest.height() as i32,
+            dest,
+            kind: DrawKind::Texture { texture, src, angle, flip_horizontal, flip_vertical },
+        });
+    }
+
+    // Queues the current frame of the current animation, call TextureManagerAnim::update first
+    pub fn draw_animation(&mut self, layer: i32, z: i32, texture_manager_anim: &'q TextureManagerAnim<'a>, dest: Rect, flip_horizontal: bool) -> Result<(), String> {
+        let animation = texture_manager_anim.current().ok_or("No animation set")?;
+        self.push(DrawCommand {
+            layer,
+            z,
+            sort_y: dest.y() + dest.height() as i32,
+            dest,
+            kind: DrawKind::Animation { animation, flip_horizontal },
+        });
+        Ok(())
+    }
+
+    pub fn fill_rect(&mut self, layer: i32, z: i32, dest: Rect, color: sdl2::pixels::Color) {
+        self.push(DrawCommand {
+            layer,
+            z,
+            sort_y: dest.y() + dest.height() as i32,
+            dest,
+            kind: DrawKind::FillRect { color },
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    // Sorts by layer, z and (for y-sorted layers) the bottom edge, then draws and empties the queue.
+    // Commands with equal keys keep the order they were queued in.
+    pub fn flush(&mut self, canvas: &mut Canvas<sdl2::video::Window>, layers: &RenderLayers, camera: Option<&Camera>) -> Result<(), String> {
+        let mut commands: Vec<DrawCommand<'q, 'a>> = self.commands.drain(..).collect();
+        commands.retain(|command| layers.layer(command.layer).is_none_or(|layer| layer.visible));
+        commands.sort_by_key(|command| {
+            let y_sort = layers.layer(command.layer).is_some_and(|layer| layer.y_sort);
+            (command.layer, command.z, if y_sort { command.sort_y } else { 0 })
+        });
+
+        for command in commands {
+            let parallax = layers.layer(command.layer).map_or(1.0, |layer| layer.parallax);
+            let dest = match camera {
+                Some(camera) => {
+                    let world = Self::parallax_rect(&command.dest, camera, parallax);
+                    if !camera.is_visible(&world) {
+                        continue;
+                    }
+                    camera.transform_rect(&world).ok_or("Camera transform failed")?
+                }
+                None => *command.dest.sdl_rect(),
+            };
+
+            match command.kind {
+                DrawKind::Texture { texture, src, angle, flip_horizontal, flip_vertical } => {
+                    canvas.copy_ex(texture, src, Some(dest), angle, None, flip_horizontal, flip_vertical)?;
+                }
+                DrawKind::Animation { animation, flip_horizontal } => {
+                    let flip = if flip_horizontal {
+                        sdl2_sys::SDL_RendererFlip::SDL_FLIP_HORIZONTAL as u32
+                    } else {
+                        sdl2_sys::SDL_RendererFlip::SDL_FLIP_NONE as u32
+                    };
+                    animation.draw(canvas, dest, flip)?;
+                }
+                DrawKind::FillRect { color } => {
+                    canvas.set_draw_color(color);
+                    canvas.fill_rect(dest)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Layers with a parallax factor below 1.0 only follow part of the camera movement
+    fn parallax_rect(rect: &Rect, camera: &Camera, parallax: f32) -> Rect {
+        if parallax == 1.0 {
+            return *rect;
+        }
+        let center = camera.center();
+        Rect::new(
+            rect.x() + (center.x * (1.0 - parallax)) as i32,
+            rect.y() + (center.y * (1.0 - parallax)) as i32,
+            rect.width(),
+            rect.height(),
+        )
+    }
+}
@@ -0,0 +1,174 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::tile::Tile;
+use crate::two_d::collider_merge::EdgeChain;
+
+use nalgebra::Vector2;
+use sdl2::rect::Rect;
+
+use std::collections::HashMap;
+
+// Wall edge that blocks light, in world coordinates
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub a: Vector2<f32>,
+    pub b: Vector2<f32>,
+}
+
+impl Segment {
+    pub fn new(a: Vector2<f32>, b: Vector2<f32>) -> Self {
+        Self { a, b }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Occluders {
+    pub segments: Vec<Segment>,
+}
+
+#[allow(dead_code)]
+impl Occluders {
+    pub fn new() -> Self {
+        Self { segments: Vec::new() }
+    }
+
+    // Outline of a group of colliders. Edges shared by two touching rects are inside a wall
+    // and can never be lit, so they are dropped to keep the light queries cheap.
+    pub fn from_rects(rects: &[Rect]) -> Self {
+        let mut edges: HashMap<(i32, i32, i32, i32), usize> = HashMap::new();
+        for rect in rects {
+            for edge in rect_edges(rect) {
+                *edges.entry(edge).or_insert(0) += 1;
+            }
+        }
+        let mut occluders = Self::new();
+        for rect in rects {
+            for edge in rect_edges(rect) {
+                if edges.get(&edge) == Some(&1) {
+                    occluders.add_segment(Segment::new(
+                        Vector2::new(edge.0 as f32, edge.1 as f32),
+                        Vector2::new(edge.2 as f32, edge.3 as f32),
+                    ));
+                }
+            }
+        }
+        occluders
+    }
+
+    // Maps with solid tile ids use the outlines of the walls, others (e.g. Tiled collision shapes) their colliders
+    pub fn from_tile(tile: &Tile) -> Self {
+        let chains = tile.edge_chains();
+        if chains.is_empty() {
+            return Self::from_rects(&tile.colliders);
+        }
+        Self::from_chains(&chains)
+    }
+
+    pub fn from_chains(chains: &[EdgeChain]) -> Self {
+        let mut occluders = Self::new();
+        for chain in chains {
+            for (a, b) in chain.segments() {
+                occluders.add_segment(Segment::new(Vector2::new(a.0 as f32, a.1 as f32), Vector2::new(b.0 as f32, b.1 as f32)));
+            }
+        }
+        occluders
+    }
+
+    pub fn add_segment(&mut self, segment: Segment) {
+        self.segments.push(segment);
+    }
+
+    // Adds all four edges, e.g. for a game object collider
+    pub fn add_rect(&mut self, rect: &Rect) {
+        for edge in rect_edges(rect) {
+            self.add_segment(Segment::new(
+                Vector2::new(edge.0 as f32, edge.1 as f32),
+                Vector2::new(edge.2 as f32, edge.3 as f32),
+            ));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.segments.clear();
+    }
+}
+
+// Edges with sorted end points so the same edge of two neighbouring rects compares equal
+fn rect_edges(rect: &Rect) -> [(i32, i32, i32, i32); 4] {
+    let (left, top, right, bottom) = (rect.left(), rect.top(), rect.right(), rect.bottom());
+    [
+        (left, top, right, top),
+        (right, top, right, bottom),
+        (left, bottom, right, bottom),
+        (left, top, left, bottom),
+    ]
+}
+
+// Area lit by a light at origin, as a polygon sorted by angle around the origin.
+// Rays are cast at every occluder corner (and slightly to both sides of it) inside the light radius.
+pub fn visibility_polygon(origin: Vector2<f32>, radius: f32, occluders: &Occluders) -> Vec<Vector2<f32>> {
+    let min = origin - Vector2::new(radius, radius);
+    let max = origin + Vector2::new(radius, radius);
+
+    let mut segments: Vec<Segment> = occluders.segments.iter().filter(|segment| {
+        segment.a.x.max(segment.b.x) >= min.x && segment.a.x.min(segment.b.x) <= max.x &&
+        segment.a.y.max(segment.b.y) >= min.y && segment.a.y.min(segment.b.y) <= max.y
+    }).cloned().collect();
+
+    // The square around the light radius closes the polygon where nothing blocks the light
+    let corners = [min, Vector2::new(max.x, min.y), max, Vector2::new(min.x, max.y)];
+    for i in 0..4 {
+        segments.push(Segment::new(corners[i], corners[(i + 1) % 4]));
+    }
+
+    let mut angles = Vec::with_capacity(segments.len() * 6);
+    for segment in &segments {
+        for point in [segment.a, segment.b].iter() {
+            let angle = (point.y - origin.y).atan2(point.x - origin.x);
+            angles.push(angle - 0.0001);
+            angles.push(angle);
+            angles.push(angle + 0.0001);
+        }
+    }
+    angles.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    angles.dedup();
+
+    let mut polygon = Vec::with_capacity(angles.len());
+    for angle in angles {
+        let direction = Vector2::new(angle.cos(), angle.sin());
+        let mut closest: Option<f32> = None;
+        for segment in &segments {
+            if let Some(distance) = ray_segment_distance(origin, direction, segment) {
+                if closest.is_none_or(|closest| distance < closest) {
+                    closest = Some(distance);
+                }
+            }
+        }
+        if let Some(distance) = closest {
+            polygon.push(origin + direction * distance);
+        }
+    }
+    polygon
+}
+
+// Distance along the ray to the segment, None if the ray misses it
+fn ray_segment_distance(origin: Vector2<f32>, direction: Vector2<f32>, segment: &Segment) -> Option<f32> {
+    let edge = segment.b - segment.a;
+    let denominator = cross(direction, edge);
+    if denominator.abs() < 1e-9 {
+        return None;
+    }
+    let to_start = segment.a - origin;
+    let t = cross(to_start, edge) / denominator;
+    let u = cross(to_start, direction) / denominator;
+    if t >= 0.0 && (0.0..=1.0).contains(&u) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+fn cross(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
@@ -0,0 +1,284 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use sdl2::rect::Rect;
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+// Sprite atlas metadata exported by TexturePacker (JSON hash / JSON array) and Aseprite (JSON)
+#[derive(Clone, Debug)]
+pub struct SpriteFrame {
+    pub name: String,
+    pub rect: Rect,         // Region inside the sheet (as packed, rotated frames are stored sideways)
+    pub rotated: bool,      // TexturePacker stores rotated frames turned 90 degrees clockwise
+    pub trimmed: bool,
+    pub trim_rect: Rect,    // Position and size of the packed pixels inside the original sprite
+    pub source_size: (u32, u32), // Size of the original, untrimmed sprite
+    pub pivot: (f32, f32),  // Normalized pivot, (0.5, 0.5) is the center
+    pub duration: u32,      // Milliseconds, 0 means "use the animation frame delay"
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TagDirection {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+#[derive(Clone, Debug)]
+pub struct FrameTag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    pub direction: TagDirection,
+}
+
+#[allow(dead_code)]
+impl FrameTag {
+    // Frame indices in playback order for one loop of the animation
+    pub fn frame_indices(&self) -> Vec<usize> {
+        let forward: Vec<usize> = (self.from..=self.to).collect();
+        let reverse: Vec<usize> = forward.iter().rev().cloned().collect();
+        match self.direction {
+            TagDirection::Forward => forward,
+            TagDirection::Reverse => reverse,
+            TagDirection::PingPong => {
+                let mut frames = forward.clone();
+                if reverse.len() > 2 {
+                    frames.extend_from_slice(&reverse[1..reverse.len() - 1]);
+                }
+                frames
+            }
+            TagDirection::PingPongReverse => {
+                let mut frames = reverse.clone();
+                if forward.len() > 2 {
+                    frames.extend_from_slice(&forward[1..forward.len() - 1]);
+                }
+                frames
+            }
+        }
+    }
+}
+
+pub struct SpriteAtlas {
+    pub image: String,
+    pub size: (u32, u32),
+    pub frames: Vec<SpriteFrame>,
+    pub tags: Vec<FrameTag>,
+}
+
+#[derive(Deserialize)]
+struct RawRect {
+    x: i32,
+    y: i32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct RawSize {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct RawPoint {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawFrame {
+    filename: Option<String>,
+    frame: RawRect,
+    #[serde(default)]
+    rotated: bool,
+    #[serde(default)]
+    trimmed: bool,
+    sprite_source_size: Option<RawRect>,
+    source_size: Option<RawSize>,
+    pivot: Option<RawPoint>,
+    duration: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct RawTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMeta {
+    image: Option<String>,
+    size: Option<RawSize>,
+    #[serde(default)]
+    frame_tags: Vec<RawTag>,
+}
+
+// "frames" is an object in the hash format and an array in the array format.
+// The object keys are kept in file order, a sorted map would put "walk 10" before "walk 2".
+struct RawFrames(Vec<(String, RawFrame)>);
+
+impl<'de> Deserialize<'de> for RawFrames {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FramesVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for FramesVisitor {
+            type Value = RawFrames;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a frame hash or a frame array")
+            }
+
+            fn visit_map<V>(self, mut map: V) -> Result<RawFrames, V::Error>
+            where
+                V: serde::de::MapAccess<'de>,
+            {
+                let mut frames = Vec::new();
+                while let Some((name, frame)) = map.next_entry::<String, RawFrame>()? {
+                    frames.push((name, frame));
+                }
+                Ok(RawFrames(frames))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<RawFrames, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut frames = Vec::new();
+                while let Some(frame) = seq.next_element::<RawFrame>()? {
+                    let name = frame.filename.clone().unwrap_or_else(|| frames.len().to_string());
+                    frames.push((name, frame));
+                }
+                Ok(RawFrames(frames))
+            }
+        }
+
+        deserializer.deserialize_any(FramesVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+struct RawAtlas {
+    frames: RawFrames,
+    meta: Option<RawMeta>,
+}
+
+#[allow(dead_code)]
+impl SpriteAtlas {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let raw: RawAtlas = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let frames = raw.frames.0.into_iter().map(|(name, frame)| {
+            let rect = if frame.rotated {
+                Rect::new(frame.frame.x, frame.frame.y, frame.frame.h, frame.frame.w)
+            } else {
+                Rect::new(frame.frame.x, frame.frame.y, frame.frame.w, frame.frame.h)
+            };
+            let trim_rect = match frame.sprite_source_size {
+                Some(r) => Rect::new(r.x, r.y, r.w, r.h),
+                None => Rect::new(0, 0, frame.frame.w, frame.frame.h),
+            };
+            let source_size = match frame.source_size {
+                Some(s) => (s.w, s.h),
+                None => (frame.frame.w, frame.frame.h),
+            };
+            SpriteFrame {
+                name,
+                rect,
+                rotated: frame.rotated,
+                trimmed: frame.trimmed,
+                trim_rect,
+                source_size,
+                pivot: frame.pivot.map(|p| (p.x, p.y)).unwrap_or((0.5, 0.5)),
+                duration: frame.duration.unwrap_or(0),
+            }
+        }).collect::<Vec<_>>();
+
+        let (image, size, raw_tags) = match raw.meta {
+            Some(meta) => (meta.image.unwrap_or_default(), meta.size.map(|s| (s.w, s.h)).unwrap_or((0, 0)), meta.frame_tags),
+            None => (String::new(), (0, 0), Vec::new()),
+        };
+
+        let mut tags = Vec::new();
+        for tag in raw_tags {
+            if tag.from > tag.to || tag.to >= frames.len() {
+                return Err(format!("Frame tag '{}' is out of range", tag.name));
+            }
+            let direction = match tag.direction.as_str() {
+                "reverse" => TagDirection::Reverse,
+                "pingpong" => TagDirection::PingPong,
+                "pingpong_reverse" => TagDirection::PingPongReverse,
+                _ => TagDirection::Forward,
+            };
+            tags.push(FrameTag { name: tag.name, from: tag.from, to: tag.to, direction });
+        }
+
+        Ok(Self { image, size, frames, tags })
+    }
+
+    // The sheet image is stored relative to the metadata file
+    pub fn image_path(&self, metadata_path: &Path) -> PathBuf {
+        match metadata_path.parent() {
+            Some(dir) => dir.join(&self.image),
+            None => PathBuf::from(&self.image),
+        }
+    }
+
+    pub fn frame(&self, name: &str) -> Option<&SpriteFrame> {
+        self.frames.iter().find(|frame| frame.name == name)
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&FrameTag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    // Aseprite tags when present. TexturePacker has no tags, so runs of frames sharing
+    // a name prefix ("walk_01.png", "walk_02.png") are grouped into one animation instead.
+    pub fn animations(&self) -> Vec<FrameTag> {
+        if !self.tags.is_empty() {
+            return self.tags.clone();
+        }
+
+        let mut animations: Vec<FrameTag> = Vec::new();
+        for (index, frame) in self.frames.iter().enumerate() {
+            let name = animation_name(&frame.name);
+            match animations.last_mut() {
+                Some(last) if last.name == name && last.to + 1 == index => last.to = index,
+                _ => animations.push(FrameTag { name, from: index, to: index, direction: TagDirection::Forward }),
+            }
+        }
+        animations
+    }
+}
+
+// "walk_01.png" -> "walk", "hero run 3.aseprite" -> "hero run"
+fn animation_name(frame_name: &str) -> String {
+    let stem = match frame_name.rfind('.') {
+        Some(dot) if dot > 0 => &frame_name[..dot],
+        _ => frame_name,
+    };
+    let name = stem.trim_end_matches(|c: char| c.is_ascii_digit())
+        .trim_end_matches(['_', '-', ' ', '.']);
+    if name.is_empty() {
+        "default".to_string()
+    } else {
+        name.to_string()
+    }
+}
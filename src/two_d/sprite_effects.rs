@@ -0,0 +1,267 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use sdl2::image::LoadSurface;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, Canvas, RenderTarget, Texture, TextureCreator};
+use sdl2::surface::Surface;
+use sdl2::video::WindowContext;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+// How a sprite is drawn: transform, color and effects. The default draws the sprite as is.
+#[derive(Clone, Copy, Debug)]
+pub struct SpriteDrawParams {
+    pub rotation: f64,             // Degrees clockwise
+    pub pivot: Option<(f32, f32)>, // Rotation and scale origin, 0.0..1.0 of the sprite; None is the center
+    pub scale: (f32, f32),         // Negative values mirror the sprite
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    pub tint: Color,
+    pub alpha: u8,
+    pub flash: f32,                 // 0.0..1.0 of white drawn over the sprite, e.g. when hit
+    pub silhouette: Option<Color>,  // Draws the sprite shape in one color instead of its pixels
+    pub outline: Option<Color>,     // 1px outline around the opaque pixels
+}
+
+impl Default for SpriteDrawParams {
+    fn default() -> Self {
+        Self {
+            rotation: 0.0,
+            pivot: None,
+            scale: (1.0, 1.0),
+            flip_horizontal: false,
+            flip_vertical: false,
+            tint: Color::RGB(255, 255, 255),
+            alpha: 255,
+            flash: 0.0,
+            silhouette: None,
+            outline: None,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl SpriteDrawParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_rotation(&mut self, rotation: f64) {
+        self.rotation = rotation;
+    }
+
+    pub fn set_pivot(&mut self, x: f32, y: f32) {
+        self.pivot = Some((x, y));
+    }
+
+    pub fn set_scale(&mut self, x: f32, y: f32) {
+        self.scale = (x, y);
+    }
+
+    pub fn set_flip(&mut self, horizontal: bool, vertical: bool) {
+        self.flip_horizontal = horizontal;
+        self.flip_vertical = vertical;
+    }
+
+    pub fn set_tint(&mut self, tint: Color) {
+        self.tint = tint;
+    }
+
+    pub fn set_alpha(&mut self, alpha: u8) {
+        self.alpha = alpha;
+    }
+
+    pub fn set_flash(&mut self, flash: f32) {
+        self.flash = flash.clamp(0.0, 1.0);
+    }
+
+    pub fn set_silhouette(&mut self, silhouette: Option<Color>) {
+        self.silhouette = silhouette;
+    }
+
+    pub fn set_outline(&mut self, outline: Option<Color>) {
+        self.outline = outline;
+    }
+
+    // Flash, silhouette and outline need textures generated from the sprite pixels
+    pub fn uses_effects(&self) -> bool {
+        self.flash > 0.0 || self.silhouette.is_some() || self.outline.is_some()
+    }
+
+    // Scales dest around the pivot, the result is where the sprite ends up before rotating
+    pub fn transform_dest(&self, dest: Rect) -> Rect {
+        let (pivot_x, pivot_y) = self.pivot.unwrap_or((0.5, 0.5));
+        let width = (dest.width() as f32 * self.scale.0.abs()).round().max(1.0);
+        let height = (dest.height() as f32 * self.scale.1.abs()).round().max(1.0);
+        let anchor_x = dest.x() as f32 + dest.width() as f32 * pivot_x;
+        let anchor_y = dest.y() as f32 + dest.height() as f32 * pivot_y;
+        Rect::new(
+            (anchor_x - width * pivot_x).round() as i32,
+            (anchor_y - height * pivot_y).round() as i32,
+            width as u32,
+            height as u32,
+        )
+    }
+}
+
+// White masks and outlines of sprite frames, generated from the image file the first time
+// a frame is drawn with an effect and reused afterwards
+pub struct SpriteEffectCache<'a> {
+    texture_creator: &'a TextureCreator<WindowContext>,
+    source: Surface<'static>,
+    masks: HashMap<(i32, i32, u32, u32), Texture<'a>>,
+    outlines: HashMap<(i32, i32, u32, u32), Texture<'a>>,
+}
+
+#[allow(dead_code)]
+impl<'a> SpriteEffectCache<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>, path: &Path) -> Result<Self, String> {
+        let source = Surface::from_file(path)?.convert_format(PixelFormatEnum::RGBA32)?;
+        Ok(Self { texture_creator, source, masks: HashMap::new(), outlines: HashMap::new() })
+    }
+
+    pub fn clear(&mut self) {
+        self.masks.clear();
+        self.outlines.clear();
+    }
+
+    fn alpha_of(&self, src: Rect) -> Vec<u8> {
+        let (width, height) = (src.width() as usize, src.height() as usize);
+        let mut alpha = vec![0u8; width * height];
+        let pitch = self.source.pitch() as usize;
+        let (surface_width, surface_height) = (self.source.width() as i32, self.source.height() as i32);
+        self.source.with_lock(|pixels| {
+            for y in 0..height {
+                for x in 0..width {
+                    let (sx, sy) = (src.x() + x as i32, src.y() + y as i32);
+                    if sx >= 0 && sy >= 0 && sx < surface_width && sy < surface_height {
+                        alpha[y * width + x] = pixels[sy as usize * pitch + sx as usize * 4 + 3];
+                    }
+                }
+            }
+        });
+        alpha
+    }
+
+    fn white_texture(&self, width: u32, height: u32, alpha: &[u8]) -> Result<Texture<'a>, String> {
+        let mut pixels = Vec::with_capacity(alpha.len() * 4);
+        for &a in alpha {
+            pixels.extend_from_slice(&[255, 255, 255, a]);
+        }
+        let mut texture = self.texture_creator
+            .create_texture_static(PixelFormatEnum::RGBA32, width, height)
+            .map_err(|e| e.to_string())?;
+        texture.update(None, &pixels, width as usize * 4).map_err(|e| e.to_string())?;
+        texture.set_blend_mode(BlendMode::Blend);
+        Ok(texture)
+    }
+
+    // The frame in white with its own alpha, tinted with color mod for flash and silhouette
+    pub fn mask(&mut self, src: Rect) -> Result<&mut Texture<'a>, String> {
+        let key = (src.x(), src.y(), src.width(), src.height());
+        if !self.masks.contains_key(&key) {
+            let alpha = self.alpha_of(src);
+            let texture = self.white_texture(src.width(), src.height(), &alpha)?;
+            self.masks.insert(key, texture);
+        }
+        Ok(self.masks.get_mut(&key).unwrap())
+    }
+
+    // Transparent pixels next to opaque ones, one pixel larger than the frame on every side
+    pub fn outline(&mut self, src: Rect) -> Result<&mut Texture<'a>, String> {
+        let key = (src.x(), src.y(), src.width(), src.height());
+        if !self.outlines.contains_key(&key) {
+            let alpha = self.alpha_of(src);
+            let (width, height) = (src.width() as i32, src.height() as i32);
+            let opaque = |x: i32, y: i32| x >= 0 && y >= 0 && x < width && y < height && alpha[(y * width + x) as usize] > 127;
+            let (outline_width, outline_height) = (width + 2, height + 2);
+            let mut outline = vec![0u8; (outline_width * outline_height) as usize];
+            for y in 0..outline_height {
+                for x in 0..outline_width {
+                    let (fx, fy) = (x - 1, y - 1);
+                    if !opaque(fx, fy) && (opaque(fx - 1, fy) || opaque(fx + 1, fy) || opaque(fx, fy - 1) || opaque(fx, fy + 1)) {
+                        outline[(y * outline_width + x) as usize] = 255;
+                    }
+                }
+            }
+            let texture = self.white_texture(outline_width as u32, outline_height as u32, &outline)?;
+            self.outlines.insert(key, texture);
+        }
+        Ok(self.outlines.get_mut(&key).unwrap())
+    }
+}
+
+// Color and alpha mod of a texture that may be shared between sprite sheets (an atlas).
+// SDL keeps the mods on the texture itself, so they are set right before a copy and reset after.
+fn set_texture_mods(texture: &Texture, color: Color, alpha: u8) {
+    unsafe {
+        sdl2_sys::SDL_SetTextureColorMod(texture.raw(), color.r, color.g, color.b);
+        sdl2_sys::SDL_SetTextureAlphaMod(texture.raw(), alpha);
+    }
+}
+
+// Draws src of texture into dest with params. rotated is for atlas frames packed sideways.
+// Without an effect cache flash, silhouette and outline are skipped.
+pub fn draw_sprite<'a, T: RenderTarget>(
+    canvas: &mut Canvas<T>,
+    texture: &Texture<'a>,
+    src: Rect,
+    dest: Rect,
+    params: &SpriteDrawParams,
+    rotated: bool,
+    effects: Option<&mut SpriteEffectCache<'a>>,
+) -> Result<(), String> {
+    let mut flip_horizontal = params.flip_horizontal != (params.scale.0 < 0.0);
+    let mut flip_vertical = params.flip_vertical != (params.scale.1 < 0.0);
+    let (pivot_x, pivot_y) = params.pivot.unwrap_or((0.5, 0.5));
+    let mut angle = params.rotation;
+    let mut center = Point::new((dest.width() as f32 * pivot_x).round() as i32, (dest.height() as f32 * pivot_y).round() as i32);
+    let mut dest = dest;
+    if rotated {
+        // Packed sideways: draw with swapped sides around the same center and turn it back.
+        // These frames always rotate around their center.
+        dest = Rect::from_center(dest.center(), dest.height(), dest.width());
+        center = Point::new(dest.width() as i32 / 2, dest.height() as i32 / 2);
+        angle -= 90.0;
+        std::mem::swap(&mut flip_horizontal, &mut flip_vertical);
+    }
+
+    let mut effects = effects;
+    if let (Some(color), Some(cache)) = (params.outline, effects.as_deref_mut()) {
+        // One source pixel of border on every side, at the scale the sprite is drawn with
+        let pixel_x = (dest.width() / src.width().max(1)).max(1);
+        let pixel_y = (dest.height() / src.height().max(1)).max(1);
+        let outline_dest = Rect::new(dest.x() - pixel_x as i32, dest.y() - pixel_y as i32, dest.width() + pixel_x * 2, dest.height() + pixel_y * 2);
+        let outline_center = Point::new(center.x() + pixel_x as i32, center.y() + pixel_y as i32);
+        let outline = cache.outline(src)?;
+        outline.set_color_mod(color.r, color.g, color.b);
+        outline.set_alpha_mod(((color.a as u32 * params.alpha as u32) / 255) as u8);
+        canvas.copy_ex(outline, None, Some(outline_dest), angle, Some(outline_center), flip_horizontal, flip_vertical)?;
+    }
+
+    match (params.silhouette, effects.as_deref_mut()) {
+        (Some(color), Some(cache)) => {
+            let mask = cache.mask(src)?;
+            mask.set_color_mod(color.r, color.g, color.b);
+            mask.set_alpha_mod(((color.a as u32 * params.alpha as u32) / 255) as u8);
+            canvas.copy_ex(mask, None, Some(dest), angle, Some(center), flip_horizontal, flip_vertical)?;
+        }
+        _ => {
+            set_texture_mods(texture, params.tint, params.alpha);
+            let result = canvas.copy_ex(texture, Some(src), Some(dest), angle, Some(center), flip_horizontal, flip_vertical);
+            set_texture_mods(texture, Color::RGB(255, 255, 255), 255);
+            result?;
+        }
+    }
+
+    if let (true, Some(cache)) = (params.flash > 0.0, effects) {
+        let mask = cache.mask(src)?;
+        mask.set_color_mod(255, 255, 255);
+        mask.set_alpha_mod((params.flash.clamp(0.0, 1.0) * params.alpha as f32) as u8);
+        canvas.copy_ex(mask, None, Some(dest), angle, Some(center), flip_horizontal, flip_vertical)?;
+    }
+    Ok(())
+}
@@ -1,29 +1,98 @@
 extern crate sdl2;
 // extern  crate gl;
 
+use crate::two_d::sprite_atlas::SpriteFrame;
+
 use sdl2::render::Texture;
 use sdl2::rect::Rect;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+// One texture used by several sprite sheets, e.g. every tag of an atlas
+pub type SharedTexture<'a> = Rc<Texture<'a>>;
 
 pub struct SpriteSheet<'a> {
-    pub texture: Texture<'a>,
+    pub texture: SharedTexture<'a>,
     pub frame_width: u32,
     pub frame_height: u32,
     pub row: u32,
+    pub frames: Vec<SpriteFrame>, // Packed frames from atlas metadata, empty for uniform grids
+    pub source_path: Option<PathBuf>, // Image file, needed to generate flash and outline textures
 }
 
+#[allow(dead_code)]
 impl<'a> SpriteSheet<'a> {
     pub fn new(texture: Texture<'a>, frame_width: u32, frame_height: u32, row: u32) -> Self {
         Self {
-            texture,
+            texture: Rc::new(texture),
             frame_width,
             frame_height,
             row,
+            frames: Vec::new(),
+            source_path: None,
+        }
+    }
+
+    pub fn from_frames(texture: SharedTexture<'a>, frames: Vec<SpriteFrame>) -> Self {
+        // The untrimmed size is what callers use to size the destination rect
+        let frame_width = frames.iter().map(|frame| frame.source_size.0).max().unwrap_or(0);
+        let frame_height = frames.iter().map(|frame| frame.source_size.1).max().unwrap_or(0);
+        Self {
+            texture,
+            frame_width,
+            frame_height,
+            row: 0,
+            frames,
+            source_path: None,
+        }
+    }
+
+    pub fn set_source_path(&mut self, path: &Path) {
+        self.source_path = Some(path.to_path_buf());
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        if self.frames.is_empty() {
+            self.texture.query().width / self.frame_width.max(1)
+        } else {
+            self.frames.len() as u32
         }
     }
 
     pub fn get_frame(&self, index: u32) -> Rect {
+        if let Some(frame) = self.frames.get(index as usize) {
+            return frame.rect;
+        }
         let x = (index * self.frame_width) as i32;
         let y = (self.row * self.frame_height) as i32;
         Rect::new(x, y, self.frame_width, self.frame_height)
     }
+
+    pub fn frame_duration(&self, index: u32) -> Option<u32> {
+        self.frames.get(index as usize)
+            .map(|frame| frame.duration)
+            .filter(|&duration| duration > 0)
+    }
+
+    // Maps a destination rect for the whole (untrimmed) sprite to the rect the packed pixels
+    // should be drawn into, so trimmed frames keep their offset inside the sprite.
+    pub fn frame_dest(&self, index: u32, dest: Rect) -> Rect {
+        match self.frames.get(index as usize) {
+            Some(frame) if frame.source_size.0 > 0 && frame.source_size.1 > 0 => {
+                let scale_x = dest.width() as f32 / frame.source_size.0 as f32;
+                let scale_y = dest.height() as f32 / frame.source_size.1 as f32;
+                Rect::new(
+                    dest.x() + (frame.trim_rect.x() as f32 * scale_x) as i32,
+                    dest.y() + (frame.trim_rect.y() as f32 * scale_y) as i32,
+                    ((frame.trim_rect.width() as f32 * scale_x) as u32).max(1),
+                    ((frame.trim_rect.height() as f32 * scale_y) as u32).max(1),
+                )
+            }
+            _ => dest,
+        }
+    }
+
+    pub fn is_rotated(&self, index: u32) -> bool {
+        self.frames.get(index as usize).is_some_and(|frame| frame.rotated)
+    }
 }
\ No newline at end of file
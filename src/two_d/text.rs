@@ -0,0 +1,444 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, RenderTarget, Texture, TextureCreator};
+use sdl2::video::WindowContext;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const ATLAS_PAGE_SIZE: u32 = 512;
+
+// Where a character is stored in a font texture and how it is placed on a line
+#[derive(Clone, Copy, Debug)]
+pub struct Glyph {
+    pub page: usize,
+    pub src: Option<Rect>, // None for characters without pixels, like spaces in bitmap fonts
+    pub offset: (i32, i32), // From the pen position at the top of the line
+    pub advance: i32,
+}
+
+// Anything text can be drawn from: TTF fonts through a GlyphAtlas, bitmap fonts, ...
+// Glyph textures are white so the text color can be applied with color modulation.
+pub trait GlyphSource<'a> {
+    fn glyph(&mut self, ch: char) -> Result<Option<Glyph>, String>;
+    fn page_mut(&mut self, page: usize) -> Option<&mut Texture<'a>>;
+    fn line_height(&self) -> i32;
+    fn kerning(&self, _left: char, _right: char) -> i32 {
+        0
+    }
+}
+
+pub type SharedGlyphSource<'a> = Rc<RefCell<dyn GlyphSource<'a> + 'a>>;
+
+// Glyphs of one TTF font at one size, rendered once and packed into textures on first use
+pub struct GlyphAtlas<'a> {
+    pub font: std::sync::Arc<sdl2::ttf::Font<'a, 'static>>,
+    texture_creator: &'a TextureCreator<WindowContext>,
+    pages: Vec<Texture<'a>>,
+    glyphs: HashMap<char, Option<Glyph>>,
+    cursor: (i32, i32),
+    row_height: i32,
+}
+
+#[allow(dead_code)]
+impl<'a> GlyphAtlas<'a> {
+    pub fn new(font: std::sync::Arc<sdl2::ttf::Font<'a, 'static>>, texture_creator: &'a TextureCreator<WindowContext>) -> Self {
+        Self {
+            font,
+            texture_creator,
+            pages: Vec::new(),
+            glyphs: HashMap::new(),
+            cursor: (0, 0),
+            row_height: 0,
+        }
+    }
+
+    pub fn shared(font: std::sync::Arc<sdl2::ttf::Font<'a, 'static>>, texture_creator: &'a TextureCreator<WindowContext>) -> SharedGlyphSource<'a> {
+        Rc::new(RefCell::new(Self::new(font, texture_creator)))
+    }
+
+    // Renders the characters up front, e.g. the alphabet of a dialogue, to avoid hitches later
+    pub fn preload(&mut self, characters: &str) -> Result<(), String> {
+        for ch in characters.chars() {
+            self.glyph(ch)?;
+        }
+        Ok(())
+    }
+
+    fn new_page(&mut self) -> Result<(), String> {
+        let mut page = self.texture_creator
+            .create_texture_static(PixelFormatEnum::ARGB8888, ATLAS_PAGE_SIZE, ATLAS_PAGE_SIZE)
+            .map_err(|e| e.to_string())?;
+        let empty = vec![0u8; (ATLAS_PAGE_SIZE * ATLAS_PAGE_SIZE * 4) as usize];
+        page.update(None, &empty, (ATLAS_PAGE_SIZE * 4) as usize).map_err(|e| e.to_string())?;
+        page.set_blend_mode(BlendMode::Blend);
+        self.pages.push(page);
+        self.cursor = (0, 0);
+        self.row_height = 0;
+        Ok(())
+    }
+
+    fn rasterize(&mut self, ch: char) -> Result<Option<Glyph>, String> {
+        if self.font.find_glyph(ch).is_none() {
+            return Ok(None);
+        }
+        let advance = self.font.find_glyph_metrics(ch).map_or(0, |metrics| metrics.advance);
+        let surface = match self.font.render_char(ch).blended(Color::RGBA(255, 255, 255, 255)) {
+            Ok(surface) => surface,
+            Err(_) => return Ok(None), // Zero width characters cannot be rendered on their own
+        };
+        let surface = surface.convert_format(PixelFormatEnum::ARGB8888)?;
+        let (width, height) = (surface.width(), surface.height());
+        if width == 0 || height == 0 {
+            return Ok(Some(Glyph { page: 0, src: None, offset: (0, 0), advance }));
+        }
+        if width > ATLAS_PAGE_SIZE || height > ATLAS_PAGE_SIZE {
+            return Err(format!("Glyph '{}' does not fit into a font atlas page", ch));
+        }
+
+        // Shelf packing: fill rows left to right, start a new page when the rows run out
+        if self.pages.is_empty() {
+            self.new_page()?;
+        }
+        if self.cursor.0 + width as i32 > ATLAS_PAGE_SIZE as i32 {
+            self.cursor = (0, self.cursor.1 + self.row_height + 1);
+            self.row_height = 0;
+        }
+        if self.cursor.1 + height as i32 > ATLAS_PAGE_SIZE as i32 {
+            self.new_page()?;
+        }
+        let src = Rect::new(self.cursor.0, self.cursor.1, width, height);
+        let page_index = self.pages.len() - 1;
+        let pitch = surface.pitch() as usize;
+        let page = &mut self.pages[page_index];
+        surface.with_lock(|pixels| page.update(src, pixels, pitch)).map_err(|e| e.to_string())?;
+
+        self.cursor.0 += width as i32 + 1;
+        self.row_height = self.row_height.max(height as i32);
+        Ok(Some(Glyph { page: page_index, src: Some(src), offset: (0, 0), advance }))
+    }
+}
+
+impl<'a> GlyphSource<'a> for GlyphAtlas<'a> {
+    fn glyph(&mut self, ch: char) -> Result<Option<Glyph>, String> {
+        if let Some(glyph) = self.glyphs.get(&ch) {
+            return Ok(*glyph);
+        }
+        let glyph = self.rasterize(ch)?;
+        self.glyphs.insert(ch, glyph);
+        Ok(glyph)
+    }
+
+    fn page_mut(&mut self, page: usize) -> Option<&mut Texture<'a>> {
+        self.pages.get_mut(page)
+    }
+
+    fn line_height(&self) -> i32 {
+        self.font.recommended_line_spacing().max(self.font.height())
+    }
+}
+
+// One atlas per font file and point size, shared by everything drawing with it
+pub struct FontCache<'a> {
+    ttf_context: &'a sdl2::ttf::Sdl2TtfContext,
+    texture_creator: &'a TextureCreator<WindowContext>,
+    atlases: HashMap<(PathBuf, u16), SharedGlyphSource<'a>>,
+}
+
+#[allow(dead_code)]
+impl<'a> FontCache<'a> {
+    pub fn new(ttf_context: &'a sdl2::ttf::Sdl2TtfContext, texture_creator: &'a TextureCreator<WindowContext>) -> Self {
+        Self { ttf_context, texture_creator, atlases: HashMap::new() }
+    }
+
+    pub fn get(&mut self, path: &Path, size: u16) -> Result<SharedGlyphSource<'a>, String> {
+        let key = (path.to_path_buf(), size);
+        if let Some(atlas) = self.atlases.get(&key) {
+            return Ok(atlas.clone());
+        }
+        let font = self.ttf_context.load_font(path, size)?;
+        // Arc like TextBox::font, the atlas never leaves the render thread
+        #[allow(clippy::arc_with_non_send_sync)]
+        let atlas = GlyphAtlas::shared(std::sync::Arc::new(font), self.texture_creator);
+        self.atlases.insert(key, atlas.clone());
+        Ok(atlas)
+    }
+
+    pub fn clear(&mut self) {
+        self.atlases.clear();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TextOptions {
+    pub max_width: Option<u32>, // Wrap words at this width
+    pub align: TextAlign,
+    pub line_spacing: f32, // Multiplier of the font line height
+    pub markup: bool,      // Parse [color=#f00], [b] and [wave] tags
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self { max_width: None, align: TextAlign::Left, line_spacing: 1.0, markup: true }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextStyle {
+    pub color: Option<Color>, // None uses the color passed to draw_text
+    pub bold: bool,
+    pub wave: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PlacedGlyph {
+    pub glyph: Glyph,
+    pub x: i32,
+    pub y: i32,
+    pub style: TextStyle,
+    pub index: usize, // Position in the visible text, used to phase the wave effect
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TextLayout {
+    pub glyphs: Vec<PlacedGlyph>,
+    pub width: u32,
+    pub height: u32,
+    pub lines: usize,
+}
+
+// Splits markup into characters with their style. Unknown or malformed tags stay as text, "[[" is a literal "[".
+pub fn parse_markup(text: &str) -> Vec<(char, TextStyle)> {
+    let mut result = Vec::with_capacity(text.len());
+    let mut colors: Vec<Color> = Vec::new();
+    let (mut bold, mut wave) = (0, 0);
+    let mut rest = text;
+
+    while let Some(ch) = rest.chars().next() {
+        if ch == '[' {
+            if rest.starts_with("[[") {
+                result.push(('[', style_of(&colors, bold, wave)));
+                rest = &rest[2..];
+                continue;
+            }
+            if let Some(end) = rest.find(']') {
+                let tag = &rest[1..end];
+                let known = match tag {
+                    "b" => { bold += 1; true }
+                    "/b" => { bold = (bold - 1).max(0); true }
+                    "wave" => { wave += 1; true }
+                    "/wave" => { wave = (wave - 1).max(0); true }
+                    "/color" => { colors.pop(); true }
+                    _ => match tag.strip_prefix("color=").and_then(parse_hex_color) {
+                        Some(color) => { colors.push(color); true }
+                        None => false,
+                    },
+                };
+                if known {
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+        }
+        result.push((ch, style_of(&colors, bold, wave)));
+        rest = &rest[ch.len_utf8()..];
+    }
+    result
+}
+
+fn style_of(colors: &[Color], bold: i32, wave: i32) -> TextStyle {
+    TextStyle { color: colors.last().copied(), bold: bold > 0, wave: wave > 0 }
+}
+
+// "#f00", "#ff0000" or "#ff000080"
+pub fn parse_hex_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+    let digits: Vec<u8> = hex.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<Vec<u8>>>()?;
+    match digits.len() {
+        3 => Some(Color::RGB(digits[0] * 17, digits[1] * 17, digits[2] * 17)),
+        6 => Some(Color::RGB(digits[0] * 16 + digits[1], digits[2] * 16 + digits[3], digits[4] * 16 + digits[5])),
+        8 => Some(Color::RGBA(digits[0] * 16 + digits[1], digits[2] * 16 + digits[3], digits[4] * 16 + digits[5], digits[6] * 16 + digits[7])),
+        _ => None,
+    }
+}
+
+// Places every glyph without drawing, e.g. to size a dialogue box before showing it
+pub fn layout_text<'a>(source: &mut dyn GlyphSource<'a>, text: &str, options: &TextOptions) -> Result<TextLayout, String> {
+    let characters = if options.markup {
+        parse_markup(text)
+    } else {
+        text.chars().map(|ch| (ch, TextStyle::default())).collect()
+    };
+    let line_height = source.line_height();
+    let line_advance = (line_height as f32 * options.line_spacing).round() as i32;
+    let max_width = options.max_width.map(|width| width as i32);
+
+    // Lines of (character index, x) pairs, words are wrapped as a whole when they pass max_width
+    let mut glyphs: Vec<Option<Glyph>> = Vec::with_capacity(characters.len());
+    for (ch, _) in &characters {
+        glyphs.push(if *ch == '\n' { None } else { source.glyph(*ch)? });
+    }
+    let mut lines: Vec<Vec<(usize, i32)>> = vec![Vec::new()];
+    let mut x = 0;
+    let mut previous: Option<char> = None;
+    let mut wrapped = false;
+
+    let mut index = 0;
+    while index < characters.len() {
+        let ch = characters[index].0;
+        if ch == '\n' {
+            lines.push(Vec::new());
+            x = 0;
+            previous = None;
+            wrapped = false;
+            index += 1;
+            continue;
+        }
+        if ch.is_whitespace() {
+            // Spaces at the start of a wrapped line are dropped
+            if !(wrapped && lines.last().unwrap().is_empty()) {
+                lines.last_mut().unwrap().push((index, x));
+                x += glyphs[index].map_or(0, |glyph| glyph.advance);
+            }
+            previous = Some(ch);
+            index += 1;
+            continue;
+        }
+
+        // Measure the whole word first
+        let word_end = (index..characters.len())
+            .find(|&i| characters[i].0.is_whitespace())
+            .unwrap_or(characters.len());
+        let mut word_width = 0;
+        let mut word_previous = previous;
+        for i in index..word_end {
+            let ch = characters[i].0;
+            word_width += word_previous.map_or(0, |p| source.kerning(p, ch)) + glyphs[i].map_or(0, |glyph| glyph.advance);
+            word_previous = Some(ch);
+        }
+        for i in index..word_end {
+            let ch = characters[i].0;
+            let advance = glyphs[i].map_or(0, |glyph| glyph.advance);
+            if let Some(max_width) = max_width {
+                // Words longer than a whole line are broken between characters
+                let overflow = if i == index { x + word_width > max_width } else { x + advance > max_width };
+                if x > 0 && overflow {
+                    lines.push(Vec::new());
+                    x = 0;
+                    previous = None;
+                    wrapped = true;
+                }
+            }
+            x += previous.map_or(0, |p| source.kerning(p, ch));
+            lines.last_mut().unwrap().push((i, x));
+            x += advance;
+            previous = Some(ch);
+        }
+        index = word_end;
+    }
+    let line_widths: Vec<i32> = lines.iter().map(|line| line_width(line, &characters, &glyphs)).collect();
+
+    let width = line_widths.iter().copied().max().unwrap_or(0).max(0);
+    let box_width = max_width.unwrap_or(width);
+    let mut layout = TextLayout {
+        glyphs: Vec::new(),
+        width: width as u32,
+        height: (line_advance * (lines.len() as i32 - 1) + line_height).max(0) as u32,
+        lines: lines.len(),
+    };
+    for (line_index, line) in lines.iter().enumerate() {
+        let offset = match options.align {
+            TextAlign::Left => 0,
+            TextAlign::Center => (box_width - line_widths[line_index]) / 2,
+            TextAlign::Right => box_width - line_widths[line_index],
+        };
+        for &(index, x) in line {
+            if let Some(glyph) = glyphs[index] {
+                layout.glyphs.push(PlacedGlyph {
+                    glyph,
+                    x: x + offset + glyph.offset.0,
+                    y: line_index as i32 * line_advance + glyph.offset.1,
+                    style: characters[index].1,
+                    index,
+                });
+            }
+        }
+    }
+    Ok(layout)
+}
+
+// Width of a line without its trailing spaces
+fn line_width(line: &[(usize, i32)], characters: &[(char, TextStyle)], glyphs: &[Option<Glyph>]) -> i32 {
+    line.iter().rev()
+        .find(|(index, _)| !characters[*index].0.is_whitespace())
+        .map_or(0, |&(index, x)| x + glyphs[index].map_or(0, |glyph| glyph.advance))
+}
+
+pub fn measure_text<'a>(source: &mut dyn GlyphSource<'a>, text: &str, options: &TextOptions) -> Result<(u32, u32), String> {
+    let layout = layout_text(source, text, options)?;
+    Ok((layout.width, layout.height))
+}
+
+// Draws text with its top left corner at (x, y) and returns the layout that was drawn
+pub fn draw_text<'a, T: RenderTarget>(
+    canvas: &mut Canvas<T>,
+    source: &mut dyn GlyphSource<'a>,
+    text: &str,
+    x: i32,
+    y: i32,
+    color: Color,
+    options: &TextOptions,
+) -> Result<TextLayout, String> {
+    let layout = layout_text(source, text, options)?;
+    draw_layout(canvas, source, &layout, x, y, color)?;
+    Ok(layout)
+}
+
+pub fn draw_layout<'a, T: RenderTarget>(
+    canvas: &mut Canvas<T>,
+    source: &mut dyn GlyphSource<'a>,
+    layout: &TextLayout,
+    x: i32,
+    y: i32,
+    color: Color,
+) -> Result<(), String> {
+    let time = unsafe { sdl2_sys::SDL_GetTicks() } as f32 / 1000.0;
+    let amplitude = (source.line_height() as f32 * 0.12).max(1.0);
+    for placed in &layout.glyphs {
+        let src = match placed.glyph.src {
+            Some(src) => src,
+            None => continue,
+        };
+        let color = placed.style.color.unwrap_or(color);
+        let wave_offset = if placed.style.wave {
+            ((time * 8.0 - placed.index as f32 * 0.6).sin() * amplitude).round() as i32
+        } else {
+            0
+        };
+        let texture = match source.page_mut(placed.glyph.page) {
+            Some(texture) => texture,
+            None => continue,
+        };
+        texture.set_color_mod(color.r, color.g, color.b);
+        texture.set_alpha_mod(color.a);
+        let dest = Rect::new(x + placed.x, y + placed.y + wave_offset, src.width(), src.height());
+        canvas.copy(texture, src, dest)?;
+        // Fake bold for fonts without a bold face: draw once more one pixel to the right
+        if placed.style.bold {
+            canvas.copy(texture, src, Rect::new(dest.x() + 1, dest.y(), dest.width(), dest.height()))?;
+        }
+    }
+    Ok(())
+}
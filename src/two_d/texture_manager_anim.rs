@@ -3,10 +3,13 @@ extern crate sdl2;
 
 use crate::two_d::sprite_sheet::SpriteSheet;
 use crate::two_d::animated_texture::AnimatedTexture;
+use crate::two_d::sprite_atlas::SpriteAtlas;
+use crate::two_d::sprite_effects::{SpriteDrawParams, SpriteEffectCache};
 
 use std::collections::HashMap;
 use sdl2::image::LoadTexture;
 use std::path::Path;
+use std::rc::Rc;
 
 pub struct TextureManagerAnim<'a> {
     pub animations: HashMap<String, AnimatedTexture<'a>>,
@@ -26,7 +29,8 @@ impl<'a> TextureManagerAnim<'a> {
 
     pub fn load_animation(&mut self, tag: &str, path: &Path, frame_width: u32, frame_height: u32, frame_delay: u32, row: u32) -> Result<(), String> {
         let texture = self.texture_creator.load_texture(path)?;
-        let sprite_sheet = SpriteSheet::new(texture, frame_width, frame_height, row);
+        let mut sprite_sheet = SpriteSheet::new(texture, frame_width, frame_height, row);
+        sprite_sheet.set_source_path(path);
         let animated_texture = AnimatedTexture::new(sprite_sheet, frame_delay);
         self.animations.insert(tag.to_string(), animated_texture);
 
@@ -37,12 +41,49 @@ impl<'a> TextureManagerAnim<'a> {
         Ok(())
     }
 
+    // Loads TexturePacker or Aseprite JSON metadata, every tag becomes an animation
+    pub fn load_atlas(&mut self, path: &Path, frame_delay: u32) -> Result<(), String> {
+        let atlas = SpriteAtlas::load(path)?;
+        let image_path = atlas.image_path(path);
+        // Every tag draws from the same image, so it is loaded once and shared
+        let texture = Rc::new(self.texture_creator.load_texture(&image_path)?);
+
+        for tag in atlas.animations() {
+            let frames = tag.frame_indices().into_iter()
+                .filter_map(|index| atlas.frames.get(index).cloned())
+                .collect();
+            let mut sprite_sheet = SpriteSheet::from_frames(Rc::clone(&texture), frames);
+            sprite_sheet.set_source_path(&image_path);
+            let animated_texture = AnimatedTexture::new(sprite_sheet, frame_delay);
+            self.animations.insert(tag.name.clone(), animated_texture);
+
+            if self.current_animation.is_none() {
+                self.current_animation = Some(tag.name);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn set_animation(&mut self, tag: &str) {
         if self.animations.contains_key(tag) {
             self.current_animation = Some(tag.to_string());
         }
     }
 
+    // Advances the current animation without drawing it, e.g. before queueing it in a RenderQueue
+    pub fn update(&mut self) {
+        if let Some(tag) = &self.current_animation {
+            if let Some(texture) = self.animations.get_mut(tag) {
+                texture.update();
+            }
+        }
+    }
+
+    pub fn current(&self) -> Option<&AnimatedTexture<'a>> {
+        self.current_animation.as_ref().and_then(|tag| self.animations.get(tag))
+    }
+
     pub fn render_texture(&mut self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, dest: sdl2::rect::Rect, flip: u32) -> Result<(), String> {
         if let Some(tag) = &self.current_animation {
             if let Some(texture) = self.animations.get_mut(tag) {
@@ -55,4 +96,19 @@ impl<'a> TextureManagerAnim<'a> {
             Err("No animation set".to_owned())
         }
     }
+
+    // Like render_texture with rotation, scale, tint and effects. The textures for flash,
+    // silhouette and outline are generated the first time an animation uses them, from the
+    // sprite sheet's image file; a sheet without one is drawn without the effects.
+    pub fn render_texture_ex(&mut self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, dest: sdl2::rect::Rect, params: &SpriteDrawParams) -> Result<(), String> {
+        let tag = self.current_animation.as_ref().ok_or("No animation set")?;
+        let texture = self.animations.get_mut(tag).ok_or("Texture not loaded for the current animation tag")?;
+        if params.uses_effects() && texture.effects.is_none() {
+            if let Some(path) = &texture.sprite_sheet.source_path {
+                texture.effects = Some(SpriteEffectCache::new(self.texture_creator, path)?);
+            }
+        }
+        texture.update();
+        texture.draw_ex(canvas, dest, params)
+    }
 }
\ No newline at end of file
@@ -2,39 +2,160 @@ extern crate sdl2;
 // extern  crate gl;
 
 use crate::two_d::texture_manager::TextureManager;
+use crate::two_d::camera::Camera;
+use crate::two_d::tiled::TiledMap;
+use crate::two_d::ldtk::LdtkLevel;
+use crate::two_d::tileset::{TileAnimation, TileProperty, TilesetDefinition};
+use crate::two_d::collider_merge::{merge_cells, outline_chains, EdgeChain};
 
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use sdl2::rect::Rect;
 
 pub type TextureGrid<'a> = Vec<Vec<TextureManager<'a>>>;
 
+// Size of the cells, which tile ids block movement, typed properties and animations per tile id
+#[derive(Clone, Debug)]
+pub struct TileConfig {
+    pub tile_size: (u32, u32),
+    pub solid: HashSet<u32>,
+    pub properties: HashMap<u32, HashMap<String, TileProperty>>,
+    pub animations: HashMap<u32, TileAnimation>,
+    pub merge_colliders: bool, // One rect per solid area instead of one per cell
+    pub empty_tile: Option<u32>, // Cells holding this id draw nothing, 0 for Tiled gids
+}
+
+impl Default for TileConfig {
+    // 82px tiles with id 2 as the wall, what the examples and generated maps use
+    fn default() -> Self {
+        Self {
+            tile_size: (82, 82),
+            solid: [2].into_iter().collect(),
+            properties: HashMap::new(),
+            animations: HashMap::new(),
+            merge_colliders: true,
+            empty_tile: None,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl TileConfig {
+    pub fn new(tile_width: u32, tile_height: u32) -> Self {
+        Self {
+            tile_size: (tile_width.max(1), tile_height.max(1)),
+            solid: HashSet::new(),
+            properties: HashMap::new(),
+            animations: HashMap::new(),
+            merge_colliders: true,
+            empty_tile: None,
+        }
+    }
+
+    pub fn set_tile_size(&mut self, tile_width: u32, tile_height: u32) {
+        self.tile_size = (tile_width.max(1), tile_height.max(1));
+    }
+
+    pub fn set_solid(&mut self, tile_id: u32, solid: bool) {
+        if solid {
+            self.solid.insert(tile_id);
+        } else {
+            self.solid.remove(&tile_id);
+        }
+    }
+
+    pub fn set_property(&mut self, tile_id: u32, name: &str, value: &str) {
+        self.set_typed_property(tile_id, name, value);
+    }
+
+    // e.g. config.set_typed_property(5, "damage", 10) or (3, "ladder", true)
+    pub fn set_typed_property(&mut self, tile_id: u32, name: &str, value: impl Into<TileProperty>) {
+        self.properties.entry(tile_id).or_default().insert(name.to_string(), value.into());
+    }
+
+    pub fn set_merge_colliders(&mut self, merge_colliders: bool) {
+        self.merge_colliders = merge_colliders;
+    }
+
+    pub fn set_animation(&mut self, tile_id: u32, animation: TileAnimation) {
+        self.animations.insert(tile_id, animation);
+    }
+
+    // Takes over tile size, solidity, properties and animations of a tileset definition
+    pub fn apply_tileset(&mut self, tileset: &TilesetDefinition) {
+        if let Some((tile_width, tile_height)) = tileset.tile_size {
+            self.set_tile_size(tile_width, tile_height);
+        }
+        for (&tile_id, tile) in &tileset.tiles {
+            if let Some(solid) = tile.solid {
+                self.set_solid(tile_id, solid);
+            }
+            for (name, value) in &tile.properties {
+                self.set_typed_property(tile_id, name, value.clone());
+            }
+            if let Some(animation) = &tile.animation {
+                self.set_animation(tile_id, animation.clone());
+            }
+        }
+    }
+
+    pub fn is_solid(&self, tile_id: u32) -> bool {
+        self.solid.contains(&tile_id)
+    }
+
+    // Text value of a property, numbers and flags are read with typed_property
+    pub fn property(&self, tile_id: u32, name: &str) -> Option<&str> {
+        self.typed_property(tile_id, name)?.as_str()
+    }
+
+    pub fn typed_property(&self, tile_id: u32, name: &str) -> Option<&TileProperty> {
+        self.properties.get(&tile_id)?.get(name)
+    }
+
+    pub fn is_animated(&self, tile_id: u32) -> bool {
+        self.animations.contains_key(&tile_id)
+    }
+}
+
 pub struct Tile<'a> {
     pub textures: Vec<&'a TextureManager<'a>>,
     pub tile_map: Vec<Vec<u32>>,
     pub colliders: Vec<Rect>,
+    pub fixed_colliders: Vec<Rect>, // Kept by rebuild_colliders, e.g. the collision shapes of a Tiled layer
     pub texture_grid: Option<TextureGrid<'a>>,
+    pub config: TileConfig,
+    pub animation_time: u32, // Milliseconds, drives the animated tiles
 }
 
 #[allow(dead_code)]
 impl<'a> Tile<'a> {
     pub fn new(tile_map_path: &Path, textures: Vec<& 'a TextureManager<'a>>, texture_grid: Option<TextureGrid<'a>>) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_config(tile_map_path, textures, texture_grid, TileConfig::default())
+    }
+
+    pub fn with_config(
+        tile_map_path: &Path,
+        textures: Vec<&'a TextureManager<'a>>,
+        texture_grid: Option<TextureGrid<'a>>,
+        config: TileConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let tile_map_string = std::fs::read_to_string(tile_map_path).map_err(|e| e.to_string())?;
         let mut tile_map: Vec<Vec<u32>> = Vec::new();
-        let mut colliders: Vec<Rect> = Vec::new();
-
-        for (y, line) in tile_map_string.lines().enumerate() {
-            let row: Vec<u32> = line.split_whitespace()
-                .map(|s| s.parse::<u32>().unwrap())
-                .collect();
-            for (x, &tile_type) in row.iter().enumerate() {
-                if tile_type == 2 {
-                    let collider = Rect::new((x * 82) as i32, (y * 82) as i32, 82, 82);
-                    colliders.push(collider);
-                }
-            }
+
+        for line in tile_map_string.lines() {
+            let row = line.split_whitespace()
+                .map(|s| s.parse::<u32>())
+                .collect::<Result<Vec<u32>, _>>()?;
             tile_map.push(row);
         }
-        Ok(Self { textures, tile_map, colliders, texture_grid })
+        let mut tile = Self { textures, tile_map, colliders: Vec::new(), fixed_colliders: Vec::new(), texture_grid, config, animation_time: 0 };
+        tile.rebuild_colliders();
+        Ok(tile)
+    }
+
+    // Draws with the tile size of the config
+    pub fn render_configured(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) -> Result<(), String> {
+        self.render(canvas, self.config.tile_size)
     }
 
     pub fn render(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, tile_size: (u32, u32)) -> Result<(), String> {
@@ -43,8 +164,10 @@ impl<'a> Tile<'a> {
         let cols = self.tile_map[0].len();
         for y in 0..rows {
             for x in 0..cols {
-                let tile_index = self.tile_map[y][x] as usize;
-                let texture_manager = &self.textures[tile_index];
+                let texture_manager = match self.texture_for(self.tile_map[y][x]) {
+                    Some(texture_manager) => texture_manager,
+                    None => continue,
+                };
                 let dest = sdl2::rect::Rect::new(
                     (x * tile_width as usize) as i32,
                     (y * tile_height as usize) as i32,
@@ -57,30 +180,220 @@ impl<'a> Tile<'a> {
         Ok(())
     }
 
+    pub fn render_with_camera_configured(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, camera: &Camera) -> Result<(), String> {
+        self.render_with_camera(canvas, self.config.tile_size, camera)
+    }
+
+    // Draws only the tiles the camera can see, honouring zoom
+    pub fn render_with_camera(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, tile_size: (u32, u32), camera: &Camera) -> Result<(), String> {
+        let (tile_width, tile_height) = tile_size;
+        for (y, row) in self.tile_map.iter().enumerate() {
+            for (x, &tile_index) in row.iter().enumerate() {
+                let world_rect = crate::two_d::Rect::new(
+                    (x * tile_width as usize) as i32,
+                    (y * tile_height as usize) as i32,
+                    tile_width,
+                    tile_height,
+                );
+                if !camera.is_visible(&world_rect) {
+                    continue;
+                }
+                if let (Some(texture_manager), Some(dest)) = (self.texture_for(tile_index), camera.transform_rect(&world_rect)) {
+                    texture_manager.render_texture(canvas, dest)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn set_texture_grid(&mut self, texture_grid: TextureGrid<'a>) {
         self.texture_grid = Some(texture_grid);
     }
 
+    // Advances the animated tiles
+    pub fn update(&mut self, delta_time: f32) {
+        self.animation_time = self.animation_time.wrapping_add((delta_time * 1000.0) as u32);
+    }
+
+    // Tile id to draw for a cell holding tile_id, the current frame for animated tiles
+    pub fn display_tile(&self, tile_id: u32) -> u32 {
+        self.config.animations.get(&tile_id)
+            .and_then(|animation| animation.frame_at(self.animation_time))
+            .unwrap_or(tile_id)
+    }
+
+    // Texture drawn for a cell, None for empty cells and ids without a texture
+    pub fn texture_for(&self, tile_id: u32) -> Option<&'a TextureManager<'a>> {
+        if self.config.empty_tile == Some(tile_id) {
+            return None;
+        }
+        self.textures.get(self.display_tile(tile_id) as usize).copied()
+    }
+
+    pub fn set_tileset(&mut self, tileset: &TilesetDefinition) {
+        self.config.apply_tileset(tileset);
+        self.rebuild_colliders();
+    }
+
+    // Replaces the colliders with the solid cells of the new config, plus the fixed colliders
+    pub fn set_config(&mut self, config: TileConfig) {
+        self.config = config;
+        self.rebuild_colliders();
+    }
+
+    pub fn rebuild_colliders(&mut self) {
+        let (tile_width, tile_height) = self.config.tile_size;
+        if self.config.merge_colliders {
+            self.colliders = merge_cells(&self.solid_grid(), self.config.tile_size);
+        } else {
+            self.colliders.clear();
+            for (y, row) in self.tile_map.iter().enumerate() {
+                for (x, &tile_type) in row.iter().enumerate() {
+                    if self.config.is_solid(tile_type) {
+                        let collider = Rect::new(x as i32 * tile_width as i32, y as i32 * tile_height as i32, tile_width, tile_height);
+                        self.colliders.push(collider);
+                    }
+                }
+            }
+        }
+        self.colliders.extend_from_slice(&self.fixed_colliders);
+    }
+
+    pub fn solid_grid(&self) -> Vec<Vec<bool>> {
+        self.tile_map.iter().map(|row| row.iter().map(|&tile_id| self.config.is_solid(tile_id)).collect()).collect()
+    }
+
+    // Outlines of the solid areas, for sliding along walls and as light occluders
+    pub fn edge_chains(&self) -> Vec<EdgeChain> {
+        outline_chains(&self.solid_grid(), self.config.tile_size)
+    }
+
+    // Changes one cell and its collider. Chunk renderers drawing this map need the cell invalidated.
+    pub fn set_tile(&mut self, x: usize, y: usize, tile_id: u32) {
+        let previous = match self.tile_map.get_mut(y).and_then(|row| row.get_mut(x)) {
+            Some(cell) => std::mem::replace(cell, tile_id),
+            None => return,
+        };
+        if self.config.is_solid(previous) != self.config.is_solid(tile_id) {
+            self.rebuild_colliders();
+        }
+    }
+
+    pub fn tile_at(&self, x: usize, y: usize) -> Option<u32> {
+        self.tile_map.get(y)?.get(x).copied()
+    }
+
+    // Grid cell under a world position
+    pub fn cell_at_world(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        let (tile_width, tile_height) = self.config.tile_size;
+        if x < 0 || y < 0 {
+            return None;
+        }
+        let cell = (x as usize / tile_width as usize, y as usize / tile_height as usize);
+        self.tile_at(cell.0, cell.1).map(|_| cell)
+    }
+
+    pub fn tile_at_world(&self, x: i32, y: i32) -> Option<u32> {
+        let (cell_x, cell_y) = self.cell_at_world(x, y)?;
+        self.tile_at(cell_x, cell_y)
+    }
+
+    pub fn is_solid_at(&self, x: usize, y: usize) -> bool {
+        self.tile_at(x, y).is_some_and(|tile_id| self.config.is_solid(tile_id))
+    }
+
+    pub fn property_at(&self, x: usize, y: usize, name: &str) -> Option<&str> {
+        self.config.property(self.tile_at(x, y)?, name)
+    }
+
+    pub fn typed_property_at(&self, x: usize, y: usize, name: &str) -> Option<&TileProperty> {
+        self.config.typed_property(self.tile_at(x, y)?, name)
+    }
+
+    // e.g. tile.property_at_world(feet.x, feet.y, "footstep")
+    pub fn property_at_world(&self, x: i32, y: i32, name: &str) -> Option<&str> {
+        self.config.property(self.tile_at_world(x, y)?, name)
+    }
+
+    // e.g. tile.typed_property_at_world(feet.x, feet.y, "ladder") or "damage", "friction"
+    pub fn typed_property_at_world(&self, x: i32, y: i32, name: &str) -> Option<&TileProperty> {
+        self.config.typed_property(self.tile_at_world(x, y)?, name)
+    }
+
+    // Size of the whole map in pixels
+    pub fn world_size(&self) -> (u32, u32) {
+        let (tile_width, tile_height) = self.config.tile_size;
+        let columns = self.tile_map.iter().map(|row| row.len()).max().unwrap_or(0) as u32;
+        (columns * tile_width, self.tile_map.len() as u32 * tile_height)
+    }
+
+    // tile_map holds the gids of a Tiled tile layer (0 is empty), so textures[gid] draws each tile.
+    // Colliders come from the collision shapes the Tiled tilesets give the tiles of this layer.
+    pub fn from_tiled(
+        map: &TiledMap,
+        layer_name: &str,
+        textures: Vec<&'a TextureManager<'a>>,
+        texture_grid: Option<TextureGrid<'a>>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let layer = map.layer(layer_name)
+            .filter(|layer| layer.is_tile_layer())
+            .ok_or_else(|| format!("No tile layer named '{}'", layer_name))?;
+        let mut config = TileConfig::new(map.tile_width, map.tile_height);
+        config.empty_tile = Some(0);
+        let mut tile = Self {
+            textures,
+            tile_map: map.grid(layer),
+            colliders: Vec::new(),
+            fixed_colliders: map.layer_colliders(layer),
+            texture_grid,
+            config,
+            animation_time: 0,
+        };
+        tile.rebuild_colliders();
+        Ok(tile)
+    }
+
+    // tile_map holds the values of an LDtk IntGrid layer, cells with a value in solid become colliders
+    pub fn from_ldtk(
+        level: &LdtkLevel,
+        layer_name: &str,
+        solid: &[i32],
+        textures: Vec<&'a TextureManager<'a>>,
+        texture_grid: Option<TextureGrid<'a>>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let layer = level.layer(layer_name)
+            .filter(|layer| !layer.int_grid.is_empty())
+            .ok_or_else(|| format!("No IntGrid layer named '{}' in level '{}'", layer_name, level.identifier))?;
+        let mut config = TileConfig::new(layer.grid_size, layer.grid_size);
+        config.solid = solid.iter().filter(|&&value| value > 0).map(|&value| value as u32).collect();
+        let mut tile = Self {
+            textures,
+            tile_map: layer.grid(),
+            colliders: Vec::new(),
+            fixed_colliders: Vec::new(),
+            texture_grid,
+            config,
+            animation_time: 0,
+        };
+        tile.rebuild_colliders();
+        Ok(tile)
+    }
+
     pub fn from_generated_map(
         generated_map: Vec<Vec<u32>>,
         textures: Vec<&'a TextureManager<'a>>,
         texture_grid: Option<TextureGrid<'a>>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut colliders: Vec<Rect> = Vec::new();
-
-        for (y, row) in generated_map.iter().enumerate() {
-            for (x, &tile_type) in row.iter().enumerate() {
-                if tile_type == 2 {
-                    let collider = Rect::new((x * 82) as i32, (y * 82) as i32, 82, 82);
-                    colliders.push(collider);
-                }
-            }
-        }
-        Ok(Self {
+        let mut tile = Self {
             textures,
             tile_map: generated_map,
-            colliders,
+            colliders: Vec::new(),
+            fixed_colliders: Vec::new(),
             texture_grid,
-        })
+            config: TileConfig::default(),
+            animation_time: 0,
+        };
+        tile.rebuild_colliders();
+        Ok(tile)
     }
 }
\ No newline at end of file
@@ -0,0 +1,182 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::camera::Camera;
+use crate::two_d::tile::Tile;
+
+use sdl2::pixels::Color;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
+use sdl2::video::WindowContext;
+
+use std::collections::{HashMap, HashSet};
+
+// Draws a Tile map as square chunks of cells. Each chunk is drawn into its own texture the first time
+// it comes into view, afterwards a frame costs one copy per visible chunk instead of one per cell.
+// Animated tiles are left out of the chunk textures and drawn on top every frame.
+pub struct TileChunkRenderer<'a> {
+    pub chunk_size: u32, // Cells per chunk side
+    texture_creator: &'a TextureCreator<WindowContext>,
+    chunks: HashMap<(u32, u32), Texture<'a>>,
+    dirty: HashSet<(u32, u32)>,
+    animated: HashMap<(u32, u32), Vec<(usize, usize)>>, // Cells with animated tiles per chunk
+    tile_size: (u32, u32), // Tile size the cached chunks were drawn with
+}
+
+#[allow(dead_code)]
+impl<'a> TileChunkRenderer<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>, chunk_size: u32) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            texture_creator,
+            chunks: HashMap::new(),
+            dirty: HashSet::new(),
+            animated: HashMap::new(),
+            tile_size: (0, 0),
+        }
+    }
+
+    pub fn set_chunk_size(&mut self, chunk_size: u32) {
+        self.chunk_size = chunk_size.max(1);
+        self.invalidate_all();
+    }
+
+    // Redraws the chunk holding a cell, call it after Tile::set_tile
+    pub fn invalidate_cell(&mut self, x: usize, y: usize) {
+        let chunk = (x as u32 / self.chunk_size, y as u32 / self.chunk_size);
+        if self.chunks.contains_key(&chunk) {
+            self.dirty.insert(chunk);
+        }
+    }
+
+    // For a new map, or when the renderer lost its render targets (e.g. after a window resize on Direct3D)
+    pub fn invalidate_all(&mut self) {
+        self.chunks.clear();
+        self.dirty.clear();
+        self.animated.clear();
+    }
+
+    pub fn cached_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    // Draws the chunks in view. Without a camera the map is drawn at the window origin.
+    pub fn render(&mut self, canvas: &mut Canvas<sdl2::video::Window>, tile: &Tile<'a>, camera: Option<&Camera>) -> Result<(), String> {
+        let (tile_width, tile_height) = tile.config.tile_size;
+        if self.tile_size != (tile_width, tile_height) {
+            self.invalidate_all();
+            self.tile_size = (tile_width, tile_height);
+        }
+        let rows = tile.tile_map.len() as u32;
+        let columns = tile.tile_map.iter().map(|row| row.len()).max().unwrap_or(0) as u32;
+        if rows == 0 || columns == 0 {
+            return Ok(());
+        }
+        let chunk_width = self.chunk_size * tile_width;
+        let chunk_height = self.chunk_size * tile_height;
+        let chunk_columns = columns.div_ceil(self.chunk_size);
+        let chunk_rows = rows.div_ceil(self.chunk_size);
+
+        let view = match camera {
+            Some(camera) => camera.visible_world_rect(),
+            None => {
+                let (width, height) = canvas.output_size()?;
+                crate::two_d::Rect::new(0, 0, width, height)
+            }
+        };
+        let first_x = (view.x().max(0) as u32 / chunk_width).min(chunk_columns);
+        let first_y = (view.y().max(0) as u32 / chunk_height).min(chunk_rows);
+        let last_x = ((view.x() + view.width() as i32).max(0) as u32).div_ceil(chunk_width).min(chunk_columns);
+        let last_y = ((view.y() + view.height() as i32).max(0) as u32).div_ceil(chunk_height).min(chunk_rows);
+
+        for chunk_y in first_y..last_y {
+            for chunk_x in first_x..last_x {
+                // Chunks at the right and bottom edge only cover the cells that exist
+                let cells_x = self.chunk_size.min(columns - chunk_x * self.chunk_size);
+                let cells_y = self.chunk_size.min(rows - chunk_y * self.chunk_size);
+                let world_rect = crate::two_d::Rect::new(
+                    (chunk_x * chunk_width) as i32,
+                    (chunk_y * chunk_height) as i32,
+                    cells_x * tile_width,
+                    cells_y * tile_height,
+                );
+                let dest = match camera {
+                    Some(camera) if !camera.is_visible(&world_rect) => continue,
+                    Some(camera) => match camera.transform_rect(&world_rect) {
+                        Some(dest) => dest,
+                        None => continue,
+                    },
+                    None => *world_rect.sdl_rect(),
+                };
+                let key = (chunk_x, chunk_y);
+                if !self.chunks.contains_key(&key) || self.dirty.contains(&key) {
+                    self.build_chunk(canvas, tile, key, (cells_x, cells_y))?;
+                }
+                if let Some(texture) = self.chunks.get(&key) {
+                    canvas.copy(texture, None, dest)?;
+                }
+                for &(x, y) in self.animated.get(&key).map(|cells| cells.as_slice()).unwrap_or(&[]) {
+                    let texture = match tile.tile_at(x, y)
+                        .and_then(|tile_id| tile.texture_for(tile_id))
+                        .and_then(|manager| manager.texture.as_ref())
+                    {
+                        Some(texture) => texture,
+                        None => continue,
+                    };
+                    let cell_rect = crate::two_d::Rect::new(x as i32 * tile_width as i32, y as i32 * tile_height as i32, tile_width, tile_height);
+                    let cell_dest = match camera {
+                        Some(camera) => camera.transform_rect(&cell_rect),
+                        None => Some(*cell_rect.sdl_rect()),
+                    };
+                    if let Some(cell_dest) = cell_dest {
+                        canvas.copy(texture, None, cell_dest)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn build_chunk(&mut self, canvas: &mut Canvas<sdl2::video::Window>, tile: &Tile<'a>, key: (u32, u32), cells: (u32, u32)) -> Result<(), String> {
+        let (tile_width, tile_height) = self.tile_size;
+        if !self.chunks.contains_key(&key) {
+            let mut texture = self.texture_creator
+                .create_texture_target(None, cells.0 * tile_width, cells.1 * tile_height)
+                .map_err(|e| e.to_string())?;
+            texture.set_blend_mode(BlendMode::Blend);
+            self.chunks.insert(key, texture);
+        }
+        self.dirty.remove(&key);
+        let texture = self.chunks.get_mut(&key).unwrap();
+        let (start_x, start_y) = ((key.0 * self.chunk_size) as usize, (key.1 * self.chunk_size) as usize);
+        let mut result = Ok(());
+        let mut animated = Vec::new();
+        canvas.with_texture_canvas(texture, |chunk_canvas| {
+            chunk_canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+            chunk_canvas.clear();
+            for y in 0..cells.1 as usize {
+                for x in 0..cells.0 as usize {
+                    let tile_id = match tile.tile_at(start_x + x, start_y + y) {
+                        Some(tile_id) => tile_id,
+                        None => continue,
+                    };
+                    if tile.config.is_animated(tile_id) {
+                        animated.push((start_x + x, start_y + y));
+                        continue;
+                    }
+                    // Empty cells and ids without a loaded texture are left empty
+                    let texture = match tile.texture_for(tile_id).and_then(|manager| manager.texture.as_ref()) {
+                        Some(texture) => texture,
+                        None => continue,
+                    };
+                    let dest = sdl2::rect::Rect::new(x as i32 * tile_width as i32, y as i32 * tile_height as i32, tile_width, tile_height);
+                    if let Err(e) = chunk_canvas.copy(texture, None, dest) {
+                        result = Err(e);
+                        return;
+                    }
+                }
+            }
+        }).map_err(|e| e.to_string())?;
+        self.animated.insert(key, animated);
+        result
+    }
+}
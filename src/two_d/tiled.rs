@@ -0,0 +1,854 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use crate::two_d::camera::Camera;
+
+use base64::Engine;
+use serde_json::Value;
+
+use sdl2::image::LoadTexture;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::video::WindowContext;
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// The top bits of a Tiled gid store how the tile is flipped
+pub const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+pub const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+pub const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const ROTATED_HEXAGONAL_120: u32 = 0x1000_0000;
+const GID_MASK: u32 = !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL_120);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TiledCell {
+    pub gid: u32, // 0 is an empty cell
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    pub flip_diagonal: bool, // Swaps x and y, together with the other flips this rotates by 90 degrees
+}
+
+impl TiledCell {
+    pub fn from_raw(raw: u32) -> Self {
+        Self {
+            gid: raw & GID_MASK,
+            flip_horizontal: raw & FLIPPED_HORIZONTALLY != 0,
+            flip_vertical: raw & FLIPPED_VERTICALLY != 0,
+            flip_diagonal: raw & FLIPPED_DIAGONALLY != 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gid == 0
+    }
+
+    // Angle and flips for canvas.copy_ex. SDL flips before rotating, Tiled transposes before flipping.
+    pub fn copy_ex_params(&self) -> (f64, bool, bool) {
+        if self.flip_diagonal {
+            (90.0, self.flip_vertical, !self.flip_horizontal)
+        } else {
+            (0.0, self.flip_horizontal, self.flip_vertical)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledProperty {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String), // Also color, file and class values
+}
+
+#[allow(dead_code)]
+impl TiledProperty {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            TiledProperty::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            TiledProperty::Int(value) => Some(*value),
+            TiledProperty::Float(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            TiledProperty::Int(value) => Some(*value as f64),
+            TiledProperty::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            TiledProperty::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn parse(kind: &str, value: &str) -> Self {
+        match kind {
+            "bool" => TiledProperty::Bool(value == "true"),
+            "int" | "object" => value.parse().map(TiledProperty::Int).unwrap_or(TiledProperty::String(value.to_string())),
+            "float" => value.parse().map(TiledProperty::Float).unwrap_or(TiledProperty::String(value.to_string())),
+            _ => TiledProperty::String(value.to_string()),
+        }
+    }
+}
+
+pub type TiledProperties = HashMap<String, TiledProperty>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledShape {
+    Rectangle,
+    Ellipse,
+    Point,
+    Polygon(Vec<(f32, f32)>), // Relative to the object position
+    Polyline(Vec<(f32, f32)>),
+}
+
+#[derive(Clone, Debug)]
+pub struct TiledObject {
+    pub id: u32,
+    pub name: String,
+    pub class: String, // "type" before Tiled 1.9
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub rotation: f32,
+    pub visible: bool,
+    pub cell: Option<TiledCell>, // Tile objects
+    pub shape: TiledShape,
+    pub properties: TiledProperties,
+}
+
+#[allow(dead_code)]
+impl TiledObject {
+    // Axis aligned bounds as (x, y, width, height), ignoring rotation
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        match &self.shape {
+            TiledShape::Polygon(points) | TiledShape::Polyline(points) if !points.is_empty() => {
+                let min_x = points.iter().map(|p| p.0).fold(f32::MAX, f32::min);
+                let min_y = points.iter().map(|p| p.1).fold(f32::MAX, f32::min);
+                let max_x = points.iter().map(|p| p.0).fold(f32::MIN, f32::max);
+                let max_y = points.iter().map(|p| p.1).fold(f32::MIN, f32::max);
+                (self.x + min_x, self.y + min_y, max_x - min_x, max_y - min_y)
+            }
+            // Tile objects are anchored at their bottom left corner
+            _ if self.cell.is_some() => (self.x, self.y - self.height, self.width, self.height),
+            _ => (self.x, self.y, self.width, self.height),
+        }
+    }
+
+    pub fn property(&self, name: &str) -> Option<&TiledProperty> {
+        self.properties.get(name)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TiledTileData {
+    pub properties: TiledProperties,
+    pub collision: Vec<TiledObject>, // Shapes from the tile collision editor, in tile pixels
+    pub animation: Vec<(u32, u32)>,  // (local tile id, duration in ms)
+    pub image: Option<PathBuf>,      // Tiles of image collection tilesets have their own image
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TiledTileset {
+    pub first_gid: u32,
+    pub name: String,
+    pub image: Option<PathBuf>, // Resolved against the map or .tsx file
+    pub image_width: u32,
+    pub image_height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub spacing: u32,
+    pub margin: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+    pub tiles: HashMap<u32, TiledTileData>,
+    pub properties: TiledProperties,
+}
+
+#[allow(dead_code)]
+impl TiledTileset {
+    pub fn contains(&self, gid: u32) -> bool {
+        gid >= self.first_gid && gid < self.first_gid + self.tile_count.max(1)
+    }
+
+    // Part of the tileset image showing a tile, margin and spacing included
+    pub fn source_rect(&self, local_id: u32) -> Rect {
+        let columns = self.columns.max(1);
+        let column = local_id % columns;
+        let row = local_id / columns;
+        Rect::new(
+            (self.margin + column * (self.tile_width + self.spacing)) as i32,
+            (self.margin + row * (self.tile_height + self.spacing)) as i32,
+            self.tile_width,
+            self.tile_height,
+        )
+    }
+
+    fn finish(&mut self) {
+        if self.columns == 0 && self.tile_width > 0 && self.image_width > 0 {
+            self.columns = (self.image_width.saturating_sub(self.margin * 2) + self.spacing) / (self.tile_width + self.spacing);
+        }
+        if self.tile_count == 0 && self.columns > 0 && self.tile_height > 0 {
+            let rows = (self.image_height.saturating_sub(self.margin * 2) + self.spacing) / (self.tile_height + self.spacing);
+            self.tile_count = self.columns * rows;
+        }
+        if self.tile_count == 0 {
+            self.tile_count = self.tiles.keys().max().map_or(0, |id| id + 1);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum TiledLayerData {
+    Tiles { width: u32, height: u32, cells: Vec<TiledCell> },
+    Objects(Vec<TiledObject>),
+}
+
+#[derive(Clone, Debug)]
+pub struct TiledLayer {
+    pub id: u32,
+    pub name: String, // Layers inside groups are named "group/layer"
+    pub visible: bool,
+    pub opacity: f32,
+    pub offset: (f32, f32),
+    pub properties: TiledProperties,
+    pub data: TiledLayerData,
+}
+
+#[allow(dead_code)]
+impl TiledLayer {
+    pub fn cell(&self, x: u32, y: u32) -> Option<TiledCell> {
+        match &self.data {
+            TiledLayerData::Tiles { width, height, cells } if x < *width && y < *height => cells.get((y * width + x) as usize).copied(),
+            _ => None,
+        }
+    }
+
+    pub fn objects(&self) -> &[TiledObject] {
+        match &self.data {
+            TiledLayerData::Objects(objects) => objects,
+            _ => &[],
+        }
+    }
+
+    pub fn is_tile_layer(&self) -> bool {
+        matches!(self.data, TiledLayerData::Tiles { .. })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TiledMap {
+    pub orientation: String,
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<TiledTileset>,
+    pub layers: Vec<TiledLayer>, // Group layers are flattened in draw order
+    pub properties: TiledProperties,
+}
+
+#[allow(dead_code)]
+impl TiledMap {
+    // .tmx (XML) or .tmj/.json maps, external tilesets are loaded next to the map
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        let map = if extension == "tmx" || (extension != "tmj" && extension != "json" && text.trim_start().starts_with('<')) {
+            Self::parse_tmx(&text, directory)
+        } else {
+            Self::parse_tmj(&text, directory)
+        };
+        map.map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse_tmx(text: &str, directory: &Path) -> Result<Self, String> {
+        let document = roxmltree::Document::parse(text).map_err(|e| e.to_string())?;
+        let root = document.root_element();
+        if root.tag_name().name() != "map" {
+            return Err("Not a Tiled map".to_string());
+        }
+        if attribute_u32(&root, "infinite") == 1 {
+            return Err("Infinite maps are not supported, resize the map to a fixed size in Tiled".to_string());
+        }
+        let mut map = TiledMap {
+            orientation: root.attribute("orientation").unwrap_or("orthogonal").to_string(),
+            width: attribute_u32(&root, "width"),
+            height: attribute_u32(&root, "height"),
+            tile_width: attribute_u32(&root, "tilewidth"),
+            tile_height: attribute_u32(&root, "tileheight"),
+            tilesets: Vec::new(),
+            layers: Vec::new(),
+            properties: TiledProperties::new(),
+        };
+        for child in root.children().filter(|n| n.is_element()) {
+            match child.tag_name().name() {
+                "properties" => map.properties = xml_properties(&child),
+                "tileset" => {
+                    let first_gid = attribute_u32(&child, "firstgid");
+                    let mut tileset = match child.attribute("source") {
+                        Some(source) => load_external_tileset(&directory.join(source))?,
+                        None => xml_tileset(&child, directory)?,
+                    };
+                    tileset.first_gid = first_gid;
+                    map.tilesets.push(tileset);
+                }
+                _ => {}
+            }
+        }
+        xml_layers(&root, "", (0.0, 0.0), true, 1.0, &mut map.layers)?;
+        map.tilesets.sort_by_key(|tileset| tileset.first_gid);
+        Ok(map)
+    }
+
+    pub fn parse_tmj(text: &str, directory: &Path) -> Result<Self, String> {
+        let root: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if root["infinite"].as_bool().unwrap_or(false) {
+            return Err("Infinite maps are not supported, resize the map to a fixed size in Tiled".to_string());
+        }
+        let mut map = TiledMap {
+            orientation: root["orientation"].as_str().unwrap_or("orthogonal").to_string(),
+            width: json_u32(&root, "width"),
+            height: json_u32(&root, "height"),
+            tile_width: json_u32(&root, "tilewidth"),
+            tile_height: json_u32(&root, "tileheight"),
+            tilesets: Vec::new(),
+            layers: Vec::new(),
+            properties: json_properties(&root),
+        };
+        for entry in root["tilesets"].as_array().map(|a| a.as_slice()).unwrap_or(&[]) {
+            let mut tileset = match entry["source"].as_str() {
+                Some(source) => load_external_tileset(&directory.join(source))?,
+                None => json_tileset(entry, directory)?,
+            };
+            tileset.first_gid = json_u32(entry, "firstgid");
+            map.tilesets.push(tileset);
+        }
+        json_layers(&root["layers"], "", (0.0, 0.0), true, 1.0, &mut map.layers)?;
+        map.tilesets.sort_by_key(|tileset| tileset.first_gid);
+        Ok(map)
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&TiledLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    pub fn tile_layers(&self) -> impl Iterator<Item = &TiledLayer> {
+        self.layers.iter().filter(|layer| layer.is_tile_layer())
+    }
+
+    pub fn object_layers(&self) -> impl Iterator<Item = &TiledLayer> {
+        self.layers.iter().filter(|layer| !layer.is_tile_layer())
+    }
+
+    // All objects of all object layers, e.g. to find spawn points by class
+    pub fn objects(&self) -> impl Iterator<Item = &TiledObject> {
+        self.layers.iter().flat_map(|layer| layer.objects().iter())
+    }
+
+    // Tileset holding gid and the id of the tile inside it
+    pub fn tileset_for(&self, gid: u32) -> Option<(usize, u32)> {
+        if gid == 0 {
+            return None;
+        }
+        self.tilesets.iter().rposition(|tileset| tileset.first_gid <= gid)
+            .map(|index| (index, gid - self.tilesets[index].first_gid))
+    }
+
+    pub fn tile_data(&self, gid: u32) -> Option<&TiledTileData> {
+        let (index, local_id) = self.tileset_for(gid)?;
+        self.tilesets[index].tiles.get(&local_id)
+    }
+
+    pub fn tile_property(&self, gid: u32, name: &str) -> Option<&TiledProperty> {
+        self.tile_data(gid)?.properties.get(name)
+    }
+
+    // Gids of a tile layer as rows, flip flags removed, ready for Tile::tile_map
+    pub fn grid(&self, layer: &TiledLayer) -> Vec<Vec<u32>> {
+        match &layer.data {
+            TiledLayerData::Tiles { width, cells, .. } if *width > 0 => {
+                cells.chunks(*width as usize).map(|row| row.iter().map(|cell| cell.gid).collect()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // Collision shapes of every placed tile as world rects. Polygons and ellipses use their bounds.
+    pub fn tile_colliders(&self) -> Vec<Rect> {
+        self.tile_layers().filter(|layer| layer.visible).flat_map(|layer| self.layer_colliders(layer)).collect()
+    }
+
+    // Collision shapes of the tiles placed on one layer
+    pub fn layer_colliders(&self, layer: &TiledLayer) -> Vec<Rect> {
+        let mut colliders = Vec::new();
+        if let TiledLayerData::Tiles { width, cells, .. } = &layer.data {
+            let width = *width;
+            for (index, cell) in cells.iter().enumerate() {
+                let (tileset_index, local_id) = match self.tileset_for(cell.gid) {
+                    Some(found) => found,
+                    None => continue,
+                };
+                let tileset = &self.tilesets[tileset_index];
+                let data = match tileset.tiles.get(&local_id) {
+                    Some(data) if !data.collision.is_empty() => data,
+                    _ => continue,
+                };
+                // Tiles taller than the grid stick out upwards from the bottom of their cell
+                let tile_x = (index as u32 % width * self.tile_width) as f32 + layer.offset.0;
+                let tile_y = ((index as u32 / width + 1) * self.tile_height) as f32 - tileset.tile_height as f32 + layer.offset.1;
+                for shape in &data.collision {
+                    let (mut x, mut y, mut w, mut h) = shape.bounds();
+                    let (mut tile_w, mut tile_h) = (tileset.tile_width as f32, tileset.tile_height as f32);
+                    if cell.flip_diagonal {
+                        std::mem::swap(&mut x, &mut y);
+                        std::mem::swap(&mut w, &mut h);
+                        std::mem::swap(&mut tile_w, &mut tile_h);
+                    }
+                    if cell.flip_horizontal {
+                        x = tile_w - x - w;
+                    }
+                    if cell.flip_vertical {
+                        y = tile_h - y - h;
+                    }
+                    if w >= 1.0 && h >= 1.0 {
+                        colliders.push(Rect::new((tile_x + x).round() as i32, (tile_y + y).round() as i32, w.round() as u32, h.round() as u32));
+                    }
+                }
+            }
+        }
+        colliders
+    }
+}
+
+fn load_external_tileset(path: &Path) -> Result<TiledTileset, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let tileset = if text.trim_start().starts_with('<') {
+        let document = roxmltree::Document::parse(&text).map_err(|e| e.to_string())?;
+        xml_tileset(&document.root_element(), directory)
+    } else {
+        let root: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        json_tileset(&root, directory)
+    };
+    tileset.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Layer data: little endian u32 gids, optionally zlib or gzip compressed
+fn decode_base64_cells(text: &str, compression: &str) -> Result<Vec<TiledCell>, String> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = base64::engine::general_purpose::STANDARD.decode(compact).map_err(|e| e.to_string())?;
+    let bytes = match compression {
+        "" => bytes,
+        "zlib" => {
+            let mut out = Vec::new();
+            flate2::read::ZlibDecoder::new(&bytes[..]).read_to_end(&mut out).map_err(|e| e.to_string())?;
+            out
+        }
+        "gzip" => {
+            let mut out = Vec::new();
+            flate2::read::GzDecoder::new(&bytes[..]).read_to_end(&mut out).map_err(|e| e.to_string())?;
+            out
+        }
+        other => return Err(format!("Unsupported layer compression '{}'", other)),
+    };
+    Ok(bytes.chunks_exact(4)
+        .map(|b| TiledCell::from_raw(u32::from_le_bytes([b[0], b[1], b[2], b[3]])))
+        .collect())
+}
+
+fn parse_points(points: &str) -> Vec<(f32, f32)> {
+    points.split_whitespace()
+        .filter_map(|pair| {
+            let (x, y) = pair.split_once(',')?;
+            Some((x.parse().ok()?, y.parse().ok()?))
+        })
+        .collect()
+}
+
+fn attribute_u32(node: &roxmltree::Node, name: &str) -> u32 {
+    node.attribute(name).and_then(|value| value.parse().ok()).unwrap_or(0)
+}
+
+fn attribute_f32(node: &roxmltree::Node, name: &str, default: f32) -> f32 {
+    node.attribute(name).and_then(|value| value.parse().ok()).unwrap_or(default)
+}
+
+fn xml_properties(node: &roxmltree::Node) -> TiledProperties {
+    let mut properties = TiledProperties::new();
+    for property in node.children().filter(|n| n.has_tag_name("property")) {
+        let name = property.attribute("name").unwrap_or("").to_string();
+        // Multi-line strings are stored as text instead of the value attribute
+        let value = property.attribute("value").or_else(|| property.text()).unwrap_or("");
+        properties.insert(name, TiledProperty::parse(property.attribute("type").unwrap_or("string"), value));
+    }
+    properties
+}
+
+fn xml_object(node: &roxmltree::Node) -> TiledObject {
+    let mut object = TiledObject {
+        id: attribute_u32(node, "id"),
+        name: node.attribute("name").unwrap_or("").to_string(),
+        class: node.attribute("class").or_else(|| node.attribute("type")).unwrap_or("").to_string(),
+        x: attribute_f32(node, "x", 0.0),
+        y: attribute_f32(node, "y", 0.0),
+        width: attribute_f32(node, "width", 0.0),
+        height: attribute_f32(node, "height", 0.0),
+        rotation: attribute_f32(node, "rotation", 0.0),
+        visible: node.attribute("visible") != Some("0"),
+        cell: node.attribute("gid").and_then(|gid| gid.parse().ok()).map(TiledCell::from_raw),
+        shape: TiledShape::Rectangle,
+        properties: TiledProperties::new(),
+    };
+    for child in node.children().filter(|n| n.is_element()) {
+        match child.tag_name().name() {
+            "ellipse" => object.shape = TiledShape::Ellipse,
+            "point" => object.shape = TiledShape::Point,
+            "polygon" => object.shape = TiledShape::Polygon(parse_points(child.attribute("points").unwrap_or(""))),
+            "polyline" => object.shape = TiledShape::Polyline(parse_points(child.attribute("points").unwrap_or(""))),
+            "properties" => object.properties = xml_properties(&child),
+            _ => {}
+        }
+    }
+    object
+}
+
+fn xml_tileset(node: &roxmltree::Node, directory: &Path) -> Result<TiledTileset, String> {
+    let mut tileset = TiledTileset {
+        first_gid: attribute_u32(node, "firstgid"),
+        name: node.attribute("name").unwrap_or("").to_string(),
+        tile_width: attribute_u32(node, "tilewidth"),
+        tile_height: attribute_u32(node, "tileheight"),
+        spacing: attribute_u32(node, "spacing"),
+        margin: attribute_u32(node, "margin"),
+        columns: attribute_u32(node, "columns"),
+        tile_count: attribute_u32(node, "tilecount"),
+        ..TiledTileset::default()
+    };
+    for child in node.children().filter(|n| n.is_element()) {
+        match child.tag_name().name() {
+            "image" => {
+                tileset.image = child.attribute("source").map(|source| directory.join(source));
+                tileset.image_width = attribute_u32(&child, "width");
+                tileset.image_height = attribute_u32(&child, "height");
+            }
+            "properties" => tileset.properties = xml_properties(&child),
+            "tile" => {
+                let mut data = TiledTileData::default();
+                for part in child.children().filter(|n| n.is_element()) {
+                    match part.tag_name().name() {
+                        "properties" => data.properties = xml_properties(&part),
+                        "objectgroup" => data.collision = part.children().filter(|n| n.has_tag_name("object")).map(|o| xml_object(&o)).collect(),
+                        "animation" => {
+                            data.animation = part.children()
+                                .filter(|n| n.has_tag_name("frame"))
+                                .map(|frame| (attribute_u32(&frame, "tileid"), attribute_u32(&frame, "duration")))
+                                .collect()
+                        }
+                        "image" => data.image = part.attribute("source").map(|source| directory.join(source)),
+                        _ => {}
+                    }
+                }
+                tileset.tiles.insert(attribute_u32(&child, "id"), data);
+            }
+            _ => {}
+        }
+    }
+    tileset.finish();
+    Ok(tileset)
+}
+
+// Appends the layers below node, group layers pass their offset, visibility and opacity down
+fn xml_layers(node: &roxmltree::Node, prefix: &str, offset: (f32, f32), visible: bool, opacity: f32, layers: &mut Vec<TiledLayer>) -> Result<(), String> {
+    for child in node.children().filter(|n| n.is_element()) {
+        let kind = child.tag_name().name();
+        if kind != "layer" && kind != "objectgroup" && kind != "group" {
+            continue;
+        }
+        let name = format!("{}{}", prefix, child.attribute("name").unwrap_or(""));
+        let offset = (offset.0 + attribute_f32(&child, "offsetx", 0.0), offset.1 + attribute_f32(&child, "offsety", 0.0));
+        let visible = visible && child.attribute("visible") != Some("0");
+        let opacity = opacity * attribute_f32(&child, "opacity", 1.0);
+        if kind == "group" {
+            xml_layers(&child, &format!("{}/", name), offset, visible, opacity, layers)?;
+            continue;
+        }
+        let properties = child.children().find(|n| n.has_tag_name("properties")).map(|p| xml_properties(&p)).unwrap_or_default();
+        let data = if kind == "layer" {
+            let (width, height) = (attribute_u32(&child, "width"), attribute_u32(&child, "height"));
+            let mut cells = Vec::new();
+            if let Some(data) = child.children().find(|n| n.has_tag_name("data")) {
+                let text = data.text().unwrap_or("");
+                cells = match data.attribute("encoding") {
+                    Some("csv") => text.split(',')
+                        .filter_map(|value| value.trim().parse::<u32>().ok())
+                        .map(TiledCell::from_raw)
+                        .collect(),
+                    Some("base64") => decode_base64_cells(text, data.attribute("compression").unwrap_or(""))?,
+                    _ => data.children()
+                        .filter(|n| n.has_tag_name("tile"))
+                        .map(|tile| TiledCell::from_raw(tile.attribute("gid").and_then(|gid| gid.parse().ok()).unwrap_or(0)))
+                        .collect(),
+                };
+            }
+            cells.resize((width * height) as usize, TiledCell::default());
+            TiledLayerData::Tiles { width, height, cells }
+        } else {
+            TiledLayerData::Objects(child.children().filter(|n| n.has_tag_name("object")).map(|o| xml_object(&o)).collect())
+        };
+        layers.push(TiledLayer { id: attribute_u32(&child, "id"), name, visible, opacity, offset, properties, data });
+    }
+    Ok(())
+}
+
+fn json_u32(value: &Value, key: &str) -> u32 {
+    value[key].as_u64().unwrap_or(0) as u32
+}
+
+fn json_f32(value: &Value, key: &str, default: f32) -> f32 {
+    value[key].as_f64().map_or(default, |v| v as f32)
+}
+
+fn json_properties(value: &Value) -> TiledProperties {
+    let mut properties = TiledProperties::new();
+    for property in value["properties"].as_array().map(|a| a.as_slice()).unwrap_or(&[]) {
+        let name = property["name"].as_str().unwrap_or("").to_string();
+        let property_value = match &property["value"] {
+            Value::Bool(value) => TiledProperty::Bool(*value),
+            Value::Number(number) if property["type"] == "float" => TiledProperty::Float(number.as_f64().unwrap_or(0.0)),
+            Value::Number(number) => number.as_i64().map(TiledProperty::Int).unwrap_or_else(|| TiledProperty::Float(number.as_f64().unwrap_or(0.0))),
+            Value::String(text) => TiledProperty::String(text.clone()),
+            other => TiledProperty::String(other.to_string()),
+        };
+        properties.insert(name, property_value);
+    }
+    properties
+}
+
+fn json_points(value: &Value) -> Vec<(f32, f32)> {
+    value.as_array().map(|points| {
+        points.iter().map(|point| (json_f32(point, "x", 0.0), json_f32(point, "y", 0.0))).collect()
+    }).unwrap_or_default()
+}
+
+fn json_object(value: &Value) -> TiledObject {
+    let shape = if value["ellipse"].as_bool().unwrap_or(false) {
+        TiledShape::Ellipse
+    } else if value["point"].as_bool().unwrap_or(false) {
+        TiledShape::Point
+    } else if value["polygon"].is_array() {
+        TiledShape::Polygon(json_points(&value["polygon"]))
+    } else if value["polyline"].is_array() {
+        TiledShape::Polyline(json_points(&value["polyline"]))
+    } else {
+        TiledShape::Rectangle
+    };
+    TiledObject {
+        id: json_u32(value, "id"),
+        name: value["name"].as_str().unwrap_or("").to_string(),
+        class: value["class"].as_str().or_else(|| value["type"].as_str()).unwrap_or("").to_string(),
+        x: json_f32(value, "x", 0.0),
+        y: json_f32(value, "y", 0.0),
+        width: json_f32(value, "width", 0.0),
+        height: json_f32(value, "height", 0.0),
+        rotation: json_f32(value, "rotation", 0.0),
+        visible: value["visible"].as_bool().unwrap_or(true),
+        cell: value["gid"].as_u64().map(|gid| TiledCell::from_raw(gid as u32)),
+        shape,
+        properties: json_properties(value),
+    }
+}
+
+fn json_tileset(value: &Value, directory: &Path) -> Result<TiledTileset, String> {
+    let mut tileset = TiledTileset {
+        first_gid: json_u32(value, "firstgid"),
+        name: value["name"].as_str().unwrap_or("").to_string(),
+        image: value["image"].as_str().map(|image| directory.join(image)),
+        image_width: json_u32(value, "imagewidth"),
+        image_height: json_u32(value, "imageheight"),
+        tile_width: json_u32(value, "tilewidth"),
+        tile_height: json_u32(value, "tileheight"),
+        spacing: json_u32(value, "spacing"),
+        margin: json_u32(value, "margin"),
+        columns: json_u32(value, "columns"),
+        tile_count: json_u32(value, "tilecount"),
+        properties: json_properties(value),
+        ..TiledTileset::default()
+    };
+    for tile in value["tiles"].as_array().map(|a| a.as_slice()).unwrap_or(&[]) {
+        let data = TiledTileData {
+            properties: json_properties(tile),
+            collision: tile["objectgroup"]["objects"].as_array()
+                .map(|objects| objects.iter().map(json_object).collect())
+                .unwrap_or_default(),
+            animation: tile["animation"].as_array()
+                .map(|frames| frames.iter().map(|frame| (json_u32(frame, "tileid"), json_u32(frame, "duration"))).collect())
+                .unwrap_or_default(),
+            image: tile["image"].as_str().map(|image| directory.join(image)),
+        };
+        tileset.tiles.insert(json_u32(tile, "id"), data);
+    }
+    tileset.finish();
+    Ok(tileset)
+}
+
+fn json_layers(value: &Value, prefix: &str, offset: (f32, f32), visible: bool, opacity: f32, layers: &mut Vec<TiledLayer>) -> Result<(), String> {
+    for layer in value.as_array().map(|a| a.as_slice()).unwrap_or(&[]) {
+        let kind = layer["type"].as_str().unwrap_or("");
+        let name = format!("{}{}", prefix, layer["name"].as_str().unwrap_or(""));
+        let offset = (offset.0 + json_f32(layer, "offsetx", 0.0), offset.1 + json_f32(layer, "offsety", 0.0));
+        let visible = visible && layer["visible"].as_bool().unwrap_or(true);
+        let opacity = opacity * json_f32(layer, "opacity", 1.0);
+        let data = match kind {
+            "group" => {
+                json_layers(&layer["layers"], &format!("{}/", name), offset, visible, opacity, layers)?;
+                continue;
+            }
+            "tilelayer" => {
+                let (width, height) = (json_u32(layer, "width"), json_u32(layer, "height"));
+                let mut cells = match &layer["data"] {
+                    Value::String(text) => decode_base64_cells(text, layer["compression"].as_str().unwrap_or(""))?,
+                    Value::Array(gids) => gids.iter().map(|gid| TiledCell::from_raw(gid.as_u64().unwrap_or(0) as u32)).collect(),
+                    _ => Vec::new(),
+                };
+                cells.resize((width * height) as usize, TiledCell::default());
+                TiledLayerData::Tiles { width, height, cells }
+            }
+            "objectgroup" => TiledLayerData::Objects(layer["objects"].as_array().map(|objects| objects.iter().map(json_object).collect()).unwrap_or_default()),
+            _ => continue,
+        };
+        layers.push(TiledLayer { id: json_u32(layer, "id"), name, visible, opacity, offset, properties: json_properties(layer), data });
+    }
+    Ok(())
+}
+
+// Draws the tile layers of a Tiled map with its tileset images
+pub struct TiledMapRenderer<'a> {
+    pub map: TiledMap,
+    textures: Vec<Option<Texture<'a>>>,     // One per tileset
+    tile_textures: HashMap<u32, Texture<'a>>, // Tiles of image collection tilesets, by gid
+}
+
+#[allow(dead_code)]
+impl<'a> TiledMapRenderer<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>, map: TiledMap) -> Result<Self, String> {
+        let mut textures = Vec::with_capacity(map.tilesets.len());
+        let mut tile_textures = HashMap::new();
+        for tileset in &map.tilesets {
+            textures.push(match &tileset.image {
+                Some(image) => Some(texture_creator.load_texture(image)?),
+                None => None,
+            });
+            for (local_id, data) in &tileset.tiles {
+                if let Some(image) = &data.image {
+                    tile_textures.insert(tileset.first_gid + local_id, texture_creator.load_texture(image)?);
+                }
+            }
+        }
+        Ok(Self { map, textures, tile_textures })
+    }
+
+    pub fn load(texture_creator: &'a TextureCreator<WindowContext>, path: &Path) -> Result<Self, String> {
+        Self::new(texture_creator, TiledMap::load(path)?)
+    }
+
+    // All visible tile layers in order. With a camera only the tiles it can see are drawn.
+    pub fn render(&mut self, canvas: &mut Canvas<sdl2::video::Window>, camera: Option<&Camera>) -> Result<(), String> {
+        for index in 0..self.map.layers.len() {
+            if self.map.layers[index].visible {
+                self.render_layer(canvas, index, camera)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn render_layer(&mut self, canvas: &mut Canvas<sdl2::video::Window>, index: usize, camera: Option<&Camera>) -> Result<(), String> {
+        let layer = match self.map.layers.get(index) {
+            Some(layer) => layer,
+            None => return Ok(()),
+        };
+        let (width, height) = match &layer.data {
+            TiledLayerData::Tiles { width, height, .. } => (*width, *height),
+            _ => return Ok(()),
+        };
+        let (tile_width, tile_height) = (self.map.tile_width.max(1) as i32, self.map.tile_height.max(1) as i32);
+        let (offset_x, offset_y) = (layer.offset.0.round() as i32, layer.offset.1.round() as i32);
+
+        // Cell range in view, one extra row and column for tiles larger than the grid
+        let view = match camera {
+            Some(camera) => camera.visible_world_rect(),
+            None => {
+                let (w, h) = canvas.output_size()?;
+                crate::two_d::Rect::new(0, 0, w, h)
+            }
+        };
+        let first_x = ((view.x() - offset_x) / tile_width - 1).max(0) as u32;
+        let first_y = ((view.y() - offset_y) / tile_height - 1).max(0) as u32;
+        let last_x = (((view.x() + view.width() as i32 - offset_x) / tile_width + 2).max(0) as u32).min(width);
+        let last_y = (((view.y() + view.height() as i32 - offset_y) / tile_height + 2).max(0) as u32).min(height);
+        let alpha = (layer.opacity.clamp(0.0, 1.0) * 255.0) as u8;
+
+        for y in first_y..last_y {
+            for x in first_x..last_x {
+                let cell = match layer.cell(x, y) {
+                    Some(cell) if !cell.is_empty() => cell,
+                    _ => continue,
+                };
+                let (tileset_index, local_id) = match self.map.tileset_for(cell.gid) {
+                    Some(found) => found,
+                    None => continue,
+                };
+                let tileset = &self.map.tilesets[tileset_index];
+                let (texture, src) = match self.tile_textures.get_mut(&cell.gid) {
+                    Some(texture) => {
+                        let query = texture.query();
+                        (texture, Rect::new(0, 0, query.width, query.height))
+                    }
+                    None => match self.textures[tileset_index].as_mut() {
+                        Some(texture) => (texture, tileset.source_rect(local_id)),
+                        None => continue,
+                    },
+                };
+                // Tiles are anchored at the bottom left of their cell
+                let world_rect = crate::two_d::Rect::new(
+                    x as i32 * tile_width + offset_x,
+                    (y as i32 + 1) * tile_height - src.height() as i32 + offset_y,
+                    src.width(),
+                    src.height(),
+                );
+                let dest = match camera {
+                    Some(camera) => match camera.transform_rect(&world_rect) {
+                        Some(dest) => dest,
+                        None => continue,
+                    },
+                    None => *world_rect.sdl_rect(),
+                };
+                let (angle, flip_horizontal, flip_vertical) = cell.copy_ex_params();
+                // A transposed tile is drawn with swapped sides and turned back into its cell
+                let dest = if cell.flip_diagonal && dest.width() != dest.height() {
+                    Rect::from_center(dest.center(), dest.height(), dest.width())
+                } else {
+                    dest
+                };
+                texture.set_alpha_mod(alpha);
+                canvas.copy_ex(texture, Some(src), Some(dest), angle, None, flip_horizontal, flip_vertical)?;
+            }
+        }
+        Ok(())
+    }
+}
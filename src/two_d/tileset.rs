@@ -0,0 +1,186 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use serde::Deserialize;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+// Typed value of a tile property, e.g. damage = 10, friction = 0.8, footstep = "wood.ogg", ladder = true
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum TileProperty {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+#[allow(dead_code)]
+impl TileProperty {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            TileProperty::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            TileProperty::Int(value) => Some(*value),
+            TileProperty::Float(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            TileProperty::Int(value) => Some(*value as f64),
+            TileProperty::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            TileProperty::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for TileProperty {
+    fn from(value: bool) -> Self {
+        TileProperty::Bool(value)
+    }
+}
+
+impl From<i64> for TileProperty {
+    fn from(value: i64) -> Self {
+        TileProperty::Int(value)
+    }
+}
+
+impl From<i32> for TileProperty {
+    fn from(value: i32) -> Self {
+        TileProperty::Int(value as i64)
+    }
+}
+
+impl From<f64> for TileProperty {
+    fn from(value: f64) -> Self {
+        TileProperty::Float(value)
+    }
+}
+
+impl From<f32> for TileProperty {
+    fn from(value: f32) -> Self {
+        TileProperty::Float(value as f64)
+    }
+}
+
+impl From<&str> for TileProperty {
+    fn from(value: &str) -> Self {
+        TileProperty::String(value.to_string())
+    }
+}
+
+impl From<String> for TileProperty {
+    fn from(value: String) -> Self {
+        TileProperty::String(value)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct TileFrame {
+    pub tile: u32,     // Tile id shown during this frame
+    pub duration: u32, // Milliseconds
+}
+
+// Frame sequence of an animated tile. All cells with the tile play in sync.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct TileAnimation {
+    pub frames: Vec<TileFrame>,
+}
+
+#[allow(dead_code)]
+impl TileAnimation {
+    pub fn new(frames: Vec<(u32, u32)>) -> Self {
+        Self { frames: frames.into_iter().map(|(tile, duration)| TileFrame { tile, duration }).collect() }
+    }
+
+    pub fn duration(&self) -> u32 {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+
+    // Tile id to show time_ms after the animation started, looping
+    pub fn frame_at(&self, time_ms: u32) -> Option<u32> {
+        let duration = self.duration();
+        if duration == 0 {
+            return self.frames.first().map(|frame| frame.tile);
+        }
+        let mut time = time_ms % duration;
+        for frame in &self.frames {
+            if time < frame.duration {
+                return Some(frame.tile);
+            }
+            time -= frame.duration;
+        }
+        self.frames.last().map(|frame| frame.tile)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TileDefinition {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub solid: Option<bool>, // None keeps what the tile config already says
+    #[serde(default)]
+    pub animation: Option<TileAnimation>,
+    #[serde(default)]
+    pub properties: HashMap<String, TileProperty>,
+}
+
+// Per tile id data of a tileset, usually loaded from JSON:
+// { "tile_size": [32, 32],
+//   "tiles": { "3": { "name": "ladder", "properties": { "ladder": true, "footstep": "wood.ogg" } },
+//              "5": { "solid": false, "animation": [ { "tile": 5, "duration": 150 }, { "tile": 6, "duration": 150 } ],
+//                     "properties": { "damage": 10 } } } }
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TilesetDefinition {
+    #[serde(default)]
+    pub tile_size: Option<(u32, u32)>,
+    #[serde(default)]
+    pub tiles: HashMap<u32, TileDefinition>,
+}
+
+#[allow(dead_code)]
+impl TilesetDefinition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_json(&json).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn tile(&self, tile_id: u32) -> Option<&TileDefinition> {
+        self.tiles.get(&tile_id)
+    }
+
+    // Id of a tile by its name, so game code does not hardcode ids
+    pub fn find(&self, name: &str) -> Option<u32> {
+        self.tiles.iter().find(|(_, tile)| tile.name == name).map(|(id, _)| *id)
+    }
+
+    pub fn property(&self, tile_id: u32, name: &str) -> Option<&TileProperty> {
+        self.tiles.get(&tile_id)?.properties.get(name)
+    }
+}
@@ -1,6 +1,10 @@
 extern crate sdl2;
 // extern  crate gl;
 
+use crate::two_d::draw;
+use crate::two_d::nine_slice::NineSlice;
+use crate::two_d::text::{self, SharedGlyphSource, TextAlign, TextOptions};
+
 use sdl2::render::Canvas;
 
 use serde::{Deserialize, Serialize};
@@ -64,6 +68,7 @@ pub struct Button<'a> {
     pub center: (i32, i32),
     pub radius: i32,
     on_click_callback: Box<dyn Fn() + 'a>, // Add this field
+    pub background: Option<NineSlice<'a>>, // Drawn instead of the rounded color rect
 }
 
 struct RcTextBoxWrapper<'a>(std::rc::Rc<TextBox<'a>>);
@@ -129,50 +134,27 @@ impl<'a> Serialize for Button<'a> {
 
 impl<'a> Button<'a> {
     pub fn new(text_box: std::rc::Rc<TextBox<'a>>, color: sdl2::pixels::Color, bg_rect: sdl2::rect::Rect, center: (i32, i32), radius: i32,on_click_callback: Box<dyn Fn() + 'a>,) -> Self {
-        Self { text_box, color, bg_rect, center, radius, on_click_callback, }
+        Self { text_box, color, bg_rect, center, radius, on_click_callback, background: None }
+    }
+
+    pub fn set_background(&mut self, background: NineSlice<'a>) {
+        self.background = Some(background);
     }
 
     pub fn is_pressed(&self, x: i32, y: i32) -> bool {
         self.bg_rect.contains_point(sdl2::rect::Point::new(x, y))
     }
     pub fn render(&self, canvas: &mut Canvas<sdl2::video::Window>, color_text: sdl2::pixels::Color) -> Result<(), String> {
-        canvas.set_draw_color(self.color);
-
-        // Draw the main rectangular body of the button
-        canvas.fill_rect(sdl2::rect::Rect::new(
-            self.bg_rect.x() + self.radius,
-            self.bg_rect.y(),
-            self.bg_rect.width() - 2 * self.radius as u32,
-            self.bg_rect.height(),
-        ))?;
-        canvas.fill_rect(sdl2::rect::Rect::new(
-            self.bg_rect.x(),
-            self.bg_rect.y() + self.radius,
-            self.bg_rect.width(),
-            self.bg_rect.height() - 2 * self.radius as u32,
-        ))?;
-
-        // Draw the rounded corners using filled circles
-        self.draw_filled_circle(canvas, self.bg_rect.x() + self.radius, self.bg_rect.y() + self.radius, self.radius)?; // top-left corner
-        self.draw_filled_circle(canvas, self.bg_rect.x() + self.bg_rect.width() as i32 - self.radius, self.bg_rect.y() + self.radius, self.radius)?; // top-right corner
-        self.draw_filled_circle(canvas, self.bg_rect.x() + self.radius, self.bg_rect.y() + self.bg_rect.height() as i32 - self.radius, self.radius)?; // bottom-left corner
-        self.draw_filled_circle(canvas, self.bg_rect.x() + self.bg_rect.width() as i32 - self.radius, self.bg_rect.y() + self.bg_rect.height() as i32 - self.radius, self.radius)?; // bottom-right corner
-        
+        // Draw the button body with rounded corners
+        match &self.background {
+            Some(background) => background.render(canvas, self.bg_rect)?,
+            None => draw::fill_rounded_rect(canvas, self.bg_rect, self.radius, self.color)?,
+        }
+
         canvas.set_draw_color(sdl2::pixels::Color::RGBA(0, 0, 0, 0)); // Reset the draw color
         self.text_box.render(canvas, color_text)
     }
 
-    fn draw_filled_circle(&self, canvas: &mut Canvas<sdl2::video::Window>, cx: i32, cy: i32, r: i32) -> Result<(), String> {
-        for y in -r..=r {
-            for x in -r..=r {
-                if x * x + y * y <= r * r {
-                    canvas.draw_point(sdl2::rect::Point::new(cx + x, cy + y))?;
-                }
-            }
-        }
-        Ok(())
-    }
-
     pub fn on_click(&self) {
         (self.on_click_callback)();
     }
@@ -228,8 +210,10 @@ impl<'de> Deserialize<'de> for RectWrapper {
 
 pub struct TextBox<'a> {
     pub text: String,
-    pub font: std::sync::Arc<sdl2::ttf::Font<'a, 'static>>,
+    pub font: Option<std::sync::Arc<sdl2::ttf::Font<'a, 'static>>>, // None when the text only draws through glyph_source
     pub rect: sdl2::rect::Rect,
+    pub glyph_source: Option<SharedGlyphSource<'a>>, // Cached glyphs (GlyphAtlas, BitmapFont) with wrapping, alignment and markup
+    pub options: TextOptions,
 }
 
 impl<'a> Serialize for TextBox<'a> {
@@ -248,7 +232,44 @@ impl<'a> Serialize for TextBox<'a> {
 #[allow(dead_code)]
 impl<'a> TextBox<'a> {
     pub fn new(text: String, font: std::sync::Arc<sdl2::ttf::Font<'a, 'static>>, rect: sdl2::rect::Rect) -> Self {
-        Self { text, font, rect }
+        Self { text, font: Some(font), rect, glyph_source: None, options: TextOptions::default() }
+    }
+
+    // For games without a TTF font, e.g. only a bitmap font
+    pub fn with_glyph_source(text: String, glyph_source: SharedGlyphSource<'a>, rect: sdl2::rect::Rect) -> Self {
+        let mut text_box = Self { text, font: None, rect, glyph_source: None, options: TextOptions::default() };
+        text_box.set_glyph_source(glyph_source);
+        text_box
+    }
+
+    fn font(&self) -> Result<&sdl2::ttf::Font<'a, 'static>, String> {
+        self.font.as_deref().ok_or_else(|| "TextBox has no font or glyph source".to_string())
+    }
+
+    // Draws through a glyph cache or a bitmap font instead of rendering a new texture every frame,
+    // the text wraps at the width of rect instead of being stretched into it
+    pub fn set_glyph_source(&mut self, glyph_source: SharedGlyphSource<'a>) {
+        self.glyph_source = Some(glyph_source);
+        self.options.max_width = Some(self.rect.width());
+    }
+
+    pub fn set_align(&mut self, align: TextAlign) {
+        self.options.align = align;
+    }
+
+    pub fn set_line_spacing(&mut self, line_spacing: f32) {
+        self.options.line_spacing = line_spacing;
+    }
+
+    pub fn set_markup(&mut self, markup: bool) {
+        self.options.markup = markup;
+    }
+
+    pub fn measure(&self) -> Result<(u32, u32), String> {
+        match &self.glyph_source {
+            Some(source) => text::measure_text(&mut *source.borrow_mut(), &self.text, &self.options),
+            None => self.font()?.size_of(&self.text).map_err(|e| e.to_string()),
+        }
     }
 
     // pub fn render(&self, canvas: &mut Canvas<sdl2::video::Window>) -> Result<(), String> {
@@ -265,7 +286,11 @@ impl<'a> TextBox<'a> {
     }
 
     pub fn render(&self, canvas: &mut Canvas<sdl2::video::Window>, color_text: sdl2::pixels::Color) -> Result<(), String> {
-        let surface = self.font.render(&self.text).blended(color_text).map_err(|e| e.to_string())?;
+        if let Some(source) = &self.glyph_source {
+            text::draw_text(canvas, &mut *source.borrow_mut(), &self.text, self.rect.x(), self.rect.y(), color_text, &self.options)?;
+            return Ok(());
+        }
+        let surface = self.font()?.render(&self.text).blended(color_text).map_err(|e| e.to_string())?;
         let texture_creator = canvas.texture_creator();
         let texture = texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string())?;
         canvas.copy(&texture, None, self.rect)?;
@@ -300,6 +325,10 @@ impl<'a> Checkbox<'a> {
         Self { button, checked: false }
     }
 
+    pub fn set_background(&mut self, background: NineSlice<'a>) {
+        self.button.set_background(background);
+    }
+
     pub fn render(&self, canvas: &mut Canvas<sdl2::video::Window>, color_text: sdl2::pixels::Color) -> Result<(), String> {
         self.button.render(canvas, color_text)?;
 
@@ -0,0 +1,125 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScalingMode {
+    Stretch,      // Fill the whole window, the aspect ratio is not kept
+    Letterbox,    // Largest size keeping the aspect ratio, black bars fill the rest
+    IntegerScale, // Largest whole multiple of the virtual size, pixel-perfect for pixel art
+}
+
+// Fixed size render target scaled onto the window. Games draw at e.g. 320x180 and
+// the same frame is shown on any window size.
+pub struct VirtualResolution<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub mode: ScalingMode,
+    pub border_color: sdl2::pixels::Color,
+    pub target: Texture<'a>,
+}
+
+#[allow(dead_code)]
+impl<'a> VirtualResolution<'a> {
+    pub fn new(
+        texture_creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>,
+        width: u32,
+        height: u32,
+        mode: ScalingMode,
+    ) -> Result<Self, String> {
+        // Nearest neighbour sampling keeps pixel art sharp, the hint applies to textures created afterwards
+        sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "0");
+        let target = texture_creator.create_texture_target(None, width, height).map_err(|e| e.to_string())?;
+        Ok(Self {
+            width,
+            height,
+            mode,
+            border_color: sdl2::pixels::Color::RGB(0, 0, 0),
+            target,
+        })
+    }
+
+    pub fn set_mode(&mut self, mode: ScalingMode) {
+        self.mode = mode;
+    }
+
+    pub fn set_border_color(&mut self, border_color: sdl2::pixels::Color) {
+        self.border_color = border_color;
+    }
+
+    // Area of the window the virtual screen is shown in
+    pub fn output_rect(&self, window_width: u32, window_height: u32) -> Rect {
+        scaled_rect((self.width, self.height), (window_width, window_height), self.mode)
+    }
+
+    // Calls draw with a canvas of the virtual size, then scales the result onto the window
+    pub fn render<F>(&mut self, canvas: &mut Canvas<sdl2::video::Window>, draw: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut Canvas<sdl2::video::Window>) -> Result<(), String>,
+    {
+        let mut result = Ok(());
+        canvas.with_texture_canvas(&mut self.target, |texture_canvas| {
+            result = draw(texture_canvas);
+        }).map_err(|e| e.to_string())?;
+        result?;
+
+        let (window_width, window_height) = canvas.output_size()?;
+        let dest = self.output_rect(window_width, window_height);
+        canvas.set_draw_color(self.border_color);
+        canvas.clear();
+        canvas.copy(&self.target, None, dest)
+    }
+
+    // x, y and the window size are renderer output pixels, None on the black bars
+    pub fn window_to_virtual(&self, x: i32, y: i32, window_width: u32, window_height: u32) -> Option<(i32, i32)> {
+        window_to_virtual((x, y), (self.width, self.height), (window_width, window_height), self.mode)
+    }
+}
+
+pub fn scaled_rect(virtual_size: (u32, u32), window_size: (u32, u32), mode: ScalingMode) -> Rect {
+    let (virtual_width, virtual_height) = (virtual_size.0.max(1), virtual_size.1.max(1));
+    let (window_width, window_height) = (window_size.0.max(1), window_size.1.max(1));
+    let (width, height) = match mode {
+        ScalingMode::Stretch => (window_width, window_height),
+        ScalingMode::Letterbox => {
+            let scale = (window_width as f32 / virtual_width as f32).min(window_height as f32 / virtual_height as f32);
+            (((virtual_width as f32 * scale) as u32).max(1), ((virtual_height as f32 * scale) as u32).max(1))
+        }
+        ScalingMode::IntegerScale => {
+            // Windows smaller than the virtual size still get scale 1, cropped by the window
+            let scale = (window_width / virtual_width).min(window_height / virtual_height).max(1);
+            (virtual_width * scale, virtual_height * scale)
+        }
+    };
+    Rect::new(
+        (window_width as i32 - width as i32) / 2,
+        (window_height as i32 - height as i32) / 2,
+        width,
+        height,
+    )
+}
+
+// Maps a window position (e.g. the mouse) into virtual coordinates. Positions on the
+// black bars, or cropped away by integer scaling, give None.
+pub fn window_to_virtual(position: (i32, i32), virtual_size: (u32, u32), window_size: (u32, u32), mode: ScalingMode) -> Option<(i32, i32)> {
+    let (x, y) = unclamped_window_to_virtual(position, virtual_size, window_size, mode);
+    if x < 0 || y < 0 || x >= virtual_size.0 as i32 || y >= virtual_size.1 as i32 {
+        return None;
+    }
+    Some((x, y))
+}
+
+// Like window_to_virtual, positions on the black bars move to the nearest edge of the view
+pub fn window_to_virtual_clamped(position: (i32, i32), virtual_size: (u32, u32), window_size: (u32, u32), mode: ScalingMode) -> (i32, i32) {
+    let (x, y) = unclamped_window_to_virtual(position, virtual_size, window_size, mode);
+    (x.clamp(0, virtual_size.0.max(1) as i32 - 1), y.clamp(0, virtual_size.1.max(1) as i32 - 1))
+}
+
+fn unclamped_window_to_virtual(position: (i32, i32), virtual_size: (u32, u32), window_size: (u32, u32), mode: ScalingMode) -> (i32, i32) {
+    let rect = scaled_rect(virtual_size, window_size, mode);
+    let x = ((position.0 - rect.x()) as f32 * virtual_size.0 as f32 / rect.width() as f32).floor() as i32;
+    let y = ((position.1 - rect.y()) as f32 * virtual_size.1 as f32 / rect.height() as f32).floor() as i32;
+    (x, y)
+}
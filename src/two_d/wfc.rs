@@ -0,0 +1,516 @@
+extern crate sdl2;
+// extern  crate gl;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use serde::Deserialize;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WfcDirection {
+    North,
+    East,
+    South,
+    West,
+}
+
+const DIRECTIONS: [WfcDirection; 4] = [WfcDirection::North, WfcDirection::East, WfcDirection::South, WfcDirection::West];
+
+#[allow(dead_code)]
+impl WfcDirection {
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            WfcDirection::North => (0, -1),
+            WfcDirection::East => (1, 0),
+            WfcDirection::South => (0, 1),
+            WfcDirection::West => (-1, 0),
+        }
+    }
+
+    pub fn opposite(&self) -> WfcDirection {
+        DIRECTIONS[(self.index() + 2) % 4]
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Deserialize)]
+struct RawTileRule {
+    tile: u32,
+    #[serde(default = "default_weight")]
+    weight: f64,
+    #[serde(default)]
+    north: Vec<u32>,
+    #[serde(default)]
+    east: Vec<u32>,
+    #[serde(default)]
+    south: Vec<u32>,
+    #[serde(default)]
+    west: Vec<u32>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize)]
+struct RawTileRules {
+    tiles: Vec<RawTileRule>,
+}
+
+// Patterns with their weights and which pattern may sit next to which.
+// In the simple tiled model a pattern is a single tile, in the overlapping model an n x n block of the sample.
+#[derive(Clone, Debug, Default)]
+pub struct WfcModel {
+    pub patterns: Vec<Vec<u32>>, // Row major, pattern_size * pattern_size tiles
+    pub pattern_size: usize,
+    pub weights: Vec<f64>,
+    propagator: Vec<[Vec<usize>; 4]>, // Pattern -> direction -> patterns allowed there
+}
+
+#[allow(dead_code)]
+impl WfcModel {
+    // Empty simple tiled model, filled with add_tile and allow
+    pub fn new() -> Self {
+        Self { pattern_size: 1, ..Default::default() }
+    }
+
+    pub fn add_tile(&mut self, tile: u32, weight: f64) -> usize {
+        if let Some(index) = self.pattern_of(tile) {
+            self.weights[index] = weight;
+            return index;
+        }
+        self.patterns.push(vec![tile]);
+        self.weights.push(weight);
+        self.propagator.push(Default::default());
+        self.patterns.len() - 1
+    }
+
+    // Lets tile b sit in the direction of tile a, and a in the opposite direction of b
+    pub fn allow(&mut self, a: u32, direction: WfcDirection, b: u32) -> Result<(), String> {
+        let first = self.pattern_of(a).ok_or(format!("Unknown tile {}", a))?;
+        let second = self.pattern_of(b).ok_or(format!("Unknown tile {}", b))?;
+        self.connect(first, direction, second);
+        Ok(())
+    }
+
+    // { "tiles": [ { "tile": 0, "weight": 4.0, "north": [0, 1], "east": [0], "south": [0, 1], "west": [0] },
+    //              { "tile": 1, "east": [1], "west": [1] } ] }
+    // Rules only need to be written once, the opposite direction is added automatically.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let raw: RawTileRules = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut model = Self::new();
+        for rule in &raw.tiles {
+            model.add_tile(rule.tile, rule.weight);
+        }
+        for rule in &raw.tiles {
+            for (direction, neighbours) in DIRECTIONS.iter().zip([&rule.north, &rule.east, &rule.south, &rule.west]) {
+                for &neighbour in neighbours {
+                    model.allow(rule.tile, *direction, neighbour)?;
+                }
+            }
+        }
+        Ok(model)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_json(&json).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // Simple tiled model learned from an example grid: weights are how often a tile occurs,
+    // and only neighbours seen in the sample are allowed
+    pub fn from_sample(sample: &[Vec<u32>], periodic: bool) -> Self {
+        let mut model = Self::new();
+        for &tile in sample.iter().flatten() {
+            match model.pattern_of(tile) {
+                Some(index) => model.weights[index] += 1.0,
+                None => {
+                    model.add_tile(tile, 1.0);
+                }
+            }
+        }
+        let height = sample.len();
+        for (y, row) in sample.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                for direction in [WfcDirection::East, WfcDirection::South] {
+                    let (dx, dy) = direction.offset();
+                    let (mut nx, mut ny) = (x + dx as usize, y + dy as usize);
+                    if periodic {
+                        ny %= height;
+                        nx %= sample[ny].len();
+                    }
+                    if let Some(&neighbour) = sample.get(ny).and_then(|row| row.get(nx)) {
+                        let (first, second) = (model.pattern_of(tile).unwrap_or(0), model.pattern_of(neighbour).unwrap_or(0));
+                        model.connect(first, direction, second);
+                    }
+                }
+            }
+        }
+        model
+    }
+
+    // Overlapping model: every n x n block of the sample is a pattern, two patterns fit next to each other
+    // when they agree where they overlap. symmetry 1..=8 also adds rotated and mirrored blocks.
+    pub fn overlapping(sample: &[Vec<u32>], n: usize, periodic_input: bool, symmetry: usize) -> Result<Self, String> {
+        let height = sample.len();
+        let width = sample.iter().map(|row| row.len()).min().unwrap_or(0);
+        if n == 0 || width < n || height < n {
+            return Err(format!("Sample of {}x{} is too small for {}x{} patterns", width, height, n, n));
+        }
+        let mut model = Self { pattern_size: n, ..Default::default() };
+        let mut index_of: HashMap<Vec<u32>, usize> = HashMap::new();
+        let (rows, columns) = if periodic_input { (height, width) } else { (height - n + 1, width - n + 1) };
+        for y in 0..rows {
+            for x in 0..columns {
+                let block: Vec<u32> = (0..n * n).map(|i| sample[(y + i / n) % height][(x + i % n) % width]).collect();
+                for variant in symmetries(&block, n).into_iter().take(symmetry.clamp(1, 8)) {
+                    match index_of.get(&variant) {
+                        Some(&index) => model.weights[index] += 1.0,
+                        None => {
+                            index_of.insert(variant.clone(), model.patterns.len());
+                            model.patterns.push(variant);
+                            model.weights.push(1.0);
+                        }
+                    }
+                }
+            }
+        }
+        model.propagator = vec![Default::default(); model.patterns.len()];
+        for first in 0..model.patterns.len() {
+            for direction in DIRECTIONS {
+                for second in 0..model.patterns.len() {
+                    if model.agrees(first, second, direction) {
+                        model.propagator[first][direction.index()].push(second);
+                    }
+                }
+            }
+        }
+        Ok(model)
+    }
+
+    pub fn pattern_of(&self, tile: u32) -> Option<usize> {
+        self.patterns.iter().position(|pattern| pattern.len() == 1 && pattern[0] == tile)
+    }
+
+    // Tile a pattern puts into the output grid, its top left one
+    pub fn tile(&self, pattern: usize) -> u32 {
+        self.patterns[pattern][0]
+    }
+
+    pub fn compatible(&self, pattern: usize, direction: WfcDirection) -> &[usize] {
+        &self.propagator[pattern][direction.index()]
+    }
+
+    fn connect(&mut self, first: usize, direction: WfcDirection, second: usize) {
+        if !self.propagator[first][direction.index()].contains(&second) {
+            self.propagator[first][direction.index()].push(second);
+        }
+        if !self.propagator[second][direction.opposite().index()].contains(&first) {
+            self.propagator[second][direction.opposite().index()].push(first);
+        }
+    }
+
+    // Whether pattern second, placed one cell in the direction of first, matches it on the overlap
+    fn agrees(&self, first: usize, second: usize, direction: WfcDirection) -> bool {
+        let n = self.pattern_size as i32;
+        let (dx, dy) = direction.offset();
+        let (a, b) = (&self.patterns[first], &self.patterns[second]);
+        for y in dy.max(0)..n.min(n + dy) {
+            for x in dx.max(0)..n.min(n + dx) {
+                if a[(x + y * n) as usize] != b[(x - dx + (y - dy) * n) as usize] {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+// The block, mirrored, then both rotated by 90, 180 and 270 degrees
+fn symmetries(block: &[u32], n: usize) -> Vec<Vec<u32>> {
+    let rotate = |p: &[u32]| -> Vec<u32> { (0..n * n).map(|i| p[n - 1 - i / n + (i % n) * n]).collect() };
+    let reflect = |p: &[u32]| -> Vec<u32> { (0..n * n).map(|i| p[n - 1 - i % n + (i / n) * n]).collect() };
+    let mut variants = Vec::with_capacity(8);
+    let mut current = block.to_vec();
+    for _ in 0..4 {
+        variants.push(current.clone());
+        variants.push(reflect(&current));
+        current = rotate(&current);
+    }
+    variants
+}
+
+#[derive(Clone)]
+struct WaveState {
+    wave: Vec<Vec<bool>>, // Cell -> pattern still possible
+    counts: Vec<usize>,
+}
+
+// Fills a grid so every pair of neighbouring cells follows the model, picking the most constrained cell first.
+// Contradictions undo the last choices instead of starting over.
+pub struct WaveFunctionCollapse {
+    pub model: WfcModel,
+    pub width: usize,
+    pub height: usize,
+    pub seed: u64,
+    pub periodic: bool,         // Output wraps around, e.g. for seamless textures
+    pub max_backtracks: usize,  // Per attempt
+    pub history: usize,         // Choices remembered for backtracking
+    pub attempts: usize,        // Restarts once backtracking gives up
+    pub border: Option<u32>,    // Tile forced on all edge cells
+    constraints: Vec<(usize, usize, Vec<u32>)>,
+}
+
+#[allow(dead_code)]
+impl WaveFunctionCollapse {
+    pub fn new(model: WfcModel, width: usize, height: usize, seed: u64) -> Self {
+        Self {
+            model,
+            width,
+            height,
+            seed,
+            periodic: false,
+            max_backtracks: 1000,
+            history: 64,
+            attempts: 5,
+            border: None,
+            constraints: Vec::new(),
+        }
+    }
+
+    pub fn set_periodic(&mut self, periodic: bool) {
+        self.periodic = periodic;
+    }
+
+    pub fn set_border(&mut self, tile: Option<u32>) {
+        self.border = tile;
+    }
+
+    pub fn set_backtracking(&mut self, max_backtracks: usize, history: usize) {
+        self.max_backtracks = max_backtracks;
+        self.history = history.max(1);
+    }
+
+    // Forces a tile, e.g. an entrance on the left edge
+    pub fn fix_tile(&mut self, x: usize, y: usize, tile: u32) {
+        self.constraints.push((x, y, vec![tile]));
+    }
+
+    // Limits a cell to a few tiles
+    pub fn restrict(&mut self, x: usize, y: usize, tiles: Vec<u32>) {
+        self.constraints.push((x, y, tiles));
+    }
+
+    pub fn clear_constraints(&mut self) {
+        self.constraints.clear();
+        self.border = None;
+    }
+
+    pub fn generate(&self) -> Result<Vec<Vec<u32>>, String> {
+        let pattern_count = self.model.patterns.len();
+        if pattern_count == 0 || self.width == 0 || self.height == 0 {
+            return Err("Nothing to generate, the model has no patterns".to_string());
+        }
+        if !self.periodic && (self.width < self.model.pattern_size || self.height < self.model.pattern_size) {
+            return Err(format!("Output of {}x{} is smaller than the patterns", self.width, self.height));
+        }
+        let initial = self.constrained_state()?;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        for _ in 0..self.attempts.max(1) {
+            if let Some(state) = self.run(initial.clone(), &mut rng) {
+                return Ok(self.output(&state));
+            }
+        }
+        Err(format!("No solution found in {} attempts", self.attempts.max(1)))
+    }
+
+    // Without wrapping, overlapping patterns must not hang over the edge, so there are fewer pattern
+    // cells than output cells and the last rows and columns come from the rest of the last patterns
+    fn wave_size(&self) -> (usize, usize) {
+        if self.periodic {
+            return (self.width, self.height);
+        }
+        let overhang = self.model.pattern_size.max(1) - 1;
+        (self.width - overhang, self.height - overhang)
+    }
+
+    // Pattern cell covering an output cell and the index of the tile inside that pattern
+    fn locate(&self, x: usize, y: usize) -> (usize, usize) {
+        let (wave_width, wave_height) = self.wave_size();
+        let (px, py) = (x.min(wave_width - 1), y.min(wave_height - 1));
+        (px + py * wave_width, (x - px) + (y - py) * self.model.pattern_size)
+    }
+
+    fn constrained_state(&self) -> Result<WaveState, String> {
+        let pattern_count = self.model.patterns.len();
+        let (wave_width, wave_height) = self.wave_size();
+        let cells = wave_width * wave_height;
+        let mut state = WaveState { wave: vec![vec![true; pattern_count]; cells], counts: vec![pattern_count; cells] };
+        let mut queue = Vec::new();
+        let mut restrict = |state: &mut WaveState, x: usize, y: usize, tiles: &[u32]| {
+            if x >= self.width || y >= self.height {
+                return;
+            }
+            let (cell, offset) = self.locate(x, y);
+            for pattern in 0..pattern_count {
+                if state.wave[cell][pattern] && !tiles.contains(&self.model.patterns[pattern][offset]) {
+                    state.wave[cell][pattern] = false;
+                    state.counts[cell] -= 1;
+                }
+            }
+            queue.push(cell);
+        };
+        if let Some(border) = self.border {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    if x == 0 || y == 0 || x + 1 == self.width || y + 1 == self.height {
+                        restrict(&mut state, x, y, &[border]);
+                    }
+                }
+            }
+        }
+        for (x, y, tiles) in &self.constraints {
+            restrict(&mut state, *x, *y, tiles);
+        }
+        if !self.propagate(&mut state, queue) {
+            return Err("The constraints contradict the model".to_string());
+        }
+        Ok(state)
+    }
+
+    fn run(&self, mut state: WaveState, rng: &mut StdRng) -> Option<WaveState> {
+        let mut choices: Vec<(WaveState, usize, usize)> = Vec::new();
+        let mut backtracks = 0;
+        while let Some(cell) = self.lowest_entropy(&state, rng) {
+            let pattern = self.pick(&state, cell, rng);
+            choices.push((state.clone(), cell, pattern));
+            if choices.len() > self.history {
+                choices.remove(0);
+            }
+            for other in 0..self.model.patterns.len() {
+                state.wave[cell][other] = other == pattern;
+            }
+            state.counts[cell] = 1;
+            let mut queue = vec![cell];
+            // On a contradiction go back to the last choice and rule that pattern out there
+            while !self.propagate(&mut state, queue) {
+                backtracks += 1;
+                let (saved, cell, pattern) = choices.pop()?;
+                if backtracks > self.max_backtracks {
+                    return None;
+                }
+                state = saved;
+                state.wave[cell][pattern] = false;
+                state.counts[cell] -= 1;
+                queue = vec![cell];
+            }
+        }
+        Some(state)
+    }
+
+    fn neighbour(&self, cell: usize, direction: WfcDirection) -> Option<usize> {
+        let (wave_width, wave_height) = self.wave_size();
+        let (dx, dy) = direction.offset();
+        let (mut x, mut y) = ((cell % wave_width) as i32 + dx, (cell / wave_width) as i32 + dy);
+        if self.periodic {
+            x = x.rem_euclid(wave_width as i32);
+            y = y.rem_euclid(wave_height as i32);
+        } else if x < 0 || y < 0 || x >= wave_width as i32 || y >= wave_height as i32 {
+            return None;
+        }
+        Some(x as usize + y as usize * wave_width)
+    }
+
+    // Removes patterns that no longer fit next to the changed cells, false on a contradiction
+    fn propagate(&self, state: &mut WaveState, mut queue: Vec<usize>) -> bool {
+        let pattern_count = self.model.patterns.len();
+        let mut allowed = vec![false; pattern_count];
+        while let Some(cell) = queue.pop() {
+            if state.counts[cell] == 0 {
+                return false;
+            }
+            for direction in DIRECTIONS {
+                let neighbour = match self.neighbour(cell, direction) {
+                    Some(neighbour) => neighbour,
+                    None => continue,
+                };
+                allowed.iter_mut().for_each(|allowed| *allowed = false);
+                for pattern in (0..pattern_count).filter(|&pattern| state.wave[cell][pattern]) {
+                    for &other in self.model.compatible(pattern, direction) {
+                        allowed[other] = true;
+                    }
+                }
+                let mut changed = false;
+                for (other, &is_allowed) in allowed.iter().enumerate() {
+                    if state.wave[neighbour][other] && !is_allowed {
+                        state.wave[neighbour][other] = false;
+                        state.counts[neighbour] -= 1;
+                        changed = true;
+                    }
+                }
+                if changed {
+                    if state.counts[neighbour] == 0 {
+                        return false;
+                    }
+                    queue.push(neighbour);
+                }
+            }
+        }
+        true
+    }
+
+    // Undecided cell with the lowest Shannon entropy, a little noise breaks ties
+    fn lowest_entropy(&self, state: &WaveState, rng: &mut StdRng) -> Option<usize> {
+        let mut best: Option<(f64, usize)> = None;
+        for cell in 0..state.wave.len() {
+            if state.counts[cell] <= 1 {
+                continue;
+            }
+            let (mut sum, mut sum_log) = (0.0, 0.0);
+            for (pattern, &weight) in self.model.weights.iter().enumerate() {
+                if state.wave[cell][pattern] && weight > 0.0 {
+                    sum += weight;
+                    sum_log += weight * weight.ln();
+                }
+            }
+            let entropy = if sum > 0.0 { sum.ln() - sum_log / sum } else { 0.0 } + rng.gen::<f64>() * 1e-6;
+            if best.is_none_or(|(lowest, _)| entropy < lowest) {
+                best = Some((entropy, cell));
+            }
+        }
+        best.map(|(_, cell)| cell)
+    }
+
+    fn pick(&self, state: &WaveState, cell: usize, rng: &mut StdRng) -> usize {
+        let possible: Vec<usize> = (0..self.model.patterns.len()).filter(|&pattern| state.wave[cell][pattern]).collect();
+        let total: f64 = possible.iter().map(|&pattern| self.model.weights[pattern].max(0.0)).sum();
+        if total <= 0.0 {
+            return possible[rng.gen_range(0..possible.len())];
+        }
+        let mut roll = rng.gen::<f64>() * total;
+        for &pattern in &possible {
+            roll -= self.model.weights[pattern].max(0.0);
+            if roll <= 0.0 {
+                return pattern;
+            }
+        }
+        *possible.last().unwrap_or(&0)
+    }
+
+    fn output(&self, state: &WaveState) -> Vec<Vec<u32>> {
+        (0..self.height)
+            .map(|y| (0..self.width)
+                .map(|x| {
+                    let (cell, offset) = self.locate(x, y);
+                    state.wave[cell].iter().position(|&possible| possible).map_or(0, |pattern| self.model.patterns[pattern][offset])
+                })
+                .collect())
+            .collect()
+    }
+}
@@ -39,4 +39,12 @@ impl Window {
 
         Ok(Self { sdl_context, video_subsystem, canvas })
     }
+
+    // Lets players resize the window, useful together with VirtualResolution
+    pub fn set_resizable(&mut self, resizable: bool) {
+        let resizable = if resizable { sdl2_sys::SDL_bool::SDL_TRUE } else { sdl2_sys::SDL_bool::SDL_FALSE };
+        unsafe {
+            sdl2_sys::SDL_SetWindowResizable(self.canvas.window().raw(), resizable);
+        }
+    }
 }
\ No newline at end of file